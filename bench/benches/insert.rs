@@ -28,7 +28,7 @@ fn append_lorem(repeat: usize) -> usize {
         });
         term.draw_textarea(&textarea);
     }
-    textarea.lines().len()
+    textarea.lines().count()
 }
 
 #[inline]
@@ -39,7 +39,7 @@ fn random_lorem(repeat: usize) -> usize {
 
     for _ in 0..repeat {
         for line in LOREM {
-            let row = rng.gen_range(0..textarea.lines().len() as u16);
+            let row = rng.gen_range(0..textarea.lines().count() as u16);
             textarea.move_cursor(CursorMove::Jump(row, 0));
             textarea.move_cursor(CursorMove::End);
 
@@ -63,7 +63,7 @@ fn random_lorem(repeat: usize) -> usize {
         }
     }
 
-    textarea.lines().len()
+    textarea.lines().count()
 }
 
 #[inline]
@@ -85,7 +85,7 @@ fn append_long_lorem(repeat: usize) -> usize {
         }
     }
 
-    textarea.lines().len()
+    textarea.lines().count()
 }
 
 fn append(c: &mut Criterion) {
@@ -1,5 +1,5 @@
-use automerge::AutoCommit;
-use autosurgeon::{Hydrate, Reconcile, hydrate, reconcile};
+use automerge::transaction::Transactable as _;
+use automerge::{AutoCommit, ObjId, ObjType, ROOT};
 use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::widgets::{Block, Borders};
@@ -38,15 +38,13 @@ fn main() -> io::Result<()> {
     let mut term = ratatui::init();
 
     let mut doc1 = AutoCommit::new();
-    let state1 = State {
-        text: autosurgeon::Text::with_value("The fox jumped."),
-    };
-    reconcile(&mut doc1, state1).unwrap();
+    let obj = doc1.put_object(ROOT, "text", ObjType::Text).unwrap();
+    doc1.splice_text(&obj, 0, 0, "The fox jumped.").unwrap();
 
     let doc2 = doc1.fork();
 
-    let editor1 = Editor::new(doc1);
-    let editor2 = Editor::new(doc2);
+    let editor1 = Editor::new(doc1, obj.clone());
+    let editor2 = Editor::new(doc2, obj);
     let mut editors = [editor1, editor2];
 
     let layout = Layout::default()
@@ -90,13 +88,7 @@ fn main() -> io::Result<()> {
                     (&mut right[0], &mut left[0])
                 };
 
-                curr.reconcile();
-                other.reconcile();
-
                 curr.merge(other);
-
-                // this prevents 'StaleHeads' panic
-                other.update_from_doc();
             }
 
             input => {
@@ -111,43 +103,18 @@ fn main() -> io::Result<()> {
 }
 
 struct Editor<'a> {
-    doc: AutoCommit,
     textarea: MergeArea<'a>,
 }
 
-#[derive(Hydrate, Reconcile)]
-struct State {
-    text: autosurgeon::Text,
-}
-
 impl Editor<'_> {
-    fn new(doc: AutoCommit) -> Self {
-        let state: State = hydrate(&doc).unwrap();
-
+    fn new(doc: AutoCommit, obj: ObjId) -> Self {
         Self {
-            doc,
-            textarea: MergeArea::new(state.text),
+            textarea: MergeArea::from_doc(doc, obj).unwrap(),
         }
     }
 
-    fn text(&self) -> &autosurgeon::Text {
-        self.textarea.text()
-    }
-
     fn merge(&mut self, other: &mut Editor) {
-        self.doc.merge(&mut other.doc).unwrap();
-        self.update_from_doc();
-    }
-
-    fn update_from_doc(&mut self) {
-        let state: State = hydrate(&self.doc).unwrap();
-        self.textarea.set_text(state.text);
-    }
-
-    fn reconcile(&mut self) {
-        let state = State {
-            text: self.text().clone(),
-        };
-        reconcile(&mut self.doc, &state).unwrap();
+        let other = other.textarea.doc_mut().unwrap();
+        self.textarea.merge(other).unwrap();
     }
 }
@@ -138,7 +138,7 @@ impl Vim {
                         ..
                     } => {
                         textarea.delete_line_by_end();
-                        textarea.cancel_selection_v2();
+                        textarea.cancel_selection();
                         return Transition::Mode(Mode::Insert);
                     }
                     Input {
@@ -161,7 +161,7 @@ impl Vim {
                         ctrl: true,
                         ..
                     } => {
-                        textarea.redo_v2();
+                        textarea.redo();
                         return Transition::Mode(Mode::Normal);
                     }
                     Input {
@@ -175,14 +175,14 @@ impl Vim {
                         key: Key::Char('i'),
                         ..
                     } => {
-                        textarea.cancel_selection_v2();
+                        textarea.cancel_selection();
                         return Transition::Mode(Mode::Insert);
                     }
                     Input {
                         key: Key::Char('a'),
                         ..
                     } => {
-                        textarea.cancel_selection_v2();
+                        textarea.cancel_selection();
                         textarea.move_cursor(CursorMove::Forward);
                         return Transition::Mode(Mode::Insert);
                     }
@@ -190,7 +190,7 @@ impl Vim {
                         key: Key::Char('A'),
                         ..
                     } => {
-                        textarea.cancel_selection_v2();
+                        textarea.cancel_selection();
                         textarea.move_cursor(CursorMove::End);
                         return Transition::Mode(Mode::Insert);
                     }
@@ -215,7 +215,7 @@ impl Vim {
                         key: Key::Char('I'),
                         ..
                     } => {
-                        textarea.cancel_selection_v2();
+                        textarea.cancel_selection();
                         textarea.move_cursor(CursorMove::Head);
                         return Transition::Mode(Mode::Insert);
                     }
@@ -277,7 +277,7 @@ impl Vim {
                         ctrl: false,
                         ..
                     } if self.mode == Mode::Visual => {
-                        textarea.cancel_selection_v2();
+                        textarea.cancel_selection();
                         return Transition::Mode(Mode::Normal);
                     }
                     Input {
@@ -0,0 +1,26 @@
+use crate::document::Document;
+use crate::ratatui::style::Style;
+use crate::util::SharedFn;
+use automerge::ActorId;
+use std::sync::Arc;
+
+// Styles the text by the actor who inserted it
+#[derive(Clone, Debug)]
+pub struct Blame(SharedFn<dyn Fn(&ActorId) -> Style + Send + Sync>);
+
+impl Blame {
+    pub fn new(style: impl Fn(&ActorId) -> Style + Send + Sync + 'static) -> Self {
+        Self(SharedFn::new(Arc::new(style)))
+    }
+
+    // Styled runs of chars between the char offsets `start` and `end`. Nothing is styled when no document is attached.
+    pub fn runs(&self, text: &Document, start: usize, end: usize) -> Vec<(usize, usize, Style)> {
+        let Some(authors) = text.authors(start, end) else {
+            return vec![];
+        };
+        authors
+            .into_iter()
+            .map(|(start, end, actor)| (start, end, (self.0)(&actor)))
+            .collect()
+    }
+}
@@ -0,0 +1,58 @@
+use automerge::marks::Mark;
+use std::cmp;
+
+// Comments are stored as marks of the text object whose names are this prefix followed by the comment IDs. Each comment
+// has its own mark name so that the ranges of comments can overlap.
+const PREFIX: &str = "comment:";
+
+pub fn mark_name(id: &str) -> String {
+    format!("{}{}", PREFIX, id)
+}
+
+pub fn is_comment_mark(name: &str) -> bool {
+    name.starts_with(PREFIX)
+}
+
+/// A comment thread anchored to a range of the text. The range is stored in the automerge document bound to the
+/// textarea, so it follows the commented text while the text is edited locally or merged with changes of other peers.
+/// Add comments with [`crate::MergeArea::add_comment`] and query them with [`crate::MergeArea::comments_at`].
+///
+/// Only the anchor of the thread is stored in the text object. Host applications keep the messages of the thread under
+/// its ID.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment {
+    /// ID of the comment thread.
+    pub id: String,
+    /// Char offsets of the start and the end (exclusive) of the commented text.
+    pub range: (usize, usize),
+}
+
+impl Comment {
+    /// Return whether the char offset is in the commented text or at the edges of it.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.range.0 <= offset && offset <= self.range.1
+    }
+}
+
+// Collect comments from the marks. When the text of a comment was split by other marks or partially unmarked, the
+// comment spans all of the pieces. Comments are sorted by their ranges.
+pub fn from_marks(marks: Vec<Mark<'_>>) -> Vec<Comment> {
+    let mut comments: Vec<Comment> = vec![];
+    for mark in marks {
+        let Some(id) = mark.name().strip_prefix(PREFIX) else {
+            continue;
+        };
+        match comments.iter_mut().find(|c| c.id == id) {
+            Some(c) => {
+                c.range.0 = cmp::min(c.range.0, mark.start);
+                c.range.1 = cmp::max(c.range.1, mark.end);
+            }
+            None => comments.push(Comment {
+                id: id.to_string(),
+                range: (mark.start, mark.end),
+            }),
+        }
+    }
+    comments.sort_by(|l, r| l.range.cmp(&r.range).then_with(|| l.id.cmp(&r.id)));
+    comments
+}
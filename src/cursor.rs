@@ -1,6 +1,7 @@
 use crate::word::{
     find_word_inclusive_end_forward, find_word_start_backward_v2, find_word_start_forward,
 };
+use crate::wrap::{self, ScreenRows, Wrapper};
 use crate::{util, widget::Viewport};
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
@@ -187,7 +188,7 @@ pub enum CursorMove {
     /// - cursor at line 12 is not moved
     ///
     /// This is useful when you moved a cursor but you don't want to move the viewport.
-    /// ```ignore
+    /// ```
     /// # use ratatui::buffer::Buffer;
     /// # use ratatui::layout::Rect;
     /// # use ratatui::widgets::Widget as _;
@@ -213,6 +214,137 @@ pub enum CursorMove {
     /// assert_eq!(textarea.cursor2(), (7, 0));
     /// ```
     InViewport,
+    /// Move cursor to the start of the next region edited concurrently by several peers. The regions are detected
+    /// after merges while [`crate::MergeArea::set_concurrent_edit_style`] is enabled. When no region follows the
+    /// cursor, the cursor is not moved.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+    /// use ratatui::style::Style;
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new().with_actor(ActorId::from(b"alice"));
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "Hi !").unwrap();
+    /// let mut remote = doc.fork().with_actor(ActorId::from(b"bob"));
+    ///
+    /// let mut textarea = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    /// textarea.set_concurrent_edit_style(Style::default());
+    ///
+    /// // Both peers greet at the same spot
+    /// textarea.move_cursor(CursorMove::Jump(0, 3));
+    /// textarea.insert_str("Bob");
+    /// remote.splice_text(&obj, 3, 0, "Alice").unwrap();
+    /// textarea.merge(&mut remote).unwrap();
+    ///
+    /// textarea.move_cursor(CursorMove::Head);
+    /// textarea.move_cursor(CursorMove::NextConcurrentEdit);
+    /// assert_eq!(textarea.cursor2(), (0, 3));
+    /// ```
+    NextConcurrentEdit,
+    /// Move cursor to the start of the previous region edited concurrently by several peers. When no region precedes
+    /// the cursor, the cursor is not moved.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+    /// use ratatui::style::Style;
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new().with_actor(ActorId::from(b"alice"));
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "Hi !").unwrap();
+    /// let mut remote = doc.fork().with_actor(ActorId::from(b"bob"));
+    ///
+    /// let mut textarea = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    /// textarea.set_concurrent_edit_style(Style::default());
+    ///
+    /// textarea.move_cursor(CursorMove::Jump(0, 3));
+    /// textarea.insert_str("Bob");
+    /// remote.splice_text(&obj, 3, 0, "Alice").unwrap();
+    /// textarea.merge(&mut remote).unwrap();
+    ///
+    /// textarea.move_cursor(CursorMove::End);
+    /// textarea.move_cursor(CursorMove::PrevConcurrentEdit);
+    /// assert_eq!(textarea.cursor2(), (0, 3));
+    /// ```
+    PrevConcurrentEdit,
+    /// Move cursor to the start of the next diagnostic set by [`crate::MergeArea::set_diagnostics`]. When no diagnostic
+    /// follows the cursor, the cursor is not moved.
+    /// ```
+    /// use ratatui_mergearea::{CursorMove, Diagnostic, MergeArea, Severity};
+    ///
+    /// let mut textarea = MergeArea::with_value("a b\nc d");
+    /// let warn = |range| Diagnostic { range, severity: Severity::Warning, message: String::new() };
+    /// textarea.set_diagnostics(vec![warn((6, 7)), warn((2, 3))]);
+    ///
+    /// textarea.move_cursor(CursorMove::NextDiagnostic);
+    /// assert_eq!(textarea.cursor2(), (0, 2));
+    /// textarea.move_cursor(CursorMove::NextDiagnostic);
+    /// assert_eq!(textarea.cursor2(), (1, 2));
+    /// textarea.move_cursor(CursorMove::NextDiagnostic);
+    /// assert_eq!(textarea.cursor2(), (1, 2));
+    /// ```
+    NextDiagnostic,
+    /// Move cursor to the start of the previous diagnostic set by [`crate::MergeArea::set_diagnostics`]. When no
+    /// diagnostic precedes the cursor, the cursor is not moved.
+    /// ```
+    /// use ratatui_mergearea::{CursorMove, Diagnostic, MergeArea, Severity};
+    ///
+    /// let mut textarea = MergeArea::with_value("a b\nc d");
+    /// let error = |range| Diagnostic { range, severity: Severity::Error, message: String::new() };
+    /// textarea.set_diagnostics(vec![error((0, 1)), error((6, 7))]);
+    ///
+    /// textarea.move_cursor(CursorMove::Bottom);
+    /// textarea.move_cursor(CursorMove::End);
+    /// textarea.move_cursor(CursorMove::PrevDiagnostic);
+    /// assert_eq!(textarea.cursor2(), (1, 2));
+    /// textarea.move_cursor(CursorMove::PrevDiagnostic);
+    /// assert_eq!(textarea.cursor2(), (0, 0));
+    /// ```
+    PrevDiagnostic,
+}
+
+// State of the textarea which some cursor movements depend on.
+pub(crate) struct CursorContext<'a> {
+    pub viewport: &'a Viewport,
+    // Sorted char ranges of the text edited concurrently by several peers
+    pub concurrent_edits: &'a [(usize, usize)],
+    // Sorted char ranges of the diagnostics
+    pub diagnostics: &'a [(usize, usize)],
+    // Set with the rows on the screen when lines are wrapped or folded. Vertical movements are then by the rows
+    pub wrap: Option<(Wrapper, ScreenRows)>,
+}
+
+// Vertical movements by the rows on the screen when lines are wrapped or folded. The cursor keeps its display column in
+// the row, so it steps over the lines hidden by folds. The viewport is scrolled by the rows on the screen.
+fn next_cursor_wrapped(
+    m: CursorMove,
+    offset: usize,
+    text: &str,
+    (w, screen): &(Wrapper, ScreenRows),
+    ctx: &CursorContext<'_>,
+) -> Option<usize> {
+    let rows = &screen.rows;
+    let i = wrap::row_index(rows, offset);
+    let j = match m {
+        CursorMove::Up => i.checked_sub(1)?,
+        CursorMove::Down => Some(i + 1).filter(|&j| j < rows.len())?,
+        CursorMove::InViewport => {
+            let (row_top, _, row_bottom, _) = ctx.viewport.position();
+            cmp::min(
+                (i as u16).clamp(row_top, row_bottom) as usize,
+                rows.len() - 1,
+            )
+        }
+        _ => unreachable!(),
+    };
+    let mut col = w.col_at(&screen.chars(text, i), &rows[i], offset.min(rows[i].end));
+    if m == CursorMove::InViewport && !w.wraps() {
+        // Folded lines are still scrolled horizontally
+        let (_, col_top, _, col_bottom) = ctx.viewport.position();
+        col = col.clamp(col_top as usize, col_bottom as usize);
+    }
+    Some(w.offset_at(&screen.chars(text, j), &rows[j], col))
 }
 
 impl CursorMove {
@@ -220,7 +352,7 @@ impl CursorMove {
         &self,
         offset: usize,
         text: &autosurgeon::Text,
-        viewport: &Viewport,
+        ctx: &CursorContext<'_>,
     ) -> Option<usize> {
         use CursorMove::*;
 
@@ -236,6 +368,10 @@ impl CursorMove {
             current_line_start
         }
 
+        if let (Up | Down | InViewport, Some(w)) = (self, &ctx.wrap) {
+            return next_cursor_wrapped(*self, offset, text.as_str(), w, ctx);
+        }
+
         match self {
             Forward => {
                 if offset >= clen(text) {
@@ -299,20 +435,20 @@ impl CursorMove {
                 if chars.is_empty() {
                     None
                 } else {
-                    Some(util::find_line_end(offset, &chars) + 1)
+                    Some(util::find_line_end(offset, &chars))
                 }
             }
             Top => {
                 let chars = text.as_str().chars().collect::<Vec<_>>();
                 let line_start = find_line_start(offset, &chars);
                 let col = offset - line_start;
-                CursorMove::Jump(0, col as u16).next_cursor(offset, text, viewport)
+                CursorMove::Jump(0, col as u16).next_cursor(offset, text, ctx)
             }
             Bottom => {
                 let chars = text.as_str().chars().collect::<Vec<_>>();
                 let line_start = find_line_start(offset, &chars);
                 let col = offset - line_start;
-                CursorMove::Jump(u16::MAX, col as u16).next_cursor(offset, text, viewport)
+                CursorMove::Jump(u16::MAX, col as u16).next_cursor(offset, text, ctx)
             }
             WordForward => match find_word_start_forward(text.as_str(), offset) {
                 None => find_word_inclusive_end_forward(text.as_str(), offset + 1),
@@ -325,19 +461,8 @@ impl CursorMove {
 
                 let mut curr_row = 0;
                 let mut index = 0;
-                let max_row = {
-                    let mut nls = chars
-                        .iter()
-                        .filter(|c| **c == '\n')
-                        .count()
-                        .saturating_sub(1);
-                    if let Some(last) = chars.last() {
-                        if *last != '\n' {
-                            nls += 1;
-                        }
-                    }
-                    nls
-                };
+                // The text ending with a newline has an empty last line
+                let max_row = chars.iter().filter(|c| **c == '\n').count();
                 let row = cmp::min(max_row, *row as usize);
 
                 while curr_row != row && index < chars.len() {
@@ -360,14 +485,36 @@ impl CursorMove {
                 Some(index)
             }
             InViewport => {
-                let (row_top, col_top, row_bottom, col_bottom) = viewport.position();
-
-                let top = Self::Jump(row_top, col_top).next_cursor(offset, text, viewport)?;
-                let bottom =
-                    Self::Jump(row_bottom, col_bottom).next_cursor(offset, text, viewport)?;
+                let (row_top, col_top, row_bottom, col_bottom) = ctx.viewport.position();
+                let (row, col) = util::cursor_for(text.as_str(), offset);
 
-                Some(offset.clamp(top, bottom))
+                let row = (row as u16).clamp(row_top, row_bottom);
+                let col = (col as u16).clamp(col_top, col_bottom);
+                Self::Jump(row, col).next_cursor(offset, text, ctx)
             }
+            NextConcurrentEdit => ctx
+                .concurrent_edits
+                .iter()
+                .map(|&(start, _)| start)
+                .find(|&start| start > offset),
+            PrevConcurrentEdit => ctx
+                .concurrent_edits
+                .iter()
+                .rev()
+                .map(|&(start, _)| start)
+                .find(|&start| start < offset),
+            // Diagnostics may be set for a longer text, so the jump is clamped to the end of text
+            NextDiagnostic => ctx
+                .diagnostics
+                .iter()
+                .map(|&(start, _)| cmp::min(start, clen(text)))
+                .find(|&start| start > offset),
+            PrevDiagnostic => ctx
+                .diagnostics
+                .iter()
+                .rev()
+                .map(|&(start, _)| cmp::min(start, clen(text)))
+                .find(|&start| start < offset),
         }
     }
 }
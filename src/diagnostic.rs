@@ -0,0 +1,71 @@
+use crate::ratatui::style::{Color, Modifier, Style};
+
+/// Severity of a [`Diagnostic`]. Severities are ordered from the most severe one, so the minimum severity of the
+/// diagnostics on a line is the one shown in the sign column.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// An error. The default style is red and underlined.
+    Error,
+    /// A warning. The default style is yellow and underlined.
+    Warning,
+    /// An information. The default style is blue and underlined.
+    Info,
+    /// A hint. The default style is cyan and underlined.
+    Hint,
+}
+
+impl Severity {
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+
+    // Sign of the severity in the sign column. It must be one column wide
+    pub(crate) fn sign(self) -> &'static str {
+        match self {
+            Severity::Error => "E",
+            Severity::Warning => "W",
+            Severity::Info => "I",
+            Severity::Hint => "H",
+        }
+    }
+}
+
+// Default styles of the severities in the order of their variants
+pub const DEFAULT_STYLES: [Style; 4] = [
+    Style::new()
+        .fg(Color::Red)
+        .add_modifier(Modifier::UNDERLINED),
+    Style::new()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::UNDERLINED),
+    Style::new()
+        .fg(Color::Blue)
+        .add_modifier(Modifier::UNDERLINED),
+    Style::new()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::UNDERLINED),
+];
+
+/// A message about a range of the text such as an error reported by a linter. Set diagnostics with
+/// [`crate::MergeArea::set_diagnostics`].
+///
+/// The range follows the text while the text is edited locally or merged with changes of other peers, until the
+/// diagnostics are set again. When the text of the range is deleted entirely, the range becomes empty at the position
+/// where the text was.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Char offsets of the start and the end (exclusive) of the diagnosed text.
+    pub range: (usize, usize),
+    /// Severity of the diagnostic.
+    pub severity: Severity,
+    /// Message of the diagnostic.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Return whether the char offset is in the diagnosed text or at the edges of it.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.range.0 <= offset && offset <= self.range.1
+    }
+}
@@ -0,0 +1,339 @@
+use crate::document::Splice;
+use crate::highlight::LineHighlighter;
+use crate::ratatui::buffer::Buffer;
+use crate::ratatui::layout::Rect;
+use crate::ratatui::style::{Color, Modifier, Style};
+use crate::ratatui::text::Text;
+use crate::ratatui::widgets::{Paragraph, Widget};
+use crate::textarea::MergeArea;
+use crate::util::num_digits;
+use automerge::{AutomergeError, ChangeHash};
+use std::cmp;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Change {
+    Kept,
+    Inserted,
+    Deleted,
+}
+
+// One row of the diff. Changes are ranges of char columns in the row. A range which ends after the last column covers
+// the newline at the end of the row.
+#[derive(Clone, Default, Debug)]
+struct DiffLine {
+    text: String,
+    len: usize,
+    changes: Vec<(usize, usize, Change)>,
+}
+
+// Chars of the text before the modifications followed by the modifications. Deleted chars are kept in place and
+// inserted chars are put after the chars deleted at the same position.
+fn apply(text: &str, splices: &[Splice]) -> Vec<(char, Change)> {
+    let mut chars: Vec<_> = text.chars().map(|c| (c, Change::Kept)).collect();
+
+    // Index of the char at the offset in the modified text. Deleted chars don't count
+    let index = |chars: &[(char, Change)], offset: usize| {
+        chars
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, change))| *change != Change::Deleted)
+            .nth(offset)
+            .map(|(i, _)| i)
+            .unwrap_or(chars.len())
+    };
+
+    for splice in splices {
+        let mut i = index(&chars, splice.at);
+        let mut deleted = splice.deleted.chars().count();
+        while deleted > 0 && i < chars.len() {
+            match chars[i].1 {
+                Change::Deleted => i += 1,
+                Change::Inserted => {
+                    chars.remove(i);
+                    deleted -= 1;
+                }
+                Change::Kept => {
+                    chars[i].1 = Change::Deleted;
+                    i += 1;
+                    deleted -= 1;
+                }
+            }
+        }
+
+        let i = index(&chars, splice.at);
+        let inserted = splice.inserted.chars().map(|c| (c, Change::Inserted));
+        chars.splice(i..i, inserted);
+    }
+
+    // Patches may insert before deleting at the same position. Put deleted chars first in each run of modified chars
+    for run in chars.split_mut(|(_, change)| *change == Change::Kept) {
+        run.sort_by_key(|(_, change)| *change == Change::Inserted);
+    }
+
+    chars
+}
+
+fn split_lines(chars: &[(char, Change)]) -> Vec<DiffLine> {
+    let mut lines = vec![DiffLine::default()];
+    for &(c, change) in chars {
+        let line = lines.last_mut().unwrap();
+        let col = line.len;
+        if change != Change::Kept {
+            match line.changes.last_mut() {
+                Some((_, end, last)) if *end == col && *last == change => *end = col + 1,
+                _ => line.changes.push((col, col + 1, change)),
+            }
+        }
+        if c == '\n' {
+            lines.push(DiffLine::default());
+        } else {
+            line.text.push(c);
+            line.len += 1;
+        }
+    }
+    lines
+}
+
+/// A widget to render the modifications of the text of [`MergeArea`] between two heads of the attached automerge
+/// document. Inserted text is rendered in green and deleted text is rendered inline in red with strikethrough. Tab
+/// length, line numbers, the block and the styles of the textarea are applied as [`MergeArea`] is rendered.
+///
+/// The view is computed on [`DiffView::new`]. Create a new one after the document is modified.
+/// ```
+/// use automerge::transaction::Transactable as _;
+/// use automerge::{AutoCommit, ObjType, ROOT};
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::style::{Color, Modifier};
+/// use ratatui::widgets::Widget as _;
+/// use ratatui_mergearea::{DiffView, MergeArea};
+///
+/// let mut doc = AutoCommit::new();
+/// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+/// doc.splice_text(&obj, 0, 0, "hello world").unwrap();
+/// let before = doc.get_heads();
+/// let mut textarea = MergeArea::from_doc(doc, obj).unwrap();
+///
+/// for _ in 0..5 {
+///     textarea.delete_next_char();
+/// }
+/// textarea.insert_str("goodbye");
+/// let after = textarea.doc_mut().unwrap().get_heads();
+///
+/// let view = DiffView::new(&mut textarea, &before, &after).unwrap();
+/// let area = Rect::new(0, 0, 20, 1);
+/// let mut buf = Buffer::empty(area);
+/// (&view).render(area, &mut buf);
+///
+/// // Deleted "hello" is followed by inserted "goodbye"
+/// let row: String = (0..20).map(|x| buf[(x, 0)].symbol()).collect();
+/// assert_eq!(row, "hellogoodbye world  ");
+/// assert!(buf[(0, 0)].modifier.contains(Modifier::CROSSED_OUT));
+/// assert_eq!(buf[(5, 0)].fg, Color::Green);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DiffView<'a, 'b> {
+    textarea: &'a MergeArea<'b>,
+    lines: Vec<DiffLine>,
+    insert_style: Style,
+    delete_style: Style,
+    scroll: (u16, u16),
+}
+
+impl<'a, 'b> DiffView<'a, 'b> {
+    /// Compute the modifications of the text between the `before` heads and the `after` heads of the document attached
+    /// to the textarea. An error is returned when some of the heads are not in the document. When no document is
+    /// attached, the current text is rendered without modifications.
+    pub fn new(
+        textarea: &'a mut MergeArea<'b>,
+        before: &[ChangeHash],
+        after: &[ChangeHash],
+    ) -> Result<Self, AutomergeError> {
+        let (text, splices) = textarea.diff(before, after)?;
+        let lines = split_lines(&apply(&text, &splices));
+        Ok(Self {
+            textarea,
+            lines,
+            insert_style: Style::default().fg(Color::Green),
+            delete_style: Style::default()
+                .fg(Color::Red)
+                .add_modifier(Modifier::CROSSED_OUT),
+            scroll: (0, 0),
+        })
+    }
+
+    /// Set the style of inserted text. The default style is green foreground.
+    pub fn insert_style(mut self, style: Style) -> Self {
+        self.insert_style = style;
+        self
+    }
+
+    /// Set the style of deleted text. The default style is red foreground with strikethrough.
+    pub fn delete_style(mut self, style: Style) -> Self {
+        self.delete_style = style;
+        self
+    }
+
+    /// Set the scroll offset of the view as `(row, col)`. The row is clamped so that the last row is at the bottom of
+    /// the view at most.
+    pub fn scroll(mut self, offset: (u16, u16)) -> Self {
+        self.scroll = offset;
+        self
+    }
+
+    /// Get the number of rows of the view. Deleted lines are counted.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn text_widget(&self, top_row: usize, height: usize) -> Text<'_> {
+        let t = self.textarea;
+        let lnum_len = num_digits(self.lines.len());
+        let bottom_row = cmp::min(top_row + height, self.lines.len());
+        let mut lines = Vec::with_capacity(bottom_row - top_row);
+        for (row, line) in self.lines[top_row..bottom_row].iter().enumerate() {
+            let mut hl = LineHighlighter::new(
+                &line.text,
+                Style::default(),
+                t.tab_length(),
+                t.mask_char(),
+                Style::default(),
+            );
+            if let Some(style) = t.line_number_style() {
+                hl.line_number(top_row + row, lnum_len, style);
+            }
+            for &(start, end, change) in &line.changes {
+                let style = match change {
+                    Change::Inserted => self.insert_style,
+                    Change::Deleted => self.delete_style,
+                    Change::Kept => continue,
+                };
+                hl.diff(start, end, style);
+            }
+            lines.push(hl.into_spans());
+        }
+        Text::from(lines)
+    }
+}
+
+impl Widget for &DiffView<'_, '_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let t = self.textarea;
+        let text_area = match t.block() {
+            Some(b) => {
+                let inner = b.inner(area);
+                b.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        let (top_row, top_col) = self.scroll;
+        let rows = self.lines.len() as u16;
+        let top_row = cmp::min(top_row, rows.saturating_sub(text_area.height));
+
+        let mut inner = Paragraph::new(self.text_widget(top_row as _, text_area.height as _))
+            .style(t.style())
+            .alignment(t.alignment());
+        if top_col != 0 {
+            inner = inner.scroll((0, top_col));
+        }
+        inner.render(text_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn splice(at: usize, deleted: &str, inserted: &str) -> Splice {
+        Splice {
+            at,
+            row: 0,
+            deleted: deleted.to_string(),
+            inserted: inserted.to_string(),
+        }
+    }
+
+    // Render the changes as `[-deleted-]` and `{+inserted+}`
+    fn word_diff(chars: &[(char, Change)]) -> String {
+        let mut s = String::new();
+        let mut prev = Change::Kept;
+        for &(c, change) in chars {
+            if change != prev {
+                match prev {
+                    Change::Deleted => s.push_str("-]"),
+                    Change::Inserted => s.push_str("+}"),
+                    Change::Kept => {}
+                }
+                match change {
+                    Change::Deleted => s.push_str("[-"),
+                    Change::Inserted => s.push_str("{+"),
+                    Change::Kept => {}
+                }
+                prev = change;
+            }
+            s.push(c);
+        }
+        match prev {
+            Change::Deleted => s.push_str("-]"),
+            Change::Inserted => s.push_str("+}"),
+            Change::Kept => {}
+        }
+        s
+    }
+
+    #[test]
+    fn apply_splices() {
+        let tests = [
+            ("abc", vec![], "abc"),
+            ("abc", vec![splice(1, "", "xy")], "a{+xy+}bc"),
+            ("abc", vec![splice(1, "b", "")], "a[-b-]c"),
+            (
+                "abc",
+                vec![splice(1, "", "xy"), splice(3, "b", "")],
+                "a[-b-]{+xy+}c",
+            ),
+            (
+                "abc",
+                vec![splice(1, "b", ""), splice(1, "", "xy")],
+                "a[-b-]{+xy+}c",
+            ),
+            // Deleting chars inserted by the previous splice
+            (
+                "abc",
+                vec![splice(3, "", "xyz"), splice(3, "xy", "")],
+                "abc{+z+}",
+            ),
+            ("a\nb", vec![splice(1, "\n", " ")], "a[-\n-]{+ +}b"),
+            (
+                "abc",
+                vec![splice(0, "a", ""), splice(1, "c", "")],
+                "[-a-]b[-c-]",
+            ),
+        ];
+
+        for (text, splices, want) in tests {
+            let have = word_diff(&apply(text, &splices));
+            assert_eq!(have, want, "text={text:?}, splices={splices:?}");
+        }
+    }
+
+    #[test]
+    fn split_diff_lines() {
+        use Change::*;
+
+        let chars = apply("ab\ncd", &[splice(1, "b\nc", "x\n")]);
+        let lines = split_lines(&chars);
+        let have: Vec<_> = lines
+            .iter()
+            .map(|l| (l.text.as_str(), l.changes.clone()))
+            .collect();
+        let want = vec![
+            ("ab", vec![(1, 3, Deleted)]),
+            ("cx", vec![(0, 1, Deleted), (1, 3, Inserted)]),
+            ("d", vec![]),
+        ];
+        assert_eq!(have, want);
+    }
+}
@@ -0,0 +1,805 @@
+use crate::diagnostic::Diagnostic;
+#[cfg(feature = "tree-sitter")]
+use crate::parse_tree::ParseTree;
+use automerge::marks::{ExpandMark, Mark};
+#[cfg(feature = "sync")]
+use automerge::sync::{self, SyncDoc as _};
+use automerge::transaction::Transactable as _;
+use automerge::{
+    ActorId, AutoCommit, AutomergeError, ChangeHash, Cursor, CursorPosition, ObjId, ObjType, Patch,
+    PatchAction, ReadDoc as _, ScalarValue, TextEncoding,
+};
+use std::collections::HashSet;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation as _;
+
+// Text buffer edited by the textarea. When an automerge document is attached, every splice is written to the text
+// object of the document directly and `text` is kept as a materialized copy of it so that rendering and cursor
+// movements don't need to read the document.
+#[derive(Clone, Debug, Default)]
+pub struct Document {
+    text: autosurgeon::Text,
+    attached: Option<Attached>,
+    // Incremented on every modification of the text so that values computed from the text can be cached
+    version: u64,
+    // These follow every splice of the text
+    diagnostics: Vec<Diagnostic>,
+    folds: Vec<Range<usize>>,
+    #[cfg(feature = "tree-sitter")]
+    tree: Option<ParseTree>,
+    // Modifications made through `Document::doc_mut` which were applied to the text before a local modification
+    caught_up: Vec<Splice>,
+}
+
+#[derive(Clone, Debug)]
+struct Attached {
+    doc: AutoCommit,
+    obj: ObjId,
+    // Heads of the document when it was lent out by `Document::doc_mut`. Changes made after these heads are applied
+    // incrementally on `Document::reload`.
+    lent_at: Option<Vec<ChangeHash>>,
+}
+
+// One modification of the text which was applied from outside the textarea. `at` is a char offset and `row` is a row
+// of the modified position in the text before the modification.
+#[derive(Clone, Debug)]
+pub struct Splice {
+    pub at: usize,
+    pub row: usize,
+    pub deleted: String,
+    pub inserted: String,
+}
+
+impl Splice {
+    // Modification of the text `s` by the patch action. The byte index of the modified position is returned together.
+    fn from_action(encoding: TextEncoding, s: &str, action: &PatchAction) -> Option<(usize, Self)> {
+        let (pos, deleted, inserted) = match action {
+            PatchAction::SpliceText { index, value, .. } => {
+                let pos = byte_index(encoding, s, *index);
+                (pos, String::new(), value.make_string())
+            }
+            PatchAction::DeleteSeq { index, length } => {
+                let start = byte_index(encoding, s, *index);
+                let end = start + byte_index(encoding, &s[start..], *length);
+                (start, s[start..end].to_string(), String::new())
+            }
+            _ => return None,
+        };
+        let splice = Self {
+            at: s[..pos].chars().count(),
+            row: s[..pos].matches('\n').count(),
+            deleted,
+            inserted,
+        };
+        Some((pos, splice))
+    }
+
+    // Move a char offset in the text before the modification to the same place in the text after it
+    pub fn shift(&self, offset: usize) -> usize {
+        let deleted = self.deleted.chars().count();
+        let inserted = self.inserted.chars().count();
+        if offset < self.at {
+            offset
+        } else if offset < self.at + deleted {
+            self.at
+        } else {
+            offset - deleted + inserted
+        }
+    }
+
+    // Move a row in the text before the modification to the same line in the text after it
+    pub fn shift_row(&self, row: usize) -> usize {
+        let deleted = self.deleted.matches('\n').count();
+        let inserted = self.inserted.matches('\n').count();
+        if row <= self.row {
+            row
+        } else if row < self.row + deleted {
+            self.row
+        } else {
+            row - deleted + inserted
+        }
+    }
+}
+
+// Position in the text such as the caret. While a document is attached, it is kept as a cursor of the document so
+// that it stays at the same char through merges and modifications made through `Document::doc_mut`.
+#[derive(Clone, Debug)]
+pub enum Position {
+    Offset(usize),
+    Cursor(Cursor),
+}
+
+impl Position {
+    // Move the position in the text before the modification to the same place in the text after it. Cursors don't need
+    // to be moved.
+    pub fn shift(&mut self, splice: &Splice) {
+        if let Self::Offset(offset) = self {
+            *offset = splice.shift(*offset);
+        }
+    }
+}
+
+impl Document {
+    pub fn new(text: autosurgeon::Text) -> Self {
+        Self {
+            text,
+            attached: None,
+            version: 0,
+            diagnostics: vec![],
+            folds: vec![],
+            #[cfg(feature = "tree-sitter")]
+            tree: None,
+            caught_up: vec![],
+        }
+    }
+
+    pub fn attach(doc: AutoCommit, obj: ObjId) -> Result<Self, AutomergeError> {
+        match doc.object_type(&obj)? {
+            ObjType::Text => {}
+            typ => return Err(AutomergeError::InvalidOp(typ)),
+        }
+        // Automerge counts the graphemes of each inserted string separately, so its indices can't be mapped to the
+        // chars of the whole text
+        if doc.text_encoding() == TextEncoding::GraphemeCluster {
+            return Err(AutomergeError::InvalidOp(ObjType::Text));
+        }
+        let text = doc.text(&obj)?;
+        Ok(Self {
+            text: autosurgeon::Text::with_value(text),
+            attached: Some(Attached {
+                doc,
+                obj,
+                lent_at: None,
+            }),
+            version: 0,
+            diagnostics: vec![],
+            folds: vec![],
+            #[cfg(feature = "tree-sitter")]
+            tree: None,
+            caught_up: vec![],
+        })
+    }
+
+    pub fn text(&self) -> &autosurgeon::Text {
+        &self.text
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.text.as_str()
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    pub fn parse_tree(&self) -> Option<&ParseTree> {
+        self.tree.as_ref()
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    pub fn set_parse_tree(&mut self, tree: Option<ParseTree>) {
+        self.tree = tree;
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics = diagnostics;
+    }
+
+    // Folded ranges of rows. The first row of each range is the header of the fold
+    pub fn folds(&self) -> &[Range<usize>] {
+        &self.folds
+    }
+
+    pub fn set_folds(&mut self, folds: Vec<Range<usize>>) {
+        self.folds = folds;
+    }
+
+    // Modify the bytes of the text between `start` and `end`. The parse tree is edited but not parsed yet
+    fn splice_text(&mut self, start: usize, end: usize, insert: &str) {
+        if !self.diagnostics.is_empty() || !self.folds.is_empty() {
+            let s = self.text.as_str();
+            let splice = Splice {
+                at: s[..start].chars().count(),
+                row: s[..start].matches('\n').count(),
+                deleted: s[start..end].to_string(),
+                inserted: insert.to_string(),
+            };
+            for d in &mut self.diagnostics {
+                d.range = (splice.shift(d.range.0), splice.shift(d.range.1));
+            }
+            // A fold keeps its header row. Rows inserted in its last row are not folded
+            for f in &mut self.folds {
+                *f = splice.shift_row(f.start)..splice.shift_row(f.end - 1) + 1;
+            }
+            self.folds.retain(|f| f.len() > 1);
+        }
+        #[cfg(feature = "tree-sitter")]
+        if let Some(tree) = &mut self.tree {
+            tree.edit(self.text.as_str(), start, end, insert);
+        }
+        self.text.splice(start, (end - start) as isize, insert);
+        self.version += 1;
+    }
+
+    // Parse the text again after it was modified by splices
+    fn reparse(&mut self) {
+        #[cfg(feature = "tree-sitter")]
+        if let Some(tree) = &mut self.tree {
+            tree.parse(self.text.as_str());
+        }
+    }
+
+    // The entire text was replaced, so nothing can follow the modifications. Diagnostics and folds are kept in the text
+    fn replaced(&mut self) {
+        self.version += 1;
+        let len = self.text.as_str().chars().count();
+        for d in &mut self.diagnostics {
+            d.range = (d.range.0.min(len), d.range.1.min(len));
+        }
+        let lines = self.text.as_str().matches('\n').count() + 1;
+        for f in &mut self.folds {
+            f.end = f.end.min(lines);
+        }
+        self.folds.retain(|f| f.len() > 1);
+        #[cfg(feature = "tree-sitter")]
+        if let Some(tree) = &mut self.tree {
+            tree.reset(self.text.as_str());
+        }
+    }
+
+    pub fn doc(&self) -> Option<&AutoCommit> {
+        self.attached.as_ref().map(|a| &a.doc)
+    }
+
+    pub fn doc_mut(&mut self) -> Option<&mut AutoCommit> {
+        let attached = self.attached.as_mut()?;
+        attached.lent_at = Some(attached.doc.get_heads());
+        Some(&mut attached.doc)
+    }
+
+    // Apply the modifications made through `Document::doc_mut` since it was lent out. This must be done before the text
+    // is modified locally. Otherwise the local modification would be applied again on the next reload since it is
+    // recorded after the heads where the document was lent out. The applied modifications are returned so that char
+    // offsets in the previous text can follow them.
+    pub fn catch_up(&mut self) -> Vec<Splice> {
+        let Some(Attached { doc, lent_at, .. }) = &mut self.attached else {
+            return vec![];
+        };
+        let Some(before) = lent_at.take() else {
+            return vec![];
+        };
+        let after = doc.get_heads();
+        let patches = doc.diff(&before, &after);
+        let splices = self.apply_patches(&patches);
+        self.caught_up.extend(splices.iter().cloned());
+        splices
+    }
+
+    // Modifications applied by `Document::catch_up` since the last call. Positions kept outside of this document must
+    // follow them.
+    pub fn take_caught_up(&mut self) -> Vec<Splice> {
+        std::mem::take(&mut self.caught_up)
+    }
+
+    pub fn obj(&self) -> Option<&ObjId> {
+        self.attached.as_ref().map(|a| &a.obj)
+    }
+
+    // Same semantics as `autosurgeon::Text::splice`. `pos` is a byte index and `del` is a byte length which may be
+    // negative to delete bytes preceding `pos`.
+    pub fn splice(&mut self, pos: usize, del: isize, insert: impl AsRef<str>) {
+        let insert = insert.as_ref();
+        let start = if del < 0 {
+            pos.saturating_sub(del.unsigned_abs())
+        } else {
+            pos
+        };
+        let end = start + del.unsigned_abs();
+        if let Some(Attached { doc, obj, .. }) = &mut self.attached {
+            let encoding = doc.text_encoding();
+            let s = self.text.as_str();
+            let index = encoded_len(encoding, &s[..start]);
+            let len = encoded_len(encoding, &s[start..end]);
+            doc.splice_text(&*obj, index, len as isize, insert)
+                .expect("attached text object must be writable");
+        }
+        self.splice_text(start, end, insert);
+        self.reparse();
+    }
+
+    // Insert a string at the char offset. When a document is attached, cursors of the inserted chars are returned.
+    pub fn insert(&mut self, at: usize, s: &str) -> Option<Vec<Cursor>> {
+        let at = shift(&self.catch_up(), at);
+        let pos = byte_index(TextEncoding::UnicodeCodePoint, self.text.as_str(), at);
+        self.splice(pos, 0, s);
+
+        let Attached { doc, obj, .. } = self.attached.as_ref()?;
+        let encoding = doc.text_encoding();
+        let mut index = encoded_len(encoding, &self.text.as_str()[..pos]);
+        let mut buf = [0; 4];
+        let cursors = s
+            .chars()
+            .map(|c| {
+                let cursor = doc
+                    .get_cursor(obj, index, None)
+                    .expect("inserted char must have cursor");
+                index += encoded_len(encoding, c.encode_utf8(&mut buf));
+                cursor
+            })
+            .collect();
+        Some(cursors)
+    }
+
+    // Delete `len` chars at the char offset. When a document is attached, a cursor of the first deleted char is
+    // returned. The cursor resolves to the position where the deleted chars were.
+    pub fn delete(&mut self, at: usize, len: usize) -> Option<Cursor> {
+        let at = shift(&self.catch_up(), at);
+        let s = self.text.as_str();
+        let start = byte_index(TextEncoding::UnicodeCodePoint, s, at);
+        let end = start + byte_index(TextEncoding::UnicodeCodePoint, &s[start..], len);
+        let cursor = if start < end {
+            self.attached.as_ref().map(|Attached { doc, obj, .. }| {
+                let index = encoded_len(doc.text_encoding(), &s[..start]);
+                doc.get_cursor(obj, index, None)
+                    .expect("deleted char must have cursor")
+            })
+        } else {
+            None
+        };
+        self.splice(start, (end - start) as isize, "");
+        cursor
+    }
+
+    // Delete the chars pointed by the cursors. Chars which were already deleted are skipped.
+    pub fn delete_cursors(&mut self, cursors: &[Cursor]) {
+        self.catch_up();
+        let Some(Attached { doc, obj, .. }) = &self.attached else {
+            return;
+        };
+
+        // Indices of the chars in the encoding of the document. A cursor of a deleted char resolves to the next char,
+        // whose cursor is different
+        let mut indices: Vec<usize> = cursors
+            .iter()
+            .filter_map(|c| {
+                let index = doc.get_cursor_position(obj, c, None).ok()?;
+                let found = doc.get_cursor(obj, index, None).ok()?;
+                (found == *c).then_some(index)
+            })
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        // Delete each run of consecutive chars from the last one so that the indices of preceding runs are kept
+        let encoding = doc.text_encoding();
+        let mut end = indices.len();
+        while end > 0 {
+            let mut start = end - 1;
+            while start > 0 && indices[start - 1] + 1 == indices[start] {
+                start -= 1;
+            }
+            let s = self.text.as_str();
+            let pos = byte_index(encoding, s, indices[start]);
+            let len = byte_index(TextEncoding::UnicodeCodePoint, &s[pos..], end - start);
+            self.splice(pos, len as isize, "");
+            end = start;
+        }
+    }
+
+    // Replace the entire text. When a document is attached, the difference between the current text and the new one
+    // is written to it and the modifications are returned.
+    pub fn replace(&mut self, text: autosurgeon::Text) -> Option<Vec<Splice>> {
+        self.catch_up();
+        let Some(Attached { doc, obj, .. }) = &mut self.attached else {
+            self.text = text;
+            self.replaced();
+            return None;
+        };
+        let before = doc.get_heads();
+        doc.update_text(obj, text.as_str())
+            .expect("attached text object must be writable");
+        let after = doc.get_heads();
+        let patches = doc.diff(&before, &after);
+        Some(self.apply_patches(&patches))
+    }
+
+    // Stable address of the char offset in the attached document. An offset at the end of text follows the end.
+    pub fn cursor(&self, offset: usize) -> Option<Cursor> {
+        let Attached { doc, obj, lent_at } = self.attached.as_ref()?;
+        let s = self.text.as_str();
+        let pos = byte_index(TextEncoding::UnicodeCodePoint, s, offset);
+        let position = if pos == s.len() {
+            CursorPosition::End
+        } else {
+            CursorPosition::Index(encoded_len(doc.text_encoding(), &s[..pos]))
+        };
+        // The text is as of the heads where the document was lent out until it is reloaded
+        doc.get_cursor(obj, position, lent_at.as_deref()).ok()
+    }
+
+    // Position at the char offset
+    pub fn position(&self, offset: usize) -> Position {
+        match self.cursor(offset) {
+            Some(cursor) => Position::Cursor(cursor),
+            None => Position::Offset(offset),
+        }
+    }
+
+    // Char offset of the position in the current text
+    pub fn offset(&self, position: &Position) -> usize {
+        match position {
+            Position::Offset(offset) => *offset,
+            Position::Cursor(cursor) => self
+                .cursor_offset(cursor)
+                .expect("cursor must belong to attached document"),
+        }
+    }
+
+    // Resolve the stable address into a char offset in the current text
+    pub fn cursor_offset(&self, cursor: &Cursor) -> Result<usize, AutomergeError> {
+        let Some(Attached { doc, obj, lent_at }) = &self.attached else {
+            return Err(AutomergeError::InvalidCursor(cursor.clone()));
+        };
+        // Cursors of chars inserted through `Document::doc_mut` don't exist at the heads of the text yet
+        let index = doc
+            .get_cursor_position(obj, cursor, lent_at.as_deref())
+            .or_else(|_| doc.get_cursor_position(obj, cursor, None))?;
+        let s = self.text.as_str();
+        let pos = byte_index(doc.text_encoding(), s, index);
+        Ok(s[..pos].chars().count())
+    }
+
+    // All changes of the attached document in causal order with their dependencies
+    pub fn changes(&mut self) -> Option<Vec<(ChangeHash, Vec<ChangeHash>)>> {
+        let Attached { doc, .. } = self.attached.as_mut()?;
+        let changes = doc
+            .get_changes(&[])
+            .into_iter()
+            .map(|c| (c.hash(), c.deps().to_vec()))
+            .collect();
+        Some(changes)
+    }
+
+    // The text as of the heads. The text is empty when the text object did not exist yet at the heads.
+    pub fn text_at(&self, heads: &[ChangeHash]) -> Option<String> {
+        let Attached { doc, obj, .. } = self.attached.as_ref()?;
+        Some(doc.text_at(obj, heads).unwrap_or_default())
+    }
+
+    // Actors who inserted the chars between the char offsets `start` and `end`. Consecutive chars inserted by the same
+    // actor are grouped into one `(start, end, actor)` run.
+    pub fn authors(&self, start: usize, end: usize) -> Option<Vec<(usize, usize, ActorId)>> {
+        let Attached { doc, obj, .. } = self.attached.as_ref()?;
+        let s = self.text.as_str();
+        let encoding = doc.text_encoding();
+        let start_pos = byte_index(TextEncoding::UnicodeCodePoint, s, start);
+        let end_pos =
+            start_pos + byte_index(TextEncoding::UnicodeCodePoint, &s[start_pos..], end - start);
+        let index = encoded_len(encoding, &s[..start_pos]);
+        let len = encoded_len(encoding, &s[start_pos..end_pos]);
+
+        let mut runs: Vec<(usize, usize, ActorId)> = vec![];
+        let mut offset = start;
+        for item in doc.list_range(obj, index..index + len) {
+            let next = offset + item.value.to_str().map_or(1, |v| v.chars().count());
+            if let ObjId::Id(_, actor, _) = item.id {
+                match runs.last_mut() {
+                    Some((_, end, a)) if *end == offset && *a == actor => *end = next,
+                    _ => runs.push((offset, next, actor)),
+                }
+            }
+            offset = next;
+        }
+        Some(runs)
+    }
+
+    // Regions of the text inserted concurrently by more than one actor. `merged` are the hashes of the changes applied
+    // by a merge. The last common heads are the dependencies of the merged changes which were known before the merge.
+    // Maximal runs of chars inserted after these heads are regions when several actors inserted them. Each region is
+    // returned as the stable addresses of its first and last chars.
+    pub fn concurrent_edits(&mut self, merged: &[ChangeHash]) -> Vec<(Cursor, Cursor)> {
+        let Some(Attached { doc, obj, .. }) = self.attached.as_mut() else {
+            return vec![];
+        };
+        let mut base = vec![];
+        for hash in merged {
+            let Some(change) = doc.get_change_by_hash(hash) else {
+                continue;
+            };
+            for dep in change.deps() {
+                if !merged.contains(dep) && !base.contains(dep) {
+                    base.push(*dep);
+                }
+            }
+        }
+        // Nothing is concurrent without common history
+        if base.is_empty() {
+            return vec![];
+        }
+
+        let known: HashSet<_> = doc.list_range_at(&*obj, .., &base).map(|i| i.id).collect();
+        let mut runs: Vec<(usize, usize, Vec<ActorId>)> = vec![];
+        let mut in_run = false;
+        for item in doc.list_range(&*obj, ..) {
+            if known.contains(&item.id) {
+                in_run = false;
+                continue;
+            }
+            let ObjId::Id(_, actor, _) = item.id else {
+                continue;
+            };
+            match runs.last_mut() {
+                Some((_, last, actors)) if in_run => {
+                    *last = item.index;
+                    if !actors.contains(&actor) {
+                        actors.push(actor);
+                    }
+                }
+                _ => runs.push((item.index, item.index, vec![actor])),
+            }
+            in_run = true;
+        }
+
+        runs.into_iter()
+            .filter(|(_, _, actors)| actors.len() > 1)
+            .filter_map(|(first, last, _)| {
+                let first = doc
+                    .get_cursor(&*obj, CursorPosition::Index(first), None)
+                    .ok()?;
+                let last = doc
+                    .get_cursor(&*obj, CursorPosition::Index(last), None)
+                    .ok()?;
+                Some((first, last))
+            })
+            .collect()
+    }
+
+    // Resolve the stable addresses of the first and the last chars of a range into char offsets. `None` is returned
+    // when all chars of the range were deleted.
+    pub fn cursor_range(&self, first: &Cursor, last: &Cursor) -> Option<(usize, usize)> {
+        let start = self.cursor_offset(first).ok()?;
+        let end = self.cursor_offset(last).ok()?;
+        // A cursor pointing to a deleted char resolves to the position of the next char
+        let end = if self.cursor(end).as_ref() == Some(last) {
+            end + 1
+        } else {
+            end
+        };
+        (start < end).then_some((start, end))
+    }
+
+    // Mark the chars between the char offsets `start` and `end` in the attached document. `expand` tells whether text
+    // inserted at the edges of the range is marked as well. Nothing happens when no document is attached.
+    pub fn mark(
+        &mut self,
+        start: usize,
+        end: usize,
+        name: &str,
+        value: ScalarValue,
+        expand: ExpandMark,
+    ) -> Result<(), AutomergeError> {
+        let splices = self.catch_up();
+        let (start, end) = (shift(&splices, start), shift(&splices, end));
+        let Some(Attached { doc, obj, .. }) = self.attached.as_mut() else {
+            return Ok(());
+        };
+        let (start, end) = encoded_range(doc.text_encoding(), self.text.as_str(), start, end);
+        let mark = Mark::new(name.to_string(), value, start, end);
+        doc.mark(&*obj, mark, expand)
+    }
+
+    // Remove the mark from the chars between the char offsets `start` and `end` in the attached document
+    pub fn unmark(
+        &mut self,
+        start: usize,
+        end: usize,
+        name: &str,
+        expand: ExpandMark,
+    ) -> Result<(), AutomergeError> {
+        let splices = self.catch_up();
+        let (start, end) = (shift(&splices, start), shift(&splices, end));
+        let Some(Attached { doc, obj, .. }) = self.attached.as_mut() else {
+            return Ok(());
+        };
+        let (start, end) = encoded_range(doc.text_encoding(), self.text.as_str(), start, end);
+        doc.unmark(&*obj, name, start, end, expand)
+    }
+
+    // Marks of the attached document overlapping with the chars between the char offsets `start` and `end`. Ranges of
+    // the returned marks are char offsets.
+    pub fn marks(&self, start: usize, end: usize) -> Option<Vec<Mark<'static>>> {
+        let Attached { doc, obj, .. } = self.attached.as_ref()?;
+        let s = self.text.as_str();
+        let encoding = doc.text_encoding();
+        let offset = |index: usize| s[..byte_index(encoding, s, index)].chars().count();
+        let marks = doc
+            .marks(obj)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|m| !m.value().is_null())
+            .filter_map(|m| {
+                let (from, to) = (offset(m.start), offset(m.end));
+                (from < end && start < to)
+                    .then(|| Mark::new(m.name().to_string(), m.value().clone(), from, to))
+            })
+            .collect();
+        Some(marks)
+    }
+
+    // Merge changes of another document into the attached document. Hashes of the merged changes and the
+    // modifications of the text are returned.
+    pub fn merge(
+        &mut self,
+        other: &mut AutoCommit,
+    ) -> Result<(Vec<ChangeHash>, Vec<Splice>), AutomergeError> {
+        let Some(Attached { doc, lent_at, .. }) = &mut self.attached else {
+            return Ok((vec![], vec![]));
+        };
+        // Changes made through `Document::doc_mut` are not reflected in the text yet
+        let before = lent_at.take().unwrap_or_else(|| doc.get_heads());
+        let hashes = doc.merge(other)?;
+        let after = doc.get_heads();
+        let patches = doc.diff(&before, &after);
+        Ok((hashes, self.apply_patches(&patches)))
+    }
+
+    // Generate a sync message for the remote peer represented by `state`. `None` is returned when there is nothing to
+    // send or no document is attached.
+    #[cfg(feature = "sync")]
+    pub fn generate_sync_message(&mut self, state: &mut sync::State) -> Option<sync::Message> {
+        let Attached { doc, .. } = self.attached.as_mut()?;
+        doc.sync().generate_sync_message(state)
+    }
+
+    // Apply a sync message received from the remote peer represented by `state` to the attached document. The hashes
+    // of the applied changes and the modifications of the text are returned.
+    #[cfg(feature = "sync")]
+    pub fn receive_sync_message(
+        &mut self,
+        state: &mut sync::State,
+        message: sync::Message,
+    ) -> Result<(Vec<ChangeHash>, Vec<Splice>), AutomergeError> {
+        let Some(Attached { doc, lent_at, .. }) = &mut self.attached else {
+            return Ok((vec![], vec![]));
+        };
+        // Changes made through `Document::doc_mut` are not reflected in the text yet
+        let heads = doc.get_heads();
+        let before = lent_at.clone().unwrap_or_else(|| heads.clone());
+        doc.sync().receive_sync_message(state, message)?;
+        let after = doc.get_heads();
+        // Only the changes received from the remote peer were merged
+        let hashes = doc.get_changes(&heads).iter().map(|c| c.hash()).collect();
+        let patches = doc.diff(&before, &after);
+        Ok((hashes, self.apply_patches(&patches)))
+    }
+
+    // Reflect patches of the attached document in the text. Patches for other objects are ignored. The patches must
+    // have been applied to the attached document already.
+    pub fn apply_patches(&mut self, patches: &[Patch]) -> Vec<Splice> {
+        let Some(Attached { doc, obj, lent_at }) = &mut self.attached else {
+            return vec![];
+        };
+        *lent_at = None;
+        let encoding = doc.text_encoding();
+        let obj = obj.clone();
+
+        let mut splices = vec![];
+        for patch in patches.iter().filter(|p| p.obj == obj) {
+            let Some((pos, splice)) =
+                Splice::from_action(encoding, self.text.as_str(), &patch.action)
+            else {
+                continue;
+            };
+            self.splice_text(pos, pos + splice.deleted.len(), &splice.inserted);
+            splices.push(splice);
+        }
+        if !splices.is_empty() {
+            self.reparse();
+        }
+        splices
+    }
+
+    // Modifications of the text between the `before` heads and the `after` heads of the attached document, and the
+    // text as of the `before` heads. When no document is attached, the current text is returned without modifications.
+    pub fn diff(
+        &mut self,
+        before: &[ChangeHash],
+        after: &[ChangeHash],
+    ) -> Result<(String, Vec<Splice>), AutomergeError> {
+        let Some(Attached { doc, obj, .. }) = self.attached.as_mut() else {
+            return Ok((self.text.as_str().to_string(), vec![]));
+        };
+        for hash in before.iter().chain(after) {
+            if doc.get_change_by_hash(hash).is_none() {
+                return Err(AutomergeError::InvalidHash(*hash));
+            }
+        }
+
+        let text = doc.text_at(&*obj, before).unwrap_or_default();
+        let encoding = doc.text_encoding();
+        let mut current = text.clone();
+        let mut splices = vec![];
+        for patch in doc.diff(before, after).iter().filter(|p| p.obj == *obj) {
+            let Some((pos, splice)) = Splice::from_action(encoding, &current, &patch.action) else {
+                continue;
+            };
+            current.replace_range(pos..pos + splice.deleted.len(), &splice.inserted);
+            splices.push(splice);
+        }
+        Ok((text, splices))
+    }
+
+    // Re-read the text from the attached document. This is necessary after the document was modified from outside.
+    // When the document was lent out by `Document::doc_mut`, the changes since then are applied incrementally and
+    // returned. Otherwise the entire text is replaced and `None` is returned.
+    pub fn reload(&mut self) -> Option<Vec<Splice>> {
+        let Attached { doc, obj, lent_at } = self.attached.as_mut()?;
+        if let Some(before) = lent_at.take() {
+            let after = doc.get_heads();
+            let patches = doc.diff(&before, &after);
+            return Some(self.apply_patches(&patches));
+        }
+        let text = doc
+            .text(&*obj)
+            .expect("attached text object must be readable");
+        self.text = autosurgeon::Text::with_value(text);
+        self.replaced();
+        None
+    }
+}
+
+// Move the char offset in the text before the modifications to the same position in the text after them
+fn shift(splices: &[Splice], offset: usize) -> usize {
+    splices.iter().fold(offset, |offset, s| s.shift(offset))
+}
+
+// Convert an index in the text encoding of automerge document into a byte index
+fn byte_index(encoding: TextEncoding, s: &str, index: usize) -> usize {
+    match encoding {
+        TextEncoding::UnicodeCodePoint => s
+            .char_indices()
+            .nth(index)
+            .map(|(i, _)| i)
+            .unwrap_or(s.len()),
+        TextEncoding::Utf8CodeUnit => index.min(s.len()),
+        TextEncoding::Utf16CodeUnit => {
+            let mut units = 0;
+            for (i, c) in s.char_indices() {
+                if units >= index {
+                    return i;
+                }
+                units += c.len_utf16();
+            }
+            s.len()
+        }
+        TextEncoding::GraphemeCluster => s
+            .grapheme_indices(true)
+            .nth(index)
+            .map(|(i, _)| i)
+            .unwrap_or(s.len()),
+    }
+}
+
+// Convert the range of char offsets into a range in the text encoding of automerge document
+fn encoded_range(encoding: TextEncoding, s: &str, start: usize, end: usize) -> (usize, usize) {
+    let start = byte_index(TextEncoding::UnicodeCodePoint, s, start);
+    let end = byte_index(TextEncoding::UnicodeCodePoint, s, end);
+    (
+        encoded_len(encoding, &s[..start]),
+        encoded_len(encoding, &s[..end]),
+    )
+}
+
+fn encoded_len(encoding: TextEncoding, s: &str) -> usize {
+    match encoding {
+        TextEncoding::UnicodeCodePoint => s.chars().count(),
+        TextEncoding::Utf8CodeUnit => s.len(),
+        TextEncoding::Utf16CodeUnit => s.encode_utf16().count(),
+        TextEncoding::GraphemeCluster => s.graphemes(true).count(),
+    }
+}
@@ -0,0 +1,198 @@
+use crate::util::SharedFn;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A hook to find the range of rows to fold at a row. Set it with [`crate::MergeArea::set_fold_provider`] and fold the
+/// range at the cursor with [`crate::MergeArea::toggle_fold_at_cursor`].
+///
+/// The range contains the row. Its first row stays visible as the header of the fold and the other rows are hidden.
+/// Closures taking the lines of the text and a row implement this trait. [`IndentFolds`] finds the ranges by the
+/// indentation of the lines.
+/// ```
+/// use ratatui_mergearea::FoldProvider;
+///
+/// // Fold a paragraph separated by blank lines
+/// let paragraphs = |lines: &[&str], row: usize| {
+///     let start = lines[..row].iter().rposition(|l| l.is_empty()).map_or(0, |r| r + 1);
+///     let end = lines[row..].iter().position(|l| l.is_empty()).map_or(lines.len(), |r| row + r);
+///     Some(start..end)
+/// };
+/// let lines = ["a", "b", "", "c", "d", "e"];
+/// assert_eq!(paragraphs.fold_range(&lines, 4), Some(3..6));
+/// ```
+pub trait FoldProvider {
+    /// Return the range of rows to fold at the row, or `None` when nothing can be folded there. The lines do not
+    /// contain the newlines at their ends.
+    fn fold_range(&self, lines: &[&str], row: usize) -> Option<Range<usize>>;
+}
+
+impl<F> FoldProvider for F
+where
+    F: Fn(&[&str], usize) -> Option<Range<usize>>,
+{
+    fn fold_range(&self, lines: &[&str], row: usize) -> Option<Range<usize>> {
+        self(lines, row)
+    }
+}
+
+/// [`FoldProvider`] which folds the block of lines indented deeper than the line before it, like `foldmethod=indent`
+/// of Vim. At a line followed by deeper indented lines, the line and the block after it are folded. Otherwise the
+/// innermost block around the line is folded with the line heading it. Blank lines inside a block belong to it, but
+/// blank lines at the end of a block don't.
+/// ```
+/// use ratatui_mergearea::{FoldProvider as _, IndentFolds};
+///
+/// let lines = ["fn f() {", "    if x {", "        y();", "    }", "}"];
+/// let folds = IndentFolds::default();
+/// assert_eq!(folds.fold_range(&lines, 0), Some(0..4));
+/// assert_eq!(folds.fold_range(&lines, 2), Some(1..3));
+/// assert_eq!(folds.fold_range(&lines, 3), Some(0..4));
+/// assert_eq!(folds.fold_range(&lines, 4), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndentFolds {
+    tab_len: u8,
+}
+
+impl Default for IndentFolds {
+    /// A tab is counted as 4 columns of indentation.
+    fn default() -> Self {
+        Self::new(4)
+    }
+}
+
+impl IndentFolds {
+    /// Create the provider counting a tab as `tab_len` columns of indentation.
+    pub fn new(tab_len: u8) -> Self {
+        Self { tab_len }
+    }
+
+    // Width of the indentation of the line. Blank lines have no indentation
+    fn indent(&self, line: &str) -> Option<usize> {
+        let mut width = 0;
+        for c in line.chars() {
+            match c {
+                ' ' => width += 1,
+                '\t' => width += self.tab_len as usize,
+                c if c.is_whitespace() => {}
+                _ => return Some(width),
+            }
+        }
+        None
+    }
+}
+
+impl FoldProvider for IndentFolds {
+    fn fold_range(&self, lines: &[&str], row: usize) -> Option<Range<usize>> {
+        let indent = self.indent(lines.get(row)?);
+        let next = lines[row + 1..].iter().find_map(|l| self.indent(l));
+
+        // The line heads a block when the next non-blank line is indented deeper. Otherwise find the line heading the
+        // block around this line
+        let start = match (indent, next) {
+            (Some(i), Some(n)) if n > i => row,
+            _ => {
+                let inner = indent.or(next)?;
+                lines[..row]
+                    .iter()
+                    .rposition(|l| self.indent(l).is_some_and(|i| i < inner))?
+            }
+        };
+
+        // Blank lines at the end of the block are not folded
+        let head = self.indent(lines[start]).unwrap_or(0);
+        let end = lines[start + 1..]
+            .iter()
+            .take_while(|l| self.indent(l).is_none_or(|i| i > head))
+            .enumerate()
+            .filter(|(_, l)| self.indent(l).is_some())
+            .map(|(i, _)| start + i + 2)
+            .last()?;
+        Some(start..end).filter(|r| r.contains(&row))
+    }
+}
+
+// Provides the ranges to fold
+#[derive(Clone, Debug)]
+pub struct Folder(SharedFn<dyn FoldProvider + Send + Sync>);
+
+impl Folder {
+    pub fn new(provider: impl FoldProvider + Send + Sync + 'static) -> Self {
+        Self(SharedFn::new(Arc::new(provider)))
+    }
+
+    pub fn fold_range(&self, text: &str, row: usize) -> Option<Range<usize>> {
+        let lines: Vec<_> = text.split('\n').collect();
+        self.0.fold_range(&lines, row)
+    }
+}
+
+// Merge overlapping folds into the folds on the screen. A fold whose first row is hidden by another fold is merged
+// into it. Folds of a single row hide nothing and are dropped.
+pub fn merge(folds: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut sorted: Vec<_> = folds.iter().filter(|f| f.len() > 1).cloned().collect();
+    sorted.sort_by_key(|f| f.start);
+    let mut merged: Vec<Range<usize>> = vec![];
+    for fold in sorted {
+        match merged.last_mut() {
+            Some(last) if fold.start < last.end => last.end = last.end.max(fold.end),
+            _ => merged.push(fold),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_folds() {
+        let tests = [
+            (&[][..], &[][..]),
+            (&[(0, 1), (2, 4)], &[(2, 4)]),
+            (&[(5, 8), (0, 3)], &[(0, 3), (5, 8)]),
+            (&[(0, 5), (3, 8)], &[(0, 8)]),
+            (&[(0, 8), (2, 4)], &[(0, 8)]),
+            (&[(0, 5), (4, 6)], &[(0, 6)]),
+            (&[(0, 5), (5, 6)], &[(0, 5)]),
+            (&[(0, 5), (5, 7)], &[(0, 5), (5, 7)]),
+        ];
+        for (folds, want) in tests {
+            let folds: Vec<_> = folds.iter().map(|&(s, e)| s..e).collect();
+            let have: Vec<_> = merge(&folds).iter().map(|f| (f.start, f.end)).collect();
+            assert_eq!(have, want, "folds={folds:?}");
+        }
+    }
+
+    #[test]
+    fn indent_folds() {
+        let lines = [
+            "a",     // 0
+            "  b",   // 1
+            "",      // 2
+            "  c",   // 3
+            "    d", // 4
+            "",      // 5
+            "e",     // 6
+            "\tf",   // 7
+            "   ",   // 8
+        ];
+        let folds = IndentFolds::new(2);
+        let tests = [
+            (0, Some(0..5)),
+            (1, Some(0..5)),
+            (2, Some(0..5)),
+            (3, Some(3..5)),
+            (4, Some(3..5)),
+            (5, None),
+            (6, Some(6..8)),
+            (7, Some(6..8)),
+            (8, None),
+            (9, None),
+        ];
+        for (row, want) in tests {
+            assert_eq!(folds.fold_range(&lines, row), want, "row={row}");
+        }
+    }
+}
@@ -3,39 +3,51 @@ use crate::ratatui::text::Span;
 use crate::util::{num_digits, spaces};
 use ratatui::text::Line;
 use std::borrow::Cow;
-use std::cmp::Ordering;
+use std::cmp;
 use std::iter;
 use unicode_width::UnicodeWidthChar as _;
 
+// Highlighted range in a line. When ranges overlap, the style of the highest ranked one is applied. Styles of the text
+// itself are patched onto the line style and onto each other instead.
 enum Boundary {
     Cursor(Style),
+    RemoteCursor(Style),
     Select(Style),
+    RemoteSelect(Style),
     #[cfg(feature = "search")]
     Search(Style),
-    End,
+    #[cfg(feature = "search")]
+    CurrentSearch(Style),
+    // Style of the text itself such as its author or its modification
+    Text(Style),
 }
 
 impl Boundary {
-    fn cmp(&self, other: &Boundary) -> Ordering {
-        fn rank(b: &Boundary) -> u8 {
-            match b {
-                Boundary::Cursor(_) => 3,
-                #[cfg(feature = "search")]
-                Boundary::Search(_) => 2,
-                Boundary::Select(_) => 1,
-                Boundary::End => 0,
-            }
+    fn rank(&self) -> u8 {
+        match self {
+            Boundary::Cursor(_) => 6,
+            Boundary::RemoteCursor(_) => 5,
+            #[cfg(feature = "search")]
+            Boundary::CurrentSearch(_) => 4,
+            #[cfg(feature = "search")]
+            Boundary::Search(_) => 3,
+            Boundary::Select(_) => 2,
+            Boundary::RemoteSelect(_) => 1,
+            Boundary::Text(_) => 0,
         }
-        rank(self).cmp(&rank(other))
     }
 
-    fn style(&self) -> Option<Style> {
+    fn style(&self) -> Style {
         match self {
-            Boundary::Cursor(s) => Some(*s),
-            Boundary::Select(s) => Some(*s),
+            Boundary::Cursor(s) => *s,
+            Boundary::RemoteCursor(s) => *s,
+            Boundary::Select(s) => *s,
+            Boundary::RemoteSelect(s) => *s,
+            #[cfg(feature = "search")]
+            Boundary::Search(s) => *s,
             #[cfg(feature = "search")]
-            Boundary::Search(s) => Some(*s),
-            Boundary::End => None,
+            Boundary::CurrentSearch(s) => *s,
+            Boundary::Text(s) => *s,
         }
     }
 }
@@ -58,7 +70,7 @@ impl DisplayTextBuilder {
     fn build<'s>(&mut self, s: &'s str) -> Cow<'s, str> {
         if let Some(ch) = self.mask {
             // Note: We don't need to track width on masking text since width of tab character is fixed
-            let masked = iter::repeat(ch).take(s.chars().count()).collect();
+            let masked = iter::repeat_n(ch, s.chars().count()).collect();
             return Cow::Owned(masked);
         }
 
@@ -94,7 +106,7 @@ impl DisplayTextBuilder {
 pub struct LineHighlighter<'a> {
     line: &'a str,
     spans: Vec<Span<'a>>,
-    boundaries: Vec<(Boundary, usize)>, // TODO: Consider smallvec
+    boundaries: Vec<(Boundary, usize, usize)>, // TODO: Consider smallvec
     style_begin: Style,
     cursor_at_end: bool,
     cursor_style: Style,
@@ -102,6 +114,12 @@ pub struct LineHighlighter<'a> {
     mask: Option<char>,
     select_at_end: bool,
     select_style: Style,
+    remote_cursor_at_end: Option<Style>,
+    remote_select_at_end: Option<Style>,
+    search_at_end: Option<Style>,
+    text_at_end: Option<Style>,
+    continued: bool,
+    flags: Vec<Span<'a>>,
 }
 
 impl<'a> LineHighlighter<'a> {
@@ -123,6 +141,12 @@ impl<'a> LineHighlighter<'a> {
             mask,
             select_at_end: false,
             select_style,
+            remote_cursor_at_end: None,
+            remote_select_at_end: None,
+            search_at_end: None,
+            text_at_end: None,
+            continued: false,
+            flags: vec![],
         }
     }
 
@@ -132,24 +156,94 @@ impl<'a> LineHighlighter<'a> {
             .push(Span::styled(format!("{}{} ", pad, row + 1), style));
     }
 
+    // Blank space as wide as the line number for the rows continuing a wrapped line
+    pub fn line_number_padding(&mut self, lnum_len: u8, style: Style) {
+        self.spans.push(Span::styled(spaces(lnum_len + 2), style));
+    }
+
+    // Placeholder of the lines hidden by a fold after the end of this line
+    pub fn folded(&mut self, placeholder: String, style: Style) {
+        self.flags.insert(0, Span::styled(placeholder, style));
+    }
+
+    // Insert the sign column at the head of the gutter. The sign must be one column wide.
+    pub fn sign(&mut self, sign: &'a str, style: Style) {
+        self.spans.insert(0, Span::styled(sign, style));
+    }
+
+    // Replace the padding at the head of the line number with the marker. The marker must be one column wide.
+    pub fn line_number_marker(&mut self, marker: &'a str, style: Style) {
+        let Some(lnum) = self.spans.first_mut() else {
+            return;
+        };
+        *lnum = Span::styled(lnum.content[1..].to_string(), lnum.style);
+        self.spans.insert(0, Span::styled(marker, style));
+    }
+
     pub fn cursor_line(&mut self, cursor_col: usize, style: Style) {
         if let Some((start, c)) = self.line.char_indices().nth(cursor_col) {
+            let end = start + c.len_utf8();
             self.boundaries
-                .push((Boundary::Cursor(self.cursor_style), start));
-            self.boundaries.push((Boundary::End, start + c.len_utf8()));
+                .push((Boundary::Cursor(self.cursor_style), start, end));
         } else {
             self.cursor_at_end = true;
         }
         self.style_begin = style;
     }
 
+    // The style of the line without the cursor. It is used for the rows of a wrapped cursor line which the cursor is not
+    // in.
+    pub fn line_style(&mut self, style: Style) {
+        self.style_begin = style;
+    }
+
+    // This row is a part of a wrapped line which continues on the next row. Nothing is rendered after the end of it
+    // since the newline is not in this row.
+    pub fn continued(&mut self) {
+        self.continued = true;
+    }
+
+    // Matches of the text search as ranges of chars in the text. A match can span multiple lines.
     #[cfg(feature = "search")]
-    pub fn search(&mut self, matches: impl Iterator<Item = (usize, usize)>, style: Style) {
-        for (start, end) in matches {
-            if start != end {
-                self.boundaries.push((Boundary::Search(style), start));
-                self.boundaries.push((Boundary::End, end));
-            }
+    pub fn search(
+        &mut self,
+        line_start: usize,
+        line_end: usize,
+        matches: &[(usize, usize)],
+        style: Style,
+    ) {
+        for &(start, end) in matches {
+            self.search_match(Boundary::Search(style), line_start, line_end, start, end);
+        }
+    }
+
+    // The match at the cursor. It is ranked over the other matches
+    #[cfg(feature = "search")]
+    pub fn current_search(
+        &mut self,
+        line_start: usize,
+        line_end: usize,
+        (start, end): (usize, usize),
+        style: Style,
+    ) {
+        let boundary = Boundary::CurrentSearch(style);
+        self.search_match(boundary, line_start, line_end, start, end);
+    }
+
+    #[cfg(feature = "search")]
+    fn search_match(
+        &mut self,
+        boundary: Boundary,
+        line_start: usize,
+        line_end: usize,
+        start: usize,
+        end: usize,
+    ) {
+        let style = boundary.style();
+        // The newline at the end of the line is highlighted when the match continues to the next line. Matches don't
+        // overlap, so the current match is the same one as the other match ending the line
+        if self.range(boundary, line_start, line_end, start, end) && end > line_end {
+            self.search_at_end = Some(style);
         }
     }
 
@@ -160,39 +254,109 @@ impl<'a> LineHighlighter<'a> {
         sel_start: usize,
         sel_end: usize,
     ) {
-        if sel_start == sel_end {
-            return;
+        // `line_end` is the offset of the last char of this line
+        let boundary = Boundary::Select(self.select_style);
+        if self.range(boundary, line_start, line_end + 1, sel_start, sel_end) {
+            self.select_at_end = true;
         }
+    }
 
-        if line_end + 1 < sel_start || line_start >= sel_end {
-            return;
+    // The caret of a remote peer. The label is shown as a flag at the end of the line so that it does not shift the
+    // text after the caret.
+    pub fn remote_cursor(&mut self, col: usize, style: Style, label: Option<&'a str>) {
+        if let Some((start, c)) = self.line.char_indices().nth(col) {
+            let end = start + c.len_utf8();
+            self.boundaries
+                .push((Boundary::RemoteCursor(style), start, end));
+        } else {
+            self.remote_cursor_at_end.get_or_insert(style);
+        }
+        if let Some(label) = label {
+            self.flags.push(Span::raw(" "));
+            self.flags.push(Span::styled(label, style));
         }
+    }
 
-        if sel_start == line_end + 1 {
-            self.select_at_end = true;
+    pub fn remote_selection(
+        &mut self,
+        line_start: usize,
+        line_end: usize,
+        sel_start: usize,
+        sel_end: usize,
+        style: Style,
+    ) {
+        let boundary = Boundary::RemoteSelect(style);
+        if self.range(boundary, line_start, line_end, sel_start, sel_end) {
+            self.remote_select_at_end.get_or_insert(style);
         }
-        if sel_end > line_end {
-            self.select_at_end = true;
+    }
+
+    // Styles of the text itself such as its author or its marks. Each run is a range of chars in the text with its
+    // style.
+    pub fn text_styles(
+        &mut self,
+        line_start: usize,
+        line_end: usize,
+        runs: &[(usize, usize, Style)],
+    ) {
+        for &(start, end, style) in runs {
+            self.range(Boundary::Text(style), line_start, line_end, start, end);
         }
+    }
 
-        let start = sel_start.saturating_sub(line_start);
+    // Modified text in a diff. `start` and `end` are char columns of this line. When the range covers the newline at the
+    // end of this line, the end of this line is also styled.
+    pub fn diff(&mut self, start: usize, end: usize, style: Style) {
+        let line_end = self.line.chars().count();
+        self.range(Boundary::Text(style), 0, line_end, start, end);
+        if end > line_end {
+            self.text_at_end.get_or_insert(style);
+        }
+    }
 
-        let end = if sel_end > line_end {
-            line_end + 1 - line_start
-        } else {
-            sel_end - line_start
+    // Highlight the range of chars between `start` and `end` in the text. `line_start` and `line_end` are the offsets
+    // of the start and the end (exclusive) of this line. Returns whether the range reaches the end of this line.
+    fn range(
+        &mut self,
+        boundary: Boundary,
+        line_start: usize,
+        line_end: usize,
+        start: usize,
+        end: usize,
+    ) -> bool {
+        if start == end {
+            return false;
+        }
+
+        if line_end < start || line_start >= end {
+            return false;
+        }
+
+        let at_end = end >= line_end;
+        let start = start.saturating_sub(line_start);
+        let end = cmp::min(end, line_end) - line_start;
+
+        // Convert the char columns into byte indices of the line
+        let byte = |col: usize| {
+            self.line
+                .char_indices()
+                .nth(col)
+                .map(|(i, _)| i)
+                .unwrap_or(self.line.len())
         };
+        let (start, end) = (byte(start), byte(end));
 
-        self.boundaries
-            .push((Boundary::Select(self.select_style), start));
-        self.boundaries.push((Boundary::End, end));
+        if start < end {
+            self.boundaries.push((boundary, start, end));
+        }
+        at_end
     }
 
     pub fn into_spans(self) -> Line<'a> {
         let Self {
             line,
             mut spans,
-            mut boundaries,
+            boundaries,
             tab_len,
             style_begin,
             cursor_style,
@@ -200,54 +364,65 @@ impl<'a> LineHighlighter<'a> {
             mask,
             select_at_end,
             select_style,
+            remote_cursor_at_end,
+            remote_select_at_end,
+            search_at_end,
+            text_at_end,
+            continued,
+            flags,
         } = self;
         let mut builder = DisplayTextBuilder::new(tab_len, mask);
+        let end_style = if continued {
+            None
+        } else if cursor_at_end {
+            Some(cursor_style)
+        } else {
+            remote_cursor_at_end
+                .or(search_at_end)
+                .or(select_at_end.then_some(select_style))
+                .or(remote_select_at_end)
+                .or(text_at_end)
+        };
 
         if boundaries.is_empty() {
             let built = builder.build(line);
             if !built.is_empty() {
                 spans.push(Span::styled(built, style_begin));
             }
-            if cursor_at_end {
-                spans.push(Span::styled(" ", cursor_style));
-            } else if select_at_end {
-                spans.push(Span::styled(" ", select_style));
+            if let Some(style) = end_style {
+                spans.push(Span::styled(" ", style));
             }
+            spans.extend(flags);
             return Line::from(spans);
         }
 
-        boundaries.sort_unstable_by(|(l, i), (r, j)| match i.cmp(j) {
-            Ordering::Equal => l.cmp(r),
-            o => o,
-        });
-
-        let mut style = style_begin;
-        let mut start = 0;
-        let mut stack = vec![];
-
-        for (next_boundary, end) in boundaries {
-            if start < end {
-                spans.push(Span::styled(builder.build(&line[start..end]), style));
-            }
-
-            style = if let Some(s) = next_boundary.style() {
-                stack.push(style);
-                s
-            } else {
-                stack.pop().unwrap_or(style_begin)
+        // Split the line at every edge of the ranges and style each piece with the highest ranked range covering it
+        let mut edges: Vec<usize> = boundaries
+            .iter()
+            .flat_map(|(_, start, end)| [*start, *end])
+            .chain([0, line.len()])
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+
+        for w in edges.windows(2) {
+            let (start, end) = (w[0], w[1]);
+            let covering = boundaries
+                .iter()
+                .filter(|(_, s, e)| *s <= start && end <= *e)
+                .map(|(b, _, _)| b);
+            let style = match covering.clone().max_by_key(|b| b.rank()) {
+                None => style_begin,
+                Some(Boundary::Text(_)) => covering.fold(style_begin, |s, b| s.patch(b.style())),
+                Some(b) => b.style(),
             };
-            start = end;
-        }
-
-        if start != line.len() {
-            spans.push(Span::styled(builder.build(&line[start..]), style));
+            spans.push(Span::styled(builder.build(&line[start..end]), style));
         }
 
-        if cursor_at_end {
-            spans.push(Span::styled(" ", cursor_style));
-        } else if select_at_end {
-            spans.push(Span::styled(" ", select_style));
+        if let Some(style) = end_style {
+            spans.push(Span::styled(" ", style));
         }
+        spans.extend(flags);
 
         Line::from(spans)
     }
@@ -256,7 +431,7 @@ impl<'a> LineHighlighter<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ratatui::style::Color;
+    use crate::ratatui::style::{Color, Modifier};
     use std::fmt::Debug;
     use unicode_width::UnicodeWidthStr as _;
 
@@ -368,6 +543,11 @@ mod tests {
     const SEL: Style = Style::new().bg(Color::Blue);
     const LINE: Style = Style::new().bg(Color::Gray);
     const LNUM: Style = Style::new().bg(Color::Yellow);
+    const PEER: Style = Style::new().bg(Color::Magenta);
+    const PEER_SEL: Style = Style::new().bg(Color::Cyan);
+    const ALICE: Style = Style::new().fg(Color::Green);
+    const BOB: Style = Style::new().fg(Color::Magenta);
+    const BOLD: Style = Style::new().add_modifier(Modifier::BOLD);
 
     #[test]
     fn into_spans_normal_line() {
@@ -418,6 +598,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn into_spans_line_number_marker() {
+        let tests = [
+            (0, 1, &[("*", PEER), ("1 ", LNUM)][..]),
+            (123, 5, &[("*", PEER), ("  124 ", LNUM)][..]),
+        ];
+        for test in tests {
+            let (row, len, want) = test;
+            let mut lh = LineHighlighter::new("", CUR, 4, None, SEL);
+            lh.line_number(row, len, LNUM);
+            lh.line_number_marker("*", PEER);
+            assert_spans(lh, want, test);
+        }
+    }
+
     #[cfg(feature = "search")]
     #[test]
     fn into_spans_search() {
@@ -453,7 +648,7 @@ mod tests {
             ("abcde", &[(1, 1)][..], &[("abcde", DEFAULT)][..]),
             (
                 "あいうえお",
-                &[(0, 3), (6, 9), (12, 15)][..],
+                &[(0, 1), (2, 3), (4, 5)][..],
                 &[
                     ("あ", SEARCH),
                     ("い", DEFAULT),
@@ -478,7 +673,44 @@ mod tests {
         for test in tests {
             let (line, matches, want) = test;
             let mut lh = LineHighlighter::new(line, CUR, 4, None, SEL);
-            lh.search(matches.iter().copied(), SEARCH);
+            lh.search(0, line.chars().count(), matches, SEARCH);
+            assert_spans(lh, want, test);
+        }
+    }
+
+    #[cfg(feature = "search")]
+    #[test]
+    fn into_spans_search_multi_lines() {
+        let tests = [
+            // Match continues to the next line
+            (
+                "ab",
+                0,
+                &[(1, 4)][..],
+                &[("a", DEFAULT), ("b", SEARCH), (" ", SEARCH)][..],
+            ),
+            // Match continued from the previous line
+            (
+                "cde",
+                3,
+                &[(1, 4)][..],
+                &[("c", SEARCH), ("de", DEFAULT)][..],
+            ),
+            // Match ends at the end of the line
+            (
+                "cde",
+                3,
+                &[(4, 6)][..],
+                &[("c", DEFAULT), ("de", SEARCH)][..],
+            ),
+            ("cde", 3, &[(0, 2), (7, 8)][..], &[("cde", DEFAULT)][..]),
+        ];
+
+        for test in tests {
+            let (line, line_start, matches, want) = test;
+            let line_end = line_start + line.chars().count();
+            let mut lh = LineHighlighter::new(line, CUR, 4, None, SEL);
+            lh.search(line_start, line_end, matches, SEARCH);
             assert_spans(lh, want, test);
         }
     }
@@ -513,6 +745,108 @@ mod tests {
         }
     }
 
+    #[test]
+    fn into_spans_remote_cursor() {
+        let tests = [
+            ("", 0, None, &[(" ", PEER)][..]),
+            (
+                "abc",
+                1,
+                None,
+                &[("a", DEFAULT), ("b", PEER), ("c", DEFAULT)][..],
+            ),
+            ("abc", 3, None, &[("abc", DEFAULT), (" ", PEER)][..]),
+            (
+                "あいう",
+                1,
+                None,
+                &[("あ", DEFAULT), ("い", PEER), ("う", DEFAULT)][..],
+            ),
+            (
+                "abc",
+                0,
+                Some("bob"),
+                &[("a", PEER), ("bc", DEFAULT), (" ", DEFAULT), ("bob", PEER)][..],
+            ),
+        ];
+
+        for test in tests {
+            let (line, col, label, want) = test;
+            let mut lh = LineHighlighter::new(line, CUR, 4, None, SEL);
+            lh.remote_cursor(col, PEER, label);
+            assert_spans(lh, want, test);
+        }
+    }
+
+    #[test]
+    fn into_spans_remote_selection() {
+        let tests = [
+            (
+                "abc",
+                (0, 3, 1, 2),
+                &[("a", DEFAULT), ("b", PEER_SEL), ("c", DEFAULT)][..],
+            ),
+            (
+                "abc",
+                (0, 3, 1, 5),
+                &[("a", DEFAULT), ("bc", PEER_SEL), (" ", PEER_SEL)][..],
+            ),
+            ("abc", (4, 7, 0, 2), &[("abc", DEFAULT)][..]),
+            (
+                "あいう",
+                (4, 7, 5, 6),
+                &[("あ", DEFAULT), ("い", PEER_SEL), ("う", DEFAULT)][..],
+            ),
+        ];
+
+        for test in tests {
+            let (text, (line_start, line_end, sel_start, sel_end), want) = test;
+            let mut lh = LineHighlighter::new(text, CUR, 4, None, SEL);
+            lh.remote_selection(line_start, line_end, sel_start, sel_end, PEER_SEL);
+            assert_spans(lh, want, test);
+        }
+    }
+
+    #[test]
+    fn into_spans_text_styles() {
+        let tests = [
+            (
+                "abcd",
+                0,
+                &[(0, 2, ALICE), (2, 4, BOB)][..],
+                &[("ab", ALICE), ("cd", BOB)][..],
+            ),
+            (
+                "abcd",
+                0,
+                &[(1, 3, BOB)][..],
+                &[("a", DEFAULT), ("bc", BOB), ("d", DEFAULT)][..],
+            ),
+            // Runs outside the line are ignored
+            (
+                "あいう",
+                5,
+                &[(0, 6, ALICE), (6, 7, BOB), (9, 12, ALICE)][..],
+                &[("あ", ALICE), ("い", BOB), ("う", DEFAULT)][..],
+            ),
+            // Overlapping styles are patched
+            (
+                "abc",
+                0,
+                &[(0, 3, ALICE), (1, 2, BOLD)][..],
+                &[("a", ALICE), ("b", ALICE.patch(BOLD)), ("c", ALICE)][..],
+            ),
+        ];
+
+        for test in tests {
+            let (line, line_start, runs, want) = test;
+            let line_end = line_start + line.chars().count();
+            let mut lh = LineHighlighter::new(line, CUR, 4, None, SEL);
+            lh.text_styles(line_start, line_end, runs);
+            assert_spans(lh, want, test);
+        }
+    }
+
     #[test]
     fn into_spans_mixed_highlights() {
         let tests = [
@@ -533,7 +867,7 @@ mod tests {
                     let mut lh = LineHighlighter::new("abcdefg", CUR, 4, None, SEL);
                     lh.cursor_line(3, LINE);
                     lh.selection(0, 6, 2, 5);
-                    lh.search([(1, 2), (5, 6)].into_iter(), SEARCH);
+                    lh.search(0, 7, &[(1, 2), (5, 6)], SEARCH);
                     lh
                 },
                 &[
@@ -586,6 +920,79 @@ mod tests {
                 },
                 &[("a", LINE), ("b", CUR), ("c", LINE)][..],
             ),
+            (
+                "selection across blame runs",
+                {
+                    let mut lh = LineHighlighter::new("abcdef", CUR, 4, None, SEL);
+                    lh.cursor_line(5, LINE);
+                    lh.text_styles(0, 6, &[(0, 3, ALICE), (3, 6, BOB)]);
+                    lh.selection(0, 5, 2, 4);
+                    lh
+                },
+                &[
+                    ("ab", LINE.patch(ALICE)),
+                    ("c", SEL),
+                    ("d", SEL),
+                    ("e", LINE.patch(BOB)),
+                    ("f", CUR),
+                ][..],
+            ),
+        ];
+
+        for (what, lh, want) in tests {
+            assert_spans(lh, want, what);
+        }
+    }
+
+    #[test]
+    fn into_spans_remote_mixed_highlights() {
+        let tests = [
+            (
+                "local cursor on remote cursor",
+                {
+                    let mut lh = LineHighlighter::new("abc", CUR, 4, None, SEL);
+                    lh.cursor_line(1, LINE);
+                    lh.remote_cursor(1, PEER, None);
+                    lh
+                },
+                &[("a", LINE), ("b", CUR), ("c", LINE)][..],
+            ),
+            (
+                "local and remote cursors at end",
+                {
+                    let mut lh = LineHighlighter::new("ab", CUR, 4, None, SEL);
+                    lh.remote_cursor(2, PEER, None);
+                    lh.cursor_line(2, LINE);
+                    lh
+                },
+                &[("ab", LINE), (" ", CUR)][..],
+            ),
+            (
+                "remote cursor on selection",
+                {
+                    let mut lh = LineHighlighter::new("abcd", CUR, 4, None, SEL);
+                    lh.selection(0, 3, 1, 4);
+                    lh.remote_cursor(2, PEER, None);
+                    lh
+                },
+                &[
+                    ("a", DEFAULT),
+                    ("b", SEL),
+                    ("c", PEER),
+                    ("d", SEL),
+                    (" ", SEL),
+                ][..],
+            ),
+            (
+                "remote selection under selection",
+                {
+                    let mut lh = LineHighlighter::new("abcd", CUR, 4, None, SEL);
+                    lh.remote_selection(0, 4, 1, 3, PEER_SEL);
+                    lh.selection(0, 3, 1, 3);
+                    lh
+                },
+                &[("a", DEFAULT), ("bc", SEL), ("d", DEFAULT)][..],
+            ),
         ];
 
         for (what, lh, want) in tests {
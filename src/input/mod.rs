@@ -14,7 +14,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// This type is marked as `#[non_exhaustive]` since more keys may be supported in the future.
 #[non_exhaustive]
-#[derive(Clone, Copy, Debug, PartialEq, Hash, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Hash, Eq)]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Key {
@@ -59,15 +59,10 @@ pub enum Key {
     /// Virtual key to scroll up by mouse
     MouseScrollUp,
     /// An invalid key input (this key is always ignored by [`MergeArea`](crate::MergeArea))
+    #[default]
     Null,
 }
 
-impl Default for Key {
-    fn default() -> Self {
-        Key::Null
-    }
-}
-
 /// Backend-agnostic key input type.
 ///
 /// When `crossterm`, `termion`, `termwiz` features are enabled, converting respective key input types into this
@@ -4,18 +4,32 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![doc = include_str!("../README.md")]
 
+mod blame;
+mod comment;
 mod cursor;
+mod diagnostic;
+mod diff;
+mod document;
+mod fold;
 mod highlight;
 mod history;
-mod history_v2;
 mod input;
+mod marks;
+#[cfg(feature = "tree-sitter")]
+mod parse_tree;
+mod remote;
 mod scroll;
+mod scrub;
 #[cfg(feature = "search")]
 mod search;
+#[cfg(feature = "sync")]
+mod sync;
+mod syntax;
 mod textarea;
 mod util;
 mod widget;
 mod word;
+mod wrap;
 
 #[allow(clippy::single_component_path_imports)]
 use ratatui;
@@ -28,7 +42,20 @@ use crossterm;
 #[allow(clippy::single_component_path_imports)]
 use termion;
 
+pub use comment::Comment;
 pub use cursor::CursorMove;
+pub use diagnostic::{Diagnostic, Severity};
+pub use diff::DiffView;
+pub use fold::{FoldProvider, IndentFolds};
 pub use input::{Input, Key};
+pub use remote::{RemoteCursor, RemotePosition};
 pub use scroll::Scrolling;
+#[cfg(feature = "search")]
+pub use search::SearchOptions;
+#[cfg(feature = "sync")]
+pub use sync::{SyncError, SyncSession};
+pub use syntax::Highlighter;
+#[cfg(feature = "syntect")]
+pub use syntax::SyntectHighlighter;
 pub use textarea::MergeArea;
+pub use wrap::WrapMode;
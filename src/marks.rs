@@ -0,0 +1,27 @@
+use crate::comment;
+use crate::ratatui::style::Style;
+use crate::util::SharedFn;
+use automerge::ScalarValue;
+use automerge::marks::Mark;
+use std::sync::Arc;
+
+type StyleFn = dyn Fn(&str, &ScalarValue) -> Style + Send + Sync;
+
+// Styles the text by its rich-text marks
+#[derive(Clone, Debug)]
+pub struct MarkStyle(SharedFn<StyleFn>);
+
+impl MarkStyle {
+    pub fn new(style: impl Fn(&str, &ScalarValue) -> Style + Send + Sync + 'static) -> Self {
+        Self(SharedFn::new(Arc::new(style)))
+    }
+
+    // Styled ranges of the marks. Marks of comments are not styled by the callback.
+    pub fn runs(&self, marks: &[Mark<'_>]) -> Vec<(usize, usize, Style)> {
+        marks
+            .iter()
+            .filter(|m| !comment::is_comment_mark(m.name()))
+            .map(|m| (m.start, m.end, (self.0)(m.name(), m.value())))
+            .collect()
+    }
+}
@@ -0,0 +1,171 @@
+use crate::ratatui::style::Style;
+use crate::util::SharedFn;
+use std::fmt;
+use std::sync::Arc;
+use tree_sitter::{
+    InputEdit, Language, Parser, Point, Query, QueryCursor, QueryError, QueryErrorKind,
+    StreamingIterator as _, Tree,
+};
+
+type StyleFn = dyn Fn(&str) -> Style + Send + Sync;
+
+// Parse tree of the text maintained by tree-sitter. The tree is edited on every splice of the text and parsed again
+// incrementally, so only the modified part of the text is parsed. The text is highlighted by the captures of the query.
+pub struct ParseTree {
+    language: Language,
+    parser: Parser,
+    tree: Option<Tree>,
+    query: Arc<Query>,
+    style: SharedFn<StyleFn>,
+}
+
+impl fmt::Debug for ParseTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParseTree")
+            .field("language", &self.language.name())
+            .field("tree", &self.tree)
+            .finish_non_exhaustive()
+    }
+}
+
+// Parsers can't be cloned. A new parser is created for the language and the tree is cloned
+impl Clone for ParseTree {
+    fn clone(&self) -> Self {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&self.language)
+            .expect("language was set to the parser");
+        Self {
+            language: self.language.clone(),
+            parser,
+            tree: self.tree.clone(),
+            query: self.query.clone(),
+            style: self.style.clone(),
+        }
+    }
+}
+
+// Position of the end of the text as tree-sitter counts it. Columns are counted in bytes
+fn point(s: &str) -> Point {
+    match s.rfind('\n') {
+        Some(i) => Point::new(s.matches('\n').count(), s.len() - i - 1),
+        None => Point::new(0, s.len()),
+    }
+}
+
+impl ParseTree {
+    pub fn new(
+        language: Language,
+        query: &str,
+        style: impl Fn(&str) -> Style + Send + Sync + 'static,
+        text: &str,
+    ) -> Result<Self, QueryError> {
+        let query = Query::new(&language, query)?;
+        let mut parser = Parser::new();
+        if let Err(err) = parser.set_language(&language) {
+            return Err(QueryError {
+                row: 0,
+                column: 0,
+                offset: 0,
+                message: err.to_string(),
+                kind: QueryErrorKind::Language,
+            });
+        }
+        let mut tree = Self {
+            language,
+            parser,
+            tree: None,
+            query: Arc::new(query),
+            style: SharedFn::new(Arc::new(style)),
+        };
+        tree.parse(text);
+        Ok(tree)
+    }
+
+    pub fn tree(&self) -> Option<&Tree> {
+        self.tree.as_ref()
+    }
+
+    // Tell the tree that the bytes between `start` and `end` of the text are about to be replaced with `inserted`. The
+    // tree must be parsed again after the text is modified.
+    pub fn edit(&mut self, text: &str, start: usize, end: usize, inserted: &str) {
+        let Some(tree) = &mut self.tree else {
+            return;
+        };
+        let start_position = point(&text[..start]);
+        let added = point(inserted);
+        let new_end_position = if added.row == 0 {
+            Point::new(start_position.row, start_position.column + added.column)
+        } else {
+            Point::new(start_position.row + added.row, added.column)
+        };
+        tree.edit(&InputEdit {
+            start_byte: start,
+            old_end_byte: end,
+            new_end_byte: start + inserted.len(),
+            start_position,
+            old_end_position: point(&text[..end]),
+            new_end_position,
+        });
+    }
+
+    // Parse the text reusing the unmodified parts of the edited tree
+    pub fn parse(&mut self, text: &str) {
+        self.tree = self.parser.parse(text, self.tree.as_ref());
+    }
+
+    // Parse the text from scratch after the entire text was replaced
+    pub fn reset(&mut self, text: &str) {
+        self.tree = None;
+        self.parse(text);
+    }
+
+    // Styled runs of chars between the char offsets `start` and `end` in the text. When several patterns capture the
+    // same node, the first pattern in the query is applied. Nested captures are patched onto outer ones.
+    pub fn runs(&self, text: &str, start: usize, end: usize) -> Vec<(usize, usize, Style)> {
+        let Some(tree) = &self.tree else {
+            return vec![];
+        };
+
+        let byte = |offset: usize| {
+            text.char_indices()
+                .nth(offset)
+                .map_or(text.len(), |(i, _)| i)
+        };
+        let (first, last) = (byte(start), byte(end));
+        // Only the bytes in the range are looked at, so chars are counted from the start of the range
+        let offset = |i: usize| start + text[first..i.clamp(first, last)].chars().count();
+
+        let mut cursor = QueryCursor::new();
+        cursor.set_byte_range(first..last);
+        let names = self.query.capture_names();
+        let mut captures = cursor.captures(&self.query, tree.root_node(), text.as_bytes());
+
+        let mut runs = vec![];
+        let mut prev = None;
+        while let Some((m, i)) = captures.next() {
+            let capture = m.captures[*i];
+            let range = capture.node.byte_range();
+            if prev == Some((range.start, range.end)) {
+                continue;
+            }
+            prev = Some((range.start, range.end));
+            let style = (self.style)(names[capture.index as usize]);
+            runs.push((offset(range.start), offset(range.end), style));
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_point() {
+        assert_eq!(point(""), Point::new(0, 0));
+        assert_eq!(point("ab"), Point::new(0, 2));
+        assert_eq!(point("a\n"), Point::new(1, 0));
+        assert_eq!(point("a\nbあ"), Point::new(1, 4));
+    }
+}
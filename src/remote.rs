@@ -0,0 +1,98 @@
+use crate::document::Document;
+use crate::ratatui::style::Style;
+use automerge::Cursor;
+use std::cmp;
+
+/// Position in the text of a remote peer's caret or selection.
+///
+/// A [`RemotePosition::Anchor`] keeps pointing to the same character while the text is edited locally or merged with
+/// other changes, so it is the preferred way to share positions between peers. A [`RemotePosition::Offset`] is used as
+/// is and is clamped to the end of the text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemotePosition {
+    /// 0-base char offset in the text.
+    Offset(usize),
+    /// Stable position in the automerge document bound to the textarea, obtained by `get_cursor` of the document.
+    Anchor(Cursor),
+}
+
+impl From<usize> for RemotePosition {
+    fn from(offset: usize) -> Self {
+        Self::Offset(offset)
+    }
+}
+
+impl From<Cursor> for RemotePosition {
+    fn from(cursor: Cursor) -> Self {
+        Self::Anchor(cursor)
+    }
+}
+
+/// Caret and selection of a remote peer editing the same text. Remote cursors are rendered by the textarea widget
+/// beneath the local cursor. Set them with [`crate::MergeArea::set_remote_cursors`].
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use ratatui_mergearea::RemoteCursor;
+///
+/// let mut peer = RemoteCursor::new("peer-1", 3, Style::default().bg(Color::Magenta));
+/// peer.label = Some("alice".to_string());
+/// peer.selection = Some(0.into());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemoteCursor {
+    /// Identifier of the peer, for example its automerge actor ID.
+    pub id: String,
+    /// Name of the peer shown as a flag at the end of the line where the caret is. No flag is shown when `None`.
+    pub label: Option<String>,
+    /// Position of the caret.
+    pub position: RemotePosition,
+    /// Position where the peer started text selection. The text between this position and the caret is selected.
+    pub selection: Option<RemotePosition>,
+    /// Style of the caret and the label.
+    pub style: Style,
+    /// Style of the selected text.
+    pub selection_style: Style,
+}
+
+impl RemoteCursor {
+    /// Create a remote cursor without label and selection. The selection style is the same as the caret style.
+    pub fn new(id: impl Into<String>, position: impl Into<RemotePosition>, style: Style) -> Self {
+        Self {
+            id: id.into(),
+            label: None,
+            position: position.into(),
+            selection: None,
+            style,
+            selection_style: style,
+        }
+    }
+}
+
+// Remote cursor resolved against the current text. Offsets are char offsets.
+pub struct Resolved<'a> {
+    pub caret: usize,
+    pub selection: Option<(usize, usize)>,
+    pub remote: &'a RemoteCursor,
+}
+
+impl<'a> Resolved<'a> {
+    // Returns `None` when the anchor of the caret does not belong to the document
+    pub fn new(remote: &'a RemoteCursor, text: &Document) -> Option<Self> {
+        let len = text.as_str().chars().count();
+        let resolve = |pos: &RemotePosition| match pos {
+            RemotePosition::Offset(o) => Some(cmp::min(*o, len)),
+            RemotePosition::Anchor(c) => text.cursor_offset(c).ok(),
+        };
+        let caret = resolve(&remote.position)?;
+        let selection = remote
+            .selection
+            .as_ref()
+            .and_then(resolve)
+            .map(|s| (cmp::min(s, caret), cmp::max(s, caret)));
+        Some(Self {
+            caret,
+            selection,
+            remote,
+        })
+    }
+}
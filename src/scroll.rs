@@ -70,7 +70,7 @@ pub enum Scrolling {
     PageDown,
     /// Scroll up the textarea by one page.
     ///
-    /// ```ignore
+    /// ```
     /// # use ratatui::buffer::Buffer;
     /// # use ratatui::layout::Rect;
     /// # use ratatui::widgets::Widget as _;
@@ -101,7 +101,7 @@ pub enum Scrolling {
     PageUp,
     /// Scroll down the textarea by half of the page.
     ///
-    /// ```ignore
+    /// ```
     /// # use ratatui::buffer::Buffer;
     /// # use ratatui::layout::Rect;
     /// # use ratatui::widgets::Widget as _;
@@ -110,7 +110,7 @@ pub enum Scrolling {
     /// // Let's say terminal height is 8.
     ///
     /// // Create textarea with 10 lines "0", "1", "2", "3", ...
-    /// let text = (0..20).map(|i| i.to_string()).collect::<Vec<_>>().join("\n") + "\n";
+    /// let text = (0..10).map(|i| i.to_string()).collect::<Vec<_>>().join("\n");
     /// let mut textarea = MergeArea::with_value(text);
     /// # // Call `render` at least once to populate terminal size
     /// # let r = Rect { x: 0, y: 0, width: 24, height: 8 };
@@ -128,7 +128,7 @@ pub enum Scrolling {
     HalfPageDown,
     /// Scroll up the textarea by half of the page.
     ///
-    /// ```ignore
+    /// ```
     /// # use ratatui::buffer::Buffer;
     /// # use ratatui::layout::Rect;
     /// # use ratatui::widgets::Widget as _;
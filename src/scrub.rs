@@ -0,0 +1,104 @@
+use crate::document::Document;
+use automerge::{AutomergeError, ChangeHash};
+use std::collections::HashSet;
+
+// Read-only view of the text as of past heads of the attached document
+#[derive(Clone, Debug)]
+pub struct Scrubber {
+    // All changes of the document in causal order with their dependencies. The view steps through them one by one
+    changes: Vec<(ChangeHash, Vec<ChangeHash>)>,
+    // Number of the changes applied in the view
+    index: usize,
+    heads: Vec<ChangeHash>,
+    text: String,
+}
+
+impl Scrubber {
+    // Start viewing the text at the current heads of the document. `None` is returned when no document is attached.
+    pub fn new(text: &mut Document) -> Option<Self> {
+        let changes = text.changes()?;
+        let index = changes.len();
+        let mut scrubber = Self {
+            changes,
+            index,
+            heads: vec![],
+            text: String::new(),
+        };
+        scrubber.heads = scrubber.prefix_heads(index);
+        scrubber.text = text.text_at(&scrubber.heads)?;
+        Some(scrubber)
+    }
+
+    pub fn heads(&self) -> &[ChangeHash] {
+        &self.heads
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn jump(&mut self, text: &Document, heads: &[ChangeHash]) -> Result<(), AutomergeError> {
+        let mut index = 0;
+        for head in heads {
+            let Some(i) = self.changes.iter().position(|(h, _)| h == head) else {
+                return Err(AutomergeError::InvalidHash(*head));
+            };
+            index = index.max(i + 1);
+        }
+        self.index = index;
+        self.heads = heads.to_vec();
+        self.text = text.text_at(heads).unwrap_or_default();
+        Ok(())
+    }
+
+    // Re-read the changes after the document was modified. The same heads are viewed
+    pub fn refresh(&mut self, text: &mut Document) {
+        let Some(changes) = text.changes() else {
+            return;
+        };
+        self.changes = changes;
+        let heads = std::mem::take(&mut self.heads);
+        self.jump(text, &heads)
+            .expect("viewed heads must be in modified document");
+    }
+
+    // Step to the previous change which modified the text. Returns `false` when there is no such change
+    pub fn back(&mut self, text: &Document) -> bool {
+        self.step(text, |i| i.checked_sub(1))
+    }
+
+    // Step to the next change which modified the text. Returns `false` when there is no such change
+    pub fn forward(&mut self, text: &Document) -> bool {
+        let len = self.changes.len();
+        self.step(text, |i| (i < len).then_some(i + 1))
+    }
+
+    fn step(&mut self, text: &Document, next: impl Fn(usize) -> Option<usize>) -> bool {
+        let mut index = self.index;
+        while let Some(i) = next(index) {
+            index = i;
+            let heads = self.prefix_heads(index);
+            let Some(t) = text.text_at(&heads) else {
+                return false;
+            };
+            if t != self.text {
+                self.index = index;
+                self.heads = heads;
+                self.text = t;
+                return true;
+            }
+        }
+        false
+    }
+
+    // Heads of the document which consists of the first `len` changes
+    fn prefix_heads(&self, len: usize) -> Vec<ChangeHash> {
+        let prefix = &self.changes[..len];
+        let deps: HashSet<_> = prefix.iter().flat_map(|(_, deps)| deps).collect();
+        prefix
+            .iter()
+            .map(|(h, _)| *h)
+            .filter(|h| !deps.contains(h))
+            .collect()
+    }
+}
@@ -1,154 +1,295 @@
 use crate::ratatui::style::{Color, Style};
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
+use std::sync::{Arc, Mutex};
+
+/// Options to compile the query of text search into a regular expression. Set them with
+/// [`MergeArea::set_search_options`](crate::MergeArea::set_search_options). By default, the query is compiled as a
+/// regular expression as is.
+/// ```
+/// use ratatui_mergearea::SearchOptions;
+///
+/// let opts = SearchOptions::default().literal(true).whole_word(true);
+/// let pat = opts.build("a.b").unwrap();
+/// assert!(pat.is_match("x a.b y"));
+/// assert!(!pat.is_match("x axb y"));
+/// assert!(!pat.is_match("xa.by"));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Match the query literally instead of as a regular expression.
+    pub literal: bool,
+    /// Ignore the case of letters.
+    pub case_insensitive: bool,
+    /// Match only at word boundaries. The query should start and end with word characters.
+    pub whole_word: bool,
+    /// Ignore the case of letters unless the query contains an uppercase letter. This takes precedence over
+    /// `case_insensitive`. Escape sequences such as `\S` in a regular expression are not counted as uppercase.
+    pub smart_case: bool,
+}
+
+impl SearchOptions {
+    /// Set whether the query is matched literally.
+    pub fn literal(mut self, yes: bool) -> Self {
+        self.literal = yes;
+        self
+    }
+
+    /// Set whether the case of letters is ignored.
+    pub fn case_insensitive(mut self, yes: bool) -> Self {
+        self.case_insensitive = yes;
+        self
+    }
+
+    /// Set whether the query matches only at word boundaries.
+    pub fn whole_word(mut self, yes: bool) -> Self {
+        self.whole_word = yes;
+        self
+    }
+
+    /// Set whether the case of letters is ignored unless the query contains an uppercase letter.
+    pub fn smart_case(mut self, yes: bool) -> Self {
+        self.smart_case = yes;
+        self
+    }
+
+    fn has_uppercase(&self, query: &str) -> bool {
+        if self.literal {
+            return query.chars().any(char::is_uppercase);
+        }
+        let mut escaped = false;
+        for c in query.chars() {
+            if !escaped && c.is_uppercase() {
+                return true;
+            }
+            escaped = !escaped && c == '\\';
+        }
+        false
+    }
+
+    /// Compile the query into a regular expression with the options. An error is returned when the query is not a
+    /// valid regular expression.
+    pub fn build(&self, query: &str) -> Result<Regex, regex::Error> {
+        let mut pat = if self.literal {
+            regex::escape(query)
+        } else {
+            query.to_string()
+        };
+        if self.whole_word {
+            pat = format!(r"\b(?:{})\b", pat);
+        }
+        let case_insensitive = if self.smart_case {
+            !self.has_uppercase(query)
+        } else {
+            self.case_insensitive
+        };
+        RegexBuilder::new(&pat)
+            .case_insensitive(case_insensitive)
+            .build()
+    }
+}
+
+type Matches = Arc<[(usize, usize)]>;
+
+// Matches in the text with the version of the text they were searched in. The cache is filled while rendering, so it
+// is behind a mutex to keep the textarea `Sync`.
+#[derive(Debug, Default)]
+struct MatchCache(Mutex<Option<(u64, Matches)>>);
+
+impl Clone for MatchCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().unwrap().clone()))
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Search {
     pub pat: Option<Regex>,
+    // The query and the options which `pat` was compiled from
+    pub query: String,
+    pub options: SearchOptions,
     pub style: Style,
+    // Style of the match at the cursor
+    pub current_style: Style,
+    cache: MatchCache,
 }
 
 impl Default for Search {
     fn default() -> Self {
         Self {
             pat: None,
+            query: String::new(),
+            options: SearchOptions::default(),
             style: Style::default().bg(Color::Blue),
+            current_style: Style::default().bg(Color::Yellow),
+            cache: MatchCache::default(),
         }
     }
 }
 
+// State of the incremental search started by `C-s` in `MergeArea::input_emacs`
+#[derive(Clone, Debug)]
+pub struct Isearch {
+    pub query: String,
+    pub backward: bool,
+    // The cursor and the pattern before the incremental search started. They are restored when it ends
+    pub origin: usize,
+    pub prev_pat: Option<Regex>,
+    pub prev_query: String,
+    pub prev_options: SearchOptions,
+}
+
+// Convert the char offset into the byte index of the text
+fn byte_index(text: &str, offset: usize) -> usize {
+    text.char_indices()
+        .nth(offset)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len())
+}
+
+fn char_offset(text: &str, index: usize) -> usize {
+    text[..index].chars().count()
+}
+
+// Index of the match containing the char offset. Matches must be sorted
+pub fn match_at(matches: &[(usize, usize)], offset: usize) -> Option<usize> {
+    let i = matches.partition_point(|&(start, _)| start <= offset);
+    let i = i.checked_sub(1)?;
+    (offset < matches[i].1).then_some(i)
+}
+
 impl Search {
+    // Matches in the whole text as ranges of char offsets. The pattern is matched against the whole text so that a
+    // match can span newlines.
     pub fn matches<'a>(
         &'a self,
-        line: &'a str,
+        text: &'a str,
     ) -> Option<impl Iterator<Item = (usize, usize)> + 'a> {
         let pat = self.pat.as_ref()?;
-        let matches = pat.find_iter(line).map(|m| (m.start(), m.end()));
+        // Count chars incrementally since matches are yielded in order
+        let (mut index, mut offset) = (0, 0);
+        let matches = pat.find_iter(text).map(move |m| {
+            offset += text[index..m.start()].chars().count();
+            let start = offset;
+            offset += m.as_str().chars().count();
+            index = m.end();
+            (start, offset)
+        });
         Some(matches)
     }
 
     pub fn set_pattern(&mut self, query: &str) -> Result<(), regex::Error> {
-        match &self.pat {
-            Some(r) if r.as_str() == query => {}
-            _ if query.is_empty() => self.pat = None,
-            _ => self.pat = Some(Regex::new(query)?),
-        }
-        Ok(())
+        self.set_pattern_with(query, self.options)
+    }
+
+    pub fn set_options(&mut self, options: SearchOptions) -> Result<(), regex::Error> {
+        let query = self.query.clone();
+        self.set_pattern_with(&query, options)
     }
 
-    pub fn forward(
+    // Compile the query with the options. Nothing is updated when the query is invalid
+    pub fn set_pattern_with(
         &mut self,
-        lines: &[String],
-        cursor: (usize, usize),
-        match_cursor: bool,
-    ) -> Option<(usize, usize)> {
-        let pat = if let Some(pat) = &self.pat {
-            pat
+        query: &str,
+        options: SearchOptions,
+    ) -> Result<(), regex::Error> {
+        if self.query == query && self.options == options {
+            return Ok(());
+        }
+        let pat = if query.is_empty() {
+            None
         } else {
-            return None;
+            Some(options.build(query)?)
         };
-        let (row, col) = cursor;
-        let current_line = &lines[row];
-
-        // Search current line after cursor
-        let start_col = if match_cursor { col } else { col + 1 };
-        if let Some((i, _)) = current_line.char_indices().nth(start_col) {
-            if let Some(m) = pat.find_at(current_line, i) {
-                let col = start_col + current_line[i..m.start()].chars().count();
-                return Some((row, col));
-            }
-        }
+        self.restore(pat, query.to_string(), options);
+        Ok(())
+    }
 
-        // Search lines after cursor
-        for (i, line) in lines[row + 1..].iter().enumerate() {
-            if let Some(m) = pat.find(line) {
-                let col = line[..m.start()].chars().count();
-                return Some((row + 1 + i, col));
-            }
-        }
+    // Set the pattern compiled from the query with the options
+    pub fn restore(&mut self, pat: Option<Regex>, query: String, options: SearchOptions) {
+        self.pat = pat;
+        self.query = query;
+        self.options = options;
+        self.cache = MatchCache::default();
+    }
 
-        // Search lines before cursor (wrap)
-        for (i, line) in lines[..row].iter().enumerate() {
-            if let Some(m) = pat.find(line) {
-                let col = line[..m.start()].chars().count();
-                return Some((i, col));
+    // Same as `Search::matches` but the matches are cached until the version of the text changes
+    pub fn cached_matches(&self, text: &str, version: u64) -> Matches {
+        let mut cache = self.cache.0.lock().unwrap();
+        if let Some((v, matches)) = &*cache {
+            if *v == version {
+                return matches.clone();
             }
         }
+        let matches: Matches = match self.matches(text) {
+            Some(matches) => matches.collect(),
+            None => Arc::new([]),
+        };
+        *cache = Some((version, matches.clone()));
+        matches
+    }
 
-        // Search current line before cursor
-        let col_idx = current_line
-            .char_indices()
-            .nth(col)
-            .map(|(i, _)| i)
-            .unwrap_or(current_line.len());
-        if let Some(m) = pat.find(current_line) {
-            let i = m.start();
-            if i <= col_idx {
-                let col = current_line[..i].chars().count();
-                return Some((row, col));
+    // Char offset of the next match from the cursor. When no match is after the cursor, the search wraps around to the
+    // head of the text.
+    pub fn forward(&self, text: &str, cursor: usize, match_cursor: bool) -> Option<usize> {
+        let pat = self.pat.as_ref()?;
+        let start = if match_cursor { cursor } else { cursor + 1 };
+        let i = byte_index(text, start);
+        if start <= text.chars().count() {
+            if let Some(m) = pat.find_at(text, i) {
+                return Some(char_offset(text, m.start()));
             }
         }
+        let m = pat.find(text)?;
+        Some(char_offset(text, m.start()))
+    }
 
-        None
+    // Char range of the next match at or after the cursor and the replacement of it. Capture groups such as `$1` in
+    // the replacement are expanded. The search wraps around to the head of the text.
+    pub fn replacement(
+        &self,
+        text: &str,
+        cursor: usize,
+        replacement: &str,
+    ) -> Option<(usize, usize, String)> {
+        let pat = self.pat.as_ref()?;
+        let i = byte_index(text, cursor);
+        let caps = pat.captures_at(text, i).or_else(|| pat.captures(text))?;
+        let m = caps.get(0).unwrap();
+        let mut replaced = String::new();
+        caps.expand(replacement, &mut replaced);
+        let start = char_offset(text, m.start());
+        Some((start, start + m.as_str().chars().count(), replaced))
     }
 
-    pub fn back(
-        &mut self,
-        lines: &[String],
-        cursor: (usize, usize),
-        match_cursor: bool,
-    ) -> Option<(usize, usize)> {
-        let pat = if let Some(pat) = &self.pat {
-            pat
-        } else {
-            return None;
+    // Char ranges of all matches in the text and their replacements in order
+    pub fn replacements(&self, text: &str, replacement: &str) -> Vec<(usize, usize, String)> {
+        let Some(pat) = &self.pat else {
+            return vec![];
         };
-        let (row, col) = cursor;
-        let current_line = &lines[row];
-
-        // Search current line before cursor
-        if col > 0 || match_cursor {
-            let start_col = if match_cursor { col } else { col - 1 };
-            if let Some((i, _)) = current_line.char_indices().nth(start_col) {
-                if let Some(m) = pat
-                    .find_iter(current_line)
-                    .take_while(|m| m.start() <= i)
-                    .last()
-                {
-                    let col = current_line[..m.start()].chars().count();
-                    return Some((row, col));
-                }
-            }
-        }
-
-        // Search lines before cursor
-        for (i, line) in lines[..row].iter().enumerate().rev() {
-            if let Some(m) = pat.find_iter(line).last() {
-                let col = line[..m.start()].chars().count();
-                return Some((i, col));
-            }
-        }
-
-        // Search lines after cursor (wrap)
-        for (i, line) in lines[row + 1..].iter().enumerate().rev() {
-            if let Some(m) = pat.find_iter(line).last() {
-                let col = line[..m.start()].chars().count();
-                return Some((row + 1 + i, col));
-            }
-        }
-
-        // Search current line after cursor
-        if let Some((i, _)) = current_line.char_indices().nth(col) {
-            if let Some(m) = pat
-                .find_iter(current_line)
-                .skip_while(|m| m.start() < i)
-                .last()
-            {
-                let col = col + current_line[i..m.start()].chars().count();
-                return Some((row, col));
-            }
-        }
+        let (mut index, mut offset) = (0, 0);
+        pat.captures_iter(text)
+            .map(|caps| {
+                let m = caps.get(0).unwrap();
+                offset += text[index..m.start()].chars().count();
+                let start = offset;
+                offset += m.as_str().chars().count();
+                index = m.end();
+                let mut replaced = String::new();
+                caps.expand(replacement, &mut replaced);
+                (start, offset, replaced)
+            })
+            .collect()
+    }
 
-        None
+    // Char offset of the previous match from the cursor. When no match is before the cursor, the search wraps around to
+    // the end of the text.
+    pub fn back(&self, text: &str, cursor: usize, match_cursor: bool) -> Option<usize> {
+        let starts: Vec<_> = self.matches(text)?.map(|(start, _)| start).collect();
+        let before = starts
+            .iter()
+            .rev()
+            .find(|&&start| start < cursor || match_cursor && start == cursor);
+        before.or(starts.last()).copied()
     }
 }
 
@@ -164,7 +305,198 @@ mod tests {
         let m: Vec<_> = s.matches("fo foo bar fooo").unwrap().collect();
         assert_eq!(m, [(0, 2), (3, 6), (11, 15)]);
 
+        // Offsets are in chars and matches can span newlines
+        s.set_pattern("o\nう").unwrap();
+        let m: Vec<_> = s.matches("あfo\nうfo\nう").unwrap().collect();
+        assert_eq!(m, [(2, 5), (6, 9)]);
+
         s.set_pattern("").unwrap();
         assert!(s.matches("fo foo bar fooo").is_none());
     }
+
+    #[test]
+    fn forward_back() {
+        let mut s = Search::default();
+        s.set_pattern("fo+").unwrap();
+        let text = "foo\nあfo\nfooo";
+
+        assert_eq!(s.forward(text, 0, true), Some(0));
+        assert_eq!(s.forward(text, 0, false), Some(5));
+        assert_eq!(s.forward(text, 5, false), Some(8));
+        assert_eq!(s.forward(text, 8, false), Some(0)); // Wrap
+        assert_eq!(s.forward(text, 12, false), Some(0)); // At the end of text
+
+        assert_eq!(s.back(text, 0, true), Some(0));
+        assert_eq!(s.back(text, 0, false), Some(8)); // Wrap
+        assert_eq!(s.back(text, 8, false), Some(5));
+        assert_eq!(s.back(text, 7, false), Some(5));
+
+        s.set_pattern("bar").unwrap();
+        assert_eq!(s.forward(text, 0, false), None);
+        assert_eq!(s.back(text, 0, false), None);
+    }
+
+    #[test]
+    fn replacements() {
+        let mut s = Search::default();
+        s.set_pattern(r"(\w)o+").unwrap();
+        let text = "foo\nあbooo";
+
+        let r = s.replacement(text, 0, "${1}_").unwrap();
+        assert_eq!(r, (0, 3, "f_".to_string()));
+        let r = s.replacement(text, 3, "$1").unwrap();
+        assert_eq!(r, (5, 9, "b".to_string()));
+        let r = s.replacement(text, 9, "$1").unwrap(); // Wrap
+        assert_eq!(r, (0, 3, "f".to_string()));
+
+        let r = s.replacements(text, "<$0>");
+        assert_eq!(
+            r,
+            [(0, 3, "<foo>".to_string()), (5, 9, "<booo>".to_string())],
+        );
+
+        s.set_pattern("").unwrap();
+        assert!(s.replacement(text, 0, "x").is_none());
+        assert!(s.replacements(text, "x").is_empty());
+    }
+
+    #[test]
+    fn options() {
+        let tests = [
+            // (options, query, text, matches)
+            (
+                SearchOptions::default(),
+                "a.b",
+                "a.b axb",
+                &[(0, 3), (4, 7)][..],
+            ),
+            (
+                SearchOptions::default().literal(true),
+                "a.b",
+                "a.b axb",
+                &[(0, 3)][..],
+            ),
+            (
+                SearchOptions::default().literal(true),
+                "(",
+                "f(x)",
+                &[(1, 2)][..],
+            ),
+            (SearchOptions::default(), "foo", "Foo foo", &[(4, 7)][..]),
+            (
+                SearchOptions::default().case_insensitive(true),
+                "foo",
+                "Foo foo",
+                &[(0, 3), (4, 7)][..],
+            ),
+            (
+                SearchOptions::default().smart_case(true),
+                "foo",
+                "Foo foo",
+                &[(0, 3), (4, 7)][..],
+            ),
+            (
+                SearchOptions::default().smart_case(true),
+                "Foo",
+                "Foo foo",
+                &[(0, 3)][..],
+            ),
+            // Escape sequences are not uppercase letters
+            (
+                SearchOptions::default().smart_case(true),
+                r"f\S+",
+                "Foo foo",
+                &[(0, 3), (4, 7)][..],
+            ),
+            (
+                SearchOptions::default().smart_case(true),
+                r"\\F",
+                r"\f \F",
+                &[(3, 5)][..],
+            ),
+            (
+                SearchOptions::default()
+                    .smart_case(true)
+                    .case_insensitive(true),
+                "Foo",
+                "Foo foo",
+                &[(0, 3)][..],
+            ),
+            (
+                SearchOptions::default().whole_word(true),
+                "foo",
+                "foo foobar barfoo",
+                &[(0, 3)][..],
+            ),
+            (
+                SearchOptions::default().whole_word(true),
+                "fo+|bar",
+                "foo foobar bar",
+                &[(0, 3), (11, 14)][..],
+            ),
+        ];
+
+        for (options, query, text, want) in tests {
+            let mut s = Search::default();
+            s.set_pattern_with(query, options).unwrap();
+            let have: Vec<_> = s.matches(text).unwrap().collect();
+            assert_eq!(have, want, "{options:?}, {query:?}, {text:?}");
+        }
+    }
+
+    #[test]
+    fn set_options() {
+        let mut s = Search::default();
+        s.set_pattern("f(").unwrap_err();
+        assert!(s.pat.is_none());
+
+        // The query is kept and compiled with new options
+        s.set_options(SearchOptions::default().literal(true))
+            .unwrap();
+        s.set_pattern("f(").unwrap();
+        assert_eq!(s.matches("f(x)").unwrap().count(), 1);
+        s.set_options(SearchOptions::default()).unwrap_err();
+        assert_eq!(s.options, SearchOptions::default().literal(true));
+        assert_eq!(s.query, "f(");
+
+        s.set_pattern("").unwrap();
+        assert!(s.pat.is_none());
+        s.set_options(SearchOptions::default()).unwrap();
+        assert!(s.pat.is_none());
+    }
+
+    #[test]
+    fn match_at_offset() {
+        let matches = [(1, 3), (3, 4), (6, 6), (8, 10)];
+        let tests = [
+            (0, None),
+            (1, Some(0)),
+            (2, Some(0)),
+            (3, Some(1)),
+            (4, None),
+            (6, None), // Empty match
+            (9, Some(3)),
+            (10, None),
+        ];
+        for (offset, want) in tests {
+            assert_eq!(match_at(&matches, offset), want, "{offset}");
+        }
+        assert_eq!(match_at(&[], 0), None);
+    }
+
+    #[test]
+    fn cached_matches() {
+        let mut s = Search::default();
+        assert!(s.cached_matches("foo", 0).is_empty());
+
+        s.set_pattern("o").unwrap();
+        assert_eq!(&*s.cached_matches("foo", 0), [(1, 2), (2, 3)]);
+        // The same version of the text returns the cached matches
+        assert_eq!(&*s.cached_matches("fo", 0), [(1, 2), (2, 3)]);
+        assert_eq!(&*s.cached_matches("fo", 1), [(1, 2)]);
+
+        // Changing the pattern clears the cache
+        s.set_pattern("f").unwrap();
+        assert_eq!(&*s.cached_matches("fo", 1), [(0, 1)]);
+    }
 }
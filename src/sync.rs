@@ -0,0 +1,144 @@
+use crate::textarea::MergeArea;
+use automerge::sync::{Message, ReadMessageError, State};
+use automerge::{AutoCommit, AutomergeError, ObjId};
+use std::error::Error;
+use std::fmt;
+
+/// An error returned by [`SyncSession::receive_message`].
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+#[derive(Debug)]
+pub enum SyncError {
+    /// The received bytes are not a valid sync message.
+    Decode(ReadMessageError),
+    /// The sync message could not be applied to the document.
+    Automerge(AutomergeError),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(err) => write!(f, "could not decode sync message: {}", err),
+            Self::Automerge(err) => write!(f, "could not apply sync message: {}", err),
+        }
+    }
+}
+
+impl Error for SyncError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Decode(err) => Some(err),
+            Self::Automerge(err) => Some(err),
+        }
+    }
+}
+
+impl From<ReadMessageError> for SyncError {
+    fn from(err: ReadMessageError) -> Self {
+        Self::Decode(err)
+    }
+}
+
+impl From<AutomergeError> for SyncError {
+    fn from(err: AutomergeError) -> Self {
+        Self::Automerge(err)
+    }
+}
+
+/// A session of the automerge sync protocol with one remote peer. It wraps [`MergeArea`] bound to a text object of a
+/// document and the [`automerge::sync::State`] of the remote peer.
+///
+/// Messages are exchanged as bytes so that they can be sent over any transport. Keep calling
+/// [`SyncSession::generate_message`] and sending the messages until it returns `None`, and pass the messages received
+/// from the peer to [`SyncSession::receive_message`]. Received changes are applied to the textarea in place. The
+/// cursor, the selection and the viewport follow the text they point to.
+/// ```
+/// use automerge::transaction::Transactable as _;
+/// use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+/// use ratatui_mergearea::SyncSession;
+///
+/// let mut doc = AutoCommit::new();
+/// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+/// let fork = doc.fork().with_actor(ActorId::random());
+///
+/// let mut left = SyncSession::new(doc, obj.clone()).unwrap();
+/// let mut right = SyncSession::new(fork, obj).unwrap();
+///
+/// left.textarea_mut().insert_str("hello");
+///
+/// // Exchange messages until both peers have nothing to send
+/// loop {
+///     let l = left.generate_message();
+///     let r = right.generate_message();
+///     if l.is_none() && r.is_none() {
+///         break;
+///     }
+///     if let Some(msg) = l {
+///         right.receive_message(&msg).unwrap();
+///     }
+///     if let Some(msg) = r {
+///         left.receive_message(&msg).unwrap();
+///     }
+/// }
+///
+/// assert_eq!(right.textarea().text().as_str(), "hello");
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+#[derive(Clone, Debug)]
+pub struct SyncSession<'a> {
+    textarea: MergeArea<'a>,
+    state: State,
+}
+
+impl<'a> SyncSession<'a> {
+    /// Create a session editing the text object `obj` of the document. An error is returned when `obj` is not a text
+    /// object. See [`MergeArea::from_doc`].
+    pub fn new(doc: AutoCommit, obj: ObjId) -> Result<Self, AutomergeError> {
+        Ok(Self {
+            textarea: MergeArea::from_doc(doc, obj)?,
+            state: State::new(),
+        })
+    }
+
+    /// Get the textarea of this session.
+    pub fn textarea(&self) -> &MergeArea<'a> {
+        &self.textarea
+    }
+
+    /// Get the mutable textarea of this session to edit the text or to handle key inputs. Local edits are sent to the
+    /// peer by the next [`SyncSession::generate_message`] call.
+    pub fn textarea_mut(&mut self) -> &mut MergeArea<'a> {
+        &mut self.textarea
+    }
+
+    /// Finish the session and take the textarea.
+    pub fn into_textarea(self) -> MergeArea<'a> {
+        self.textarea
+    }
+
+    /// Get the sync state of the remote peer.
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Forget what is known about the remote peer. Call this when the connection to the peer is re-established.
+    pub fn reset(&mut self) {
+        self.state = State::new();
+    }
+
+    /// Generate an encoded sync message to send to the peer. `None` is returned when there is nothing to send, either
+    /// because the peer is up to date or because an acknowledgement of the previous message is awaited.
+    pub fn generate_message(&mut self) -> Option<Vec<u8>> {
+        self.textarea
+            .generate_sync_message(&mut self.state)
+            .map(Message::encode)
+    }
+
+    /// Apply an encoded sync message received from the peer. Changes in the message are applied to the text of the
+    /// textarea in place.
+    pub fn receive_message(&mut self, message: &[u8]) -> Result<(), SyncError> {
+        let message = Message::decode(message)?;
+        self.textarea
+            .receive_sync_message(&mut self.state, message)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use crate::ratatui::style::Style;
+use crate::util::SharedFn;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A hook to highlight the syntax of the text such as keywords and strings. Set it with
+/// [`crate::MergeArea::set_highlighter`].
+///
+/// The styles are applied beneath the cursor, the selections and the search matches. The styles of the text itself
+/// such as its authors and its marks are patched onto them.
+///
+/// On rendering, lines are passed in order from the first line to the last visible line so that an implementation can
+/// carry the state of its parser from a line to the next line. Every render then costs time proportional to the lines
+/// before the viewport, so an implementation should cache its states like [`crate::SyntectHighlighter`] does, or
+/// return `false` from [`Highlighter::is_stateful`] to be passed only the visible lines.
+///
+/// Closures taking a line and its row implement this trait. They are stateless, so only the visible lines are passed to
+/// them.
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use ratatui_mergearea::Highlighter;
+///
+/// // Highlight comments starting with `#`
+/// let comments = |line: &str, _row: usize| match line.find('#') {
+///     Some(i) => vec![(i..line.len(), Style::default().fg(Color::DarkGray))],
+///     None => vec![],
+/// };
+/// let styles = comments.highlight("let x = 1; # one", 0);
+/// assert_eq!(styles, [(11..16, Style::default().fg(Color::DarkGray))]);
+/// ```
+pub trait Highlighter {
+    /// Return the styles of the byte ranges of the line at the row. The line does not contain the newline at the end.
+    /// Ranges which are not on char boundaries of the line are ignored.
+    fn highlight(&self, line: &str, row: usize) -> Vec<(Range<usize>, Style)>;
+
+    /// Return whether the highlighter carries the state of its parser from a line to the next line. When `false` is
+    /// returned, the lines before the viewport are not passed. The default is `true`.
+    fn is_stateful(&self) -> bool {
+        true
+    }
+}
+
+impl<F> Highlighter for F
+where
+    F: Fn(&str, usize) -> Vec<(Range<usize>, Style)>,
+{
+    fn highlight(&self, line: &str, row: usize) -> Vec<(Range<usize>, Style)> {
+        self(line, row)
+    }
+
+    fn is_stateful(&self) -> bool {
+        false
+    }
+}
+
+// Highlights the syntax of the text
+#[derive(Clone, Debug)]
+pub struct Syntax(SharedFn<dyn Highlighter + Send + Sync>);
+
+impl Syntax {
+    pub fn new(highlighter: impl Highlighter + Send + Sync + 'static) -> Self {
+        Self(SharedFn::new(Arc::new(highlighter)))
+    }
+
+    // Styled runs of chars between the char offsets `start` and `end` in the text. A stateful highlighter is passed all
+    // lines before `end` so that it can follow the state from the first line. Otherwise lines start at `start`.
+    pub fn runs(&self, text: &str, start: usize, end: usize) -> Vec<(usize, usize, Style)> {
+        let (first_row, mut line_start, text) = if self.0.is_stateful() {
+            (0, 0, text)
+        } else {
+            let pos = text
+                .char_indices()
+                .nth(start)
+                .map_or(text.len(), |(i, _)| i);
+            let head = text[..pos].rfind('\n').map_or(0, |i| i + 1);
+            let row = text[..head].matches('\n').count();
+            (row, text[..head].chars().count(), &text[head..])
+        };
+
+        let mut runs = vec![];
+        for (row, line) in text.split('\n').enumerate() {
+            if line_start >= end {
+                break;
+            }
+            let len = line.chars().count();
+            let styles = self.0.highlight(line, first_row + row);
+            // Styles of the lines before `start` are only for carrying the state
+            if line_start + len >= start {
+                // Convert the byte ranges of the line into char offsets in the text
+                let offset = |i: usize| line.get(..i).map(|s| line_start + s.chars().count());
+                for (range, style) in styles {
+                    if let (Some(s), Some(e)) = (offset(range.start), offset(range.end)) {
+                        runs.push((s, e, style));
+                    }
+                }
+            }
+            line_start += len + 1;
+        }
+        runs
+    }
+}
+
+#[cfg(feature = "syntect")]
+pub use self::syntect_impl::SyntectHighlighter;
+
+#[cfg(feature = "syntect")]
+mod syntect_impl {
+    use super::Highlighter;
+    use crate::ratatui::style::{Color, Modifier, Style};
+    use std::fmt;
+    use std::ops::Range;
+    use std::sync::Mutex;
+    use syntect::highlighting::{
+        self, FontStyle, HighlightState, RangedHighlightIterator, Theme, ThemeSet,
+    };
+    use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
+
+    // A highlighted line with the states of the parser before and after it. The line is highlighted again when its text
+    // or the state before it was changed.
+    struct CachedLine {
+        text: String,
+        before: (ParseState, HighlightState),
+        after: (ParseState, HighlightState),
+        styles: Vec<(Range<usize>, Style)>,
+    }
+
+    /// [`Highlighter`] backed by [syntect](https://docs.rs/syntect). Only the foreground colors and the font styles of
+    /// the theme are applied so that the style of the textarea is kept.
+    ///
+    /// The states of the parser are cached for each line, so lines are parsed again only from the first modified line.
+    /// ```
+    /// use ratatui::style::Color;
+    /// use ratatui_mergearea::{Highlighter as _, SyntectHighlighter};
+    ///
+    /// let hl = SyntectHighlighter::new("rs");
+    /// let styles = hl.highlight("fn main() {}", 0);
+    /// let (range, style) = &styles[0];
+    /// assert_eq!(range, &(0..2)); // `fn` keyword
+    /// assert!(matches!(style.fg, Some(Color::Rgb(..))));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "syntect")))]
+    pub struct SyntectHighlighter {
+        syntaxes: SyntaxSet,
+        syntax: SyntaxReference,
+        theme: Theme,
+        lines: Mutex<Vec<CachedLine>>,
+    }
+
+    impl fmt::Debug for SyntectHighlighter {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SyntectHighlighter")
+                .field("syntax", &self.syntax.name)
+                .field("theme", &self.theme.name)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl SyntectHighlighter {
+        /// Create a highlighter with the default syntaxes and the default `base16-ocean.dark` theme of syntect. The
+        /// syntax is found by `token`, which is a file extension such as `rs` or a name of the syntax such as `Rust`.
+        /// When no syntax is found, the text is highlighted as plain text.
+        pub fn new(token: &str) -> Self {
+            let mut themes = ThemeSet::load_defaults();
+            let theme = themes
+                .themes
+                .remove("base16-ocean.dark")
+                .unwrap_or_default();
+            Self::with_syntaxes(SyntaxSet::load_defaults_nonewlines(), token, theme)
+        }
+
+        /// Create a highlighter with the syntaxes and the theme. The syntaxes must be loaded to match lines without
+        /// newlines such as [`SyntaxSet::load_defaults_nonewlines`].
+        pub fn with_syntaxes(syntaxes: SyntaxSet, token: &str, theme: Theme) -> Self {
+            let syntax = syntaxes
+                .find_syntax_by_token(token)
+                .unwrap_or_else(|| syntaxes.find_syntax_plain_text())
+                .clone();
+            Self {
+                syntaxes,
+                syntax,
+                theme,
+                lines: Mutex::new(vec![]),
+            }
+        }
+
+        /// Set the theme of the highlighter.
+        pub fn theme(mut self, theme: Theme) -> Self {
+            self.theme = theme;
+            self.lines.get_mut().unwrap().clear();
+            self
+        }
+
+        fn initial_state(
+            &self,
+            highlighter: &highlighting::Highlighter<'_>,
+        ) -> (ParseState, HighlightState) {
+            let parse = ParseState::new(&self.syntax);
+            let highlight = HighlightState::new(highlighter, ScopeStack::new());
+            (parse, highlight)
+        }
+    }
+
+    fn style(s: highlighting::Style) -> Style {
+        let mut style =
+            Style::default().fg(Color::Rgb(s.foreground.r, s.foreground.g, s.foreground.b));
+        for (font, modifier) in [
+            (FontStyle::BOLD, Modifier::BOLD),
+            (FontStyle::ITALIC, Modifier::ITALIC),
+            (FontStyle::UNDERLINE, Modifier::UNDERLINED),
+        ] {
+            if s.font_style.contains(font) {
+                style = style.add_modifier(modifier);
+            }
+        }
+        style
+    }
+
+    impl Highlighter for SyntectHighlighter {
+        fn highlight(&self, line: &str, row: usize) -> Vec<(Range<usize>, Style)> {
+            let highlighter = highlighting::Highlighter::new(&self.theme);
+            let mut lines = self.lines.lock().unwrap();
+
+            // Lines are passed in order, so the state after the previous row is cached. Otherwise start over
+            let before = match row.checked_sub(1).and_then(|prev| lines.get(prev)) {
+                Some(prev) => prev.after.clone(),
+                None => self.initial_state(&highlighter),
+            };
+            if let Some(cached) = lines.get(row) {
+                if cached.text == line && cached.before == before {
+                    return cached.styles.clone();
+                }
+            }
+
+            let (mut parse, mut state) = before.clone();
+            let ops = parse.parse_line(line, &self.syntaxes).unwrap_or_default();
+            let styles: Vec<_> = RangedHighlightIterator::new(&mut state, &ops, line, &highlighter)
+                .map(|(s, _, range)| (range, style(s)))
+                .collect();
+
+            if lines.len() >= row {
+                lines.truncate(row);
+                lines.push(CachedLine {
+                    text: line.to_string(),
+                    before,
+                    after: (parse, state),
+                    styles: styles.clone(),
+                });
+            }
+            styles
+        }
+    }
+}
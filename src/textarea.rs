@@ -1,64 +1,63 @@
-use crate::cursor::CursorMove;
+use crate::blame::Blame;
+use crate::comment::{self, Comment};
+use crate::cursor::{CursorContext, CursorMove};
+use crate::diagnostic::{self, Diagnostic, Severity};
+use crate::document::{Document, Position, Splice};
+use crate::fold::{self, FoldProvider, Folder};
 use crate::highlight::LineHighlighter;
-use crate::history::History;
-use crate::history_v2::{Edit as EditV2, EditKind as EditKindV2, History as HistoryV2};
+use crate::history::{Edit, EditKind, History};
 use crate::input::{Input, Key};
+use crate::marks::MarkStyle;
+#[cfg(feature = "tree-sitter")]
+use crate::parse_tree::ParseTree;
 use crate::ratatui::layout::Alignment;
 use crate::ratatui::style::{Color, Modifier, Style};
 use crate::ratatui::widgets::Block;
+use crate::remote::{RemoteCursor, Resolved};
 use crate::scroll::Scrolling;
+use crate::scrub::Scrubber;
 #[cfg(feature = "search")]
-use crate::search::Search;
+use crate::search::{self, Isearch, Search, SearchOptions};
+use crate::syntax::{Highlighter, Syntax};
 use crate::util::{self, spaces};
-use crate::widget::Viewport;
+use crate::widget::{RowCache, Viewport};
+use crate::word::{find_word_exclusive_end_forward, find_word_start_backward};
+use crate::wrap::{VisualRow, WrapMode};
+use automerge::marks::ExpandMark;
+use automerge::{
+    ActorId, AutoCommit, AutomergeError, ChangeHash, Cursor, ObjId, Patch, ScalarValue,
+};
 use ratatui::text::Line;
 use std::cmp::{self, Ordering};
-use std::fmt;
+use std::ops::Range;
 use unicode_width::UnicodeWidthChar as _;
 
-#[derive(Debug, Clone)]
-enum YankText {
-    Piece(String),
-    Chunk(Vec<String>),
-}
-
-impl Default for YankText {
-    fn default() -> Self {
-        Self::Piece(String::new())
-    }
-}
-
-impl From<String> for YankText {
-    fn from(s: String) -> Self {
-        Self::Piece(s)
-    }
-}
-impl From<Vec<String>> for YankText {
-    fn from(mut c: Vec<String>) -> Self {
-        match c.len() {
-            0 => Self::default(),
-            1 => Self::Piece(c.remove(0)),
-            _ => Self::Chunk(c),
-        }
-    }
-}
-
-impl fmt::Display for YankText {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Piece(s) => write!(f, "{}", s),
-            Self::Chunk(ss) => write!(f, "{}", ss.join("\n")),
-        }
-    }
+// Marker next to the line number of a commented line
+const COMMENT_MARKER: &str = "▎";
+
+// Decorations of the visible text which are computed once per rendering. Offsets are char offsets in the text.
+#[derive(Default)]
+pub(crate) struct Decorations<'a> {
+    // The cursor offset and its row
+    cursor: (usize, usize),
+    selection: Option<(usize, usize)>,
+    syntax: Vec<(usize, usize, Style)>,
+    remotes: Vec<Resolved<'a>>,
+    text_styles: Vec<(usize, usize, Style)>,
+    comments: Vec<(usize, usize)>,
+    diagnostics: Vec<(usize, usize, Severity)>,
+    #[cfg(feature = "search")]
+    search_matches: Vec<(usize, usize)>,
 }
 
 /// A type to manage state of textarea. These are some important methods:
 ///
 /// - [`MergeArea::default`] creates an empty textarea.
-/// - [`MergeArea::new`] creates a textarea with given text lines.
-/// - [`MergeArea::from`] creates a textarea from an iterator of lines.
+/// - [`MergeArea::new`] creates a textarea with given [`autosurgeon::Text`].
+/// - [`MergeArea::with_value`] creates a textarea with given string.
+/// - [`MergeArea::from_doc`] creates a textarea bound to a text object of an automerge document.
 /// - [`MergeArea::input`] handles key input.
-/// - [`MergeArea::lines`] returns line texts.
+/// - [`MergeArea::text`] returns the text and [`MergeArea::lines`] iterates over its lines.
 /// ```
 /// use ratatui_mergearea::{MergeArea, Input, Key};
 /// use ratatui::backend::CrosstermBackend;
@@ -71,8 +70,8 @@ impl fmt::Display for YankText {
 /// let input = Input { key: Key::Char('a'), ctrl: false, alt: false, shift: false };
 /// textarea.input(input);
 ///
-/// // Get lines as String.
-/// println!("Lines: {:?}", textarea.text().as_str());
+/// // Get the text as `&str`.
+/// println!("Text: {:?}", textarea.text().as_str());
 /// ```
 ///
 /// It implements [`ratatui::widgets::Widget`] trait so it can be rendered to a terminal screen via
@@ -102,32 +101,43 @@ impl fmt::Display for YankText {
 /// ```
 #[derive(Clone, Debug)]
 pub struct MergeArea<'a> {
-    lines: Vec<String>,
+    text: Document,
     block: Option<Block<'a>>,
     style: Style,
-    cursor: (usize, usize), // 0-base
+    cursor: Position,
     tab_len: u8,
     hard_tab_indent: bool,
     history: History,
-    history_v2: HistoryV2,
     cursor_line_style: Style,
     line_number_style: Option<Style>,
     pub(crate) viewport: Viewport,
+    pub(crate) rows: RowCache,
     pub(crate) cursor_style: Style,
-    yank: YankText,
+    // Yanked text. Newlines are always `\n` as in the text
+    yank: String,
     #[cfg(feature = "search")]
     search: Search,
+    #[cfg(feature = "search")]
+    isearch: Option<Isearch>,
     alignment: Alignment,
     pub(crate) placeholder: String,
     pub(crate) placeholder_style: Style,
     mask: Option<char>,
-    selection_start: Option<(usize, usize)>,
+    wrap_mode: WrapMode,
+    selection_start: Option<Position>,
     select_style: Style,
-
-    text: autosurgeon::Text,
-
-    cursor_v2: usize,
-    selection_start_v2: Option<usize>,
+    remote_cursors: Vec<RemoteCursor>,
+    scrubber: Option<Scrubber>,
+    blame: Option<Blame>,
+    syntax: Option<Syntax>,
+    mark_style: Option<MarkStyle>,
+    comment_style: Style,
+    concurrent_edit_style: Option<Style>,
+    // First and last chars of the regions edited concurrently, detected on the last merge
+    concurrent_edits: Vec<(Cursor, Cursor)>,
+    diagnostic_styles: [Style; 4],
+    folder: Option<Folder>,
+    fold_style: Style,
 }
 
 /// Create [`MergeArea`] instance with empty text content.
@@ -148,31 +158,43 @@ impl<'a> MergeArea<'a> {
     /// Create [`MergeArea`] from an existing [`autosurgeon::Text`] object.
     pub fn new(text: autosurgeon::Text) -> Self {
         Self {
-            lines: vec!["".to_string()],
+            text: Document::new(text),
             block: None,
             style: Style::default(),
-            cursor: (0, 0),
+            cursor: Position::Offset(0),
             tab_len: 4,
             hard_tab_indent: false,
             history: History::new(50),
             cursor_line_style: Style::default().add_modifier(Modifier::UNDERLINED),
             line_number_style: None,
             viewport: Viewport::default(),
+            rows: RowCache::default(),
             cursor_style: Style::default().add_modifier(Modifier::REVERSED),
-            yank: YankText::default(),
+            yank: String::new(),
             #[cfg(feature = "search")]
             search: Search::default(),
+            #[cfg(feature = "search")]
+            isearch: None,
             alignment: Alignment::Left,
             placeholder: String::new(),
             placeholder_style: Style::default().fg(Color::DarkGray),
             mask: None,
+            wrap_mode: WrapMode::None,
             selection_start: None,
             select_style: Style::default().bg(Color::LightBlue),
-
-            text,
-            cursor_v2: 0,
-            selection_start_v2: None,
-            history_v2: HistoryV2::new(50),
+            remote_cursors: vec![],
+            scrubber: None,
+            blame: None,
+            syntax: None,
+            mark_style: None,
+            comment_style: Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::UNDERLINED),
+            concurrent_edit_style: None,
+            concurrent_edits: vec![],
+            diagnostic_styles: diagnostic::DEFAULT_STYLES,
+            folder: None,
+            fold_style: Style::default().fg(Color::DarkGray),
         }
     }
 
@@ -180,14 +202,201 @@ impl<'a> MergeArea<'a> {
         MergeArea::new(autosurgeon::Text::with_value(value))
     }
 
+    /// Create [`MergeArea`] bound to a text object of an automerge document. Edits made through the textarea are
+    /// written to the text object directly with `splice_text`, so the document never needs to be reconciled. An error
+    /// is returned when `obj` is not a text object or when the document counts text in grapheme clusters
+    /// ([`automerge::TextEncoding::GraphemeCluster`]).
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{AutoCommit, ObjType, ReadDoc as _, ROOT};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "hello").unwrap();
+    ///
+    /// let mut textarea = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    /// textarea.insert_str("oh, ");
+    ///
+    /// let doc = textarea.doc().unwrap();
+    /// assert_eq!(doc.text(&obj).unwrap(), "oh, hello");
+    /// ```
+    pub fn from_doc(doc: AutoCommit, obj: ObjId) -> Result<Self, AutomergeError> {
+        let text = Document::attach(doc, obj)?;
+        Ok(Self {
+            cursor: text.position(0),
+            text,
+            ..Self::default()
+        })
+    }
+
+    /// Replace the entire text. When a document is attached, the difference between the current text and the new text
+    /// is written to the document and the cursor and the selection follow the text they point to.
     pub fn set_text(&mut self, text: autosurgeon::Text) {
+        if self.scrubber.is_some() {
+            return;
+        }
         let text_len = text.as_str().chars().count();
-        self.text = text;
-        self.cursor_v2 = cmp::min(self.cursor_v2, text_len);
+        let splices = self.text.replace(text);
+        self.follow_caught_up();
+        match splices {
+            Some(splices) => self.follow_splices(&splices),
+            None => self.set_cursor(cmp::min(self.cursor(), text_len)),
+        }
     }
 
     pub fn text(&self) -> &autosurgeon::Text {
-        &self.text
+        self.text.text()
+    }
+
+    /// Get the automerge document attached by [`MergeArea::from_doc`]. `None` is returned when no document is
+    /// attached.
+    pub fn doc(&self) -> Option<&AutoCommit> {
+        self.text.doc()
+    }
+
+    /// Get the automerge document attached by [`MergeArea::from_doc`] mutably. When the text object is modified
+    /// through the returned reference, call [`MergeArea::reload`] to reflect the modification in the textarea.
+    pub fn doc_mut(&mut self) -> Option<&mut AutoCommit> {
+        self.text.doc_mut()
+    }
+
+    /// Get the ID of the text object this textarea is bound to. `None` is returned when no document is attached.
+    pub fn text_obj(&self) -> Option<&ObjId> {
+        self.text.obj()
+    }
+
+    /// Merge changes of another automerge document into the attached document and update the text. This method
+    /// returns hashes of the changes which were applied. When no document is attached, this method does nothing.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "The fox jumped.").unwrap();
+    /// let fork = doc.fork().with_actor(ActorId::random());
+    ///
+    /// let mut left = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    /// let mut right = MergeArea::from_doc(fork, obj).unwrap();
+    ///
+    /// left.insert_str("Wow! ");
+    /// right.set_text("The quick fox jumped.".into());
+    ///
+    /// left.merge(right.doc_mut().unwrap()).unwrap();
+    /// assert_eq!(left.text().as_str(), "Wow! The quick fox jumped.");
+    /// ```
+    pub fn merge(&mut self, other: &mut AutoCommit) -> Result<Vec<ChangeHash>, AutomergeError> {
+        let (hashes, splices) = self.text.merge(other)?;
+        self.follow_splices(&splices);
+        self.detect_concurrent_edits(&hashes);
+        Ok(hashes)
+    }
+
+    /// Apply patches of the attached document to the text incrementally. The patches must be already applied to the
+    /// document, for example they are obtained by `AutoCommit::diff` after merging changes of other peers. Patches for
+    /// other objects than the text object are ignored.
+    ///
+    /// The cursor, the selection and the viewport follow the text they point to, so remote edits never move them
+    /// relative to the text the local user is looking at. When no document is attached, this method does nothing.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "fox jumped").unwrap();
+    /// let mut remote = doc.fork().with_actor(ActorId::random());
+    ///
+    /// let mut textarea = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    /// textarea.move_cursor(CursorMove::WordForward); // At 'j'
+    ///
+    /// remote.splice_text(&obj, 0, 0, "The quick ").unwrap();
+    ///
+    /// let doc = textarea.doc_mut().unwrap();
+    /// let before = doc.get_heads();
+    /// doc.merge(&mut remote).unwrap();
+    /// let after = doc.get_heads();
+    /// let patches = doc.diff(&before, &after);
+    /// textarea.apply_patches(&patches);
+    ///
+    /// assert_eq!(textarea.text().as_str(), "The quick fox jumped");
+    /// assert_eq!(textarea.cursor2(), (0, 14)); // Still at 'j'
+    /// ```
+    pub fn apply_patches(&mut self, patches: &[Patch]) {
+        let splices = self.text.apply_patches(patches);
+        self.follow_splices(&splices);
+    }
+
+    /// Re-read the text from the attached document. This is necessary after the document was modified through
+    /// [`MergeArea::doc_mut`]. The modifications since the document was borrowed are applied in the same way as
+    /// [`MergeArea::apply_patches`]. When no document is attached, this method does nothing.
+    pub fn reload(&mut self) {
+        match self.text.reload() {
+            Some(splices) => self.follow_splices(&splices),
+            None => {
+                let text_len = self.text.as_str().chars().count();
+                self.set_cursor(cmp::min(self.cursor(), text_len));
+            }
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    pub(crate) fn generate_sync_message(
+        &mut self,
+        state: &mut automerge::sync::State,
+    ) -> Option<automerge::sync::Message> {
+        self.text.generate_sync_message(state)
+    }
+
+    #[cfg(feature = "sync")]
+    pub(crate) fn receive_sync_message(
+        &mut self,
+        state: &mut automerge::sync::State,
+        message: automerge::sync::Message,
+    ) -> Result<(), AutomergeError> {
+        let (hashes, splices) = self.text.receive_sync_message(state, message)?;
+        self.follow_splices(&splices);
+        self.detect_concurrent_edits(&hashes);
+        Ok(())
+    }
+
+    // Replace the concurrently edited regions with the ones made by the merged changes. Merges which applied no change
+    // keep the current regions.
+    fn detect_concurrent_edits(&mut self, merged: &[ChangeHash]) {
+        if self.concurrent_edit_style.is_some() && !merged.is_empty() {
+            self.concurrent_edits = self.text.concurrent_edits(merged);
+        }
+    }
+
+    // Follow the modifications made through `MergeArea::doc_mut` which were applied before a local modification
+    fn follow_caught_up(&mut self) {
+        let splices = self.text.take_caught_up();
+        self.follow_splices(&splices);
+    }
+
+    fn follow_splices(&mut self, splices: &[Splice]) {
+        // Changes merged while viewing the history are viewed by stepping forward
+        if let Some(scrubber) = &mut self.scrubber {
+            scrubber.refresh(&mut self.text);
+        }
+        let (top_row, _) = self.viewport.scroll_top();
+        let mut row = top_row as usize;
+        for splice in splices {
+            self.cursor.shift(splice);
+            if let Some(start) = &mut self.selection_start {
+                start.shift(splice);
+            }
+            #[cfg(feature = "search")]
+            if let Some(isearch) = &mut self.isearch {
+                isearch.origin = splice.shift(isearch.origin);
+            }
+            row = splice.shift_row(row);
+        }
+        let delta = row as isize - top_row as isize;
+        self.viewport.scroll(delta as i16, 0);
     }
 
     /// Handle a key input with emacs key mappings. For default key mappings, see the table in
@@ -225,7 +434,14 @@ impl<'a> MergeArea<'a> {
     /// assert!(modified);
     /// ```
     pub fn input_emacs(&mut self, input: impl Into<Input>) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         let input = input.into();
+        #[cfg(feature = "search")]
+        if self.isearch.is_some() && self.input_isearch(&input) {
+            return false;
+        }
         let modified = match input {
             // Insert new line
             Input {
@@ -308,37 +524,37 @@ impl<'a> MergeArea<'a> {
                 ..
             } => self.delete_line_by_head(),
 
-            // Input {
-            //     key: Key::Char('w'),
-            //     ctrl: true,
-            //     alt: false,
-            //     ..
-            // }
-            // | Input {
-            //     key: Key::Char('h'),
-            //     ctrl: false,
-            //     alt: true,
-            //     ..
-            // }
-            // | Input {
-            //     key: Key::Backspace,
-            //     ctrl: false,
-            //     alt: true,
-            //     ..
-            // } => self.delete_word(),
-
-            // Input {
-            //     key: Key::Delete,
-            //     ctrl: false,
-            //     alt: true,
-            //     ..
-            // }
-            // | Input {
-            //     key: Key::Char('d'),
-            //     ctrl: false,
-            //     alt: true,
-            //     ..
-            // } => self.delete_next_word(),
+            Input {
+                key: Key::Char('w'),
+                ctrl: true,
+                alt: false,
+                ..
+            }
+            | Input {
+                key: Key::Char('h'),
+                ctrl: false,
+                alt: true,
+                ..
+            }
+            | Input {
+                key: Key::Backspace,
+                ctrl: false,
+                alt: true,
+                ..
+            } => self.delete_word(),
+
+            Input {
+                key: Key::Delete,
+                ctrl: false,
+                alt: true,
+                ..
+            }
+            | Input {
+                key: Key::Char('d'),
+                ctrl: false,
+                alt: true,
+                ..
+            } => self.delete_next_word(),
 
             // Scroll down
             Input {
@@ -529,7 +745,7 @@ impl<'a> MergeArea<'a> {
                 ctrl: false,
                 alt: true,
                 ..
-            } => self.redo_v2(),
+            } => self.redo(),
 
             Input {
                 key: Key::Char('y'),
@@ -571,7 +787,7 @@ impl<'a> MergeArea<'a> {
                 shift,
                 ..
             } => {
-                self.scroll_with_shift_v2(Scrolling::PageDown, shift);
+                self.scroll_with_shift(Scrolling::PageDown, shift);
                 false
             }
 
@@ -586,7 +802,7 @@ impl<'a> MergeArea<'a> {
                 shift,
                 ..
             } => {
-                self.scroll_with_shift_v2(Scrolling::PageUp, shift);
+                self.scroll_with_shift(Scrolling::PageUp, shift);
                 false
             }
 
@@ -595,7 +811,7 @@ impl<'a> MergeArea<'a> {
                 shift,
                 ..
             } => {
-                self.scroll_with_shift_v2((1, 0).into(), shift);
+                self.scroll_with_shift((1, 0).into(), shift);
                 false
             }
 
@@ -604,34 +820,174 @@ impl<'a> MergeArea<'a> {
                 shift,
                 ..
             } => {
-                self.scroll_with_shift_v2((-1, 0).into(), shift);
+                self.scroll_with_shift((-1, 0).into(), shift);
+                false
+            }
+
+            #[cfg(feature = "search")]
+            Input {
+                key: Key::Char('s'),
+                ctrl: true,
+                alt: false,
+                ..
+            } => {
+                self.start_isearch();
                 false
             }
+
             _ => false,
         };
 
         // Check invariants
-        debug_assert!(!self.lines.is_empty(), "no line after {:?}", input);
-        let (r, c) = self.cursor;
+        let len = self.text.as_str().chars().count();
         debug_assert!(
-            self.lines.len() > r,
-            "cursor {:?} exceeds max lines {} after {:?}",
-            self.cursor,
-            self.lines.len(),
+            self.cursor() <= len,
+            "cursor {} exceeds text length {} after {:?}",
+            self.cursor(),
+            len,
             input,
         );
         debug_assert!(
-            self.lines[r].chars().count() >= c,
-            "cursor {:?} exceeds max col {} at line {:?} after {:?}",
-            self.cursor,
-            self.lines[r].chars().count(),
-            self.lines[r],
+            self.selection_start().is_none_or(|s| s <= len),
+            "selection start {:?} exceeds text length {} after {:?}",
+            self.selection_start(),
+            len,
             input,
         );
 
         modified
     }
 
+    // Handle the input during the incremental search. Returns `false` when the input ends the search without being
+    // consumed. Then it is handled as a usual key input, as emacs does.
+    #[cfg(feature = "search")]
+    fn input_isearch(&mut self, input: &Input) -> bool {
+        match input {
+            Input {
+                key: Key::Char('s'),
+                ctrl: true,
+                alt: false,
+                ..
+            } => self.isearch_next(false),
+            Input {
+                key: Key::Char('r'),
+                ctrl: true,
+                alt: false,
+                ..
+            } => self.isearch_next(true),
+            Input {
+                key: Key::Char('g'),
+                ctrl: true,
+                alt: false,
+                ..
+            }
+            | Input { key: Key::Esc, .. } => self.end_isearch(false),
+            Input {
+                key: Key::Char('m'),
+                ctrl: true,
+                alt: false,
+                ..
+            }
+            | Input {
+                key: Key::Enter, ..
+            } => self.end_isearch(true),
+            Input {
+                key: Key::Char('h'),
+                ctrl: true,
+                alt: false,
+                ..
+            }
+            | Input {
+                key: Key::Backspace,
+                ctrl: false,
+                alt: false,
+                ..
+            } => {
+                let isearch = self.isearch.as_mut().unwrap();
+                isearch.query.pop();
+                // Search again from where the incremental search started
+                let origin = isearch.origin;
+                self.set_cursor(origin);
+                self.update_isearch();
+            }
+            Input {
+                key: Key::Char(c),
+                ctrl: false,
+                alt: false,
+                ..
+            } => {
+                self.isearch.as_mut().unwrap().query.push(*c);
+                self.update_isearch();
+            }
+            _ => {
+                self.end_isearch(true);
+                return false;
+            }
+        }
+        true
+    }
+
+    #[cfg(feature = "search")]
+    fn start_isearch(&mut self) {
+        self.isearch = Some(Isearch {
+            query: String::new(),
+            backward: false,
+            origin: self.cursor(),
+            prev_pat: self.search.pat.clone(),
+            prev_query: self.search.query.clone(),
+            prev_options: self.search.options,
+        });
+        self.update_isearch();
+    }
+
+    // Search the query from the cursor. The match at the cursor is kept while the query matches to it. The query is
+    // matched literally with the other search options
+    #[cfg(feature = "search")]
+    fn update_isearch(&mut self) {
+        let Some(isearch) = &self.isearch else {
+            return;
+        };
+        let options = isearch.prev_options.literal(true);
+        let backward = isearch.backward;
+        let query = isearch.query.clone();
+        self.search
+            .set_pattern_with(&query, options)
+            .expect("literal query must be valid pattern");
+        if backward {
+            self.search_back(true);
+        } else {
+            self.search_forward(true);
+        }
+    }
+
+    #[cfg(feature = "search")]
+    fn isearch_next(&mut self, backward: bool) {
+        let Some(isearch) = &mut self.isearch else {
+            return;
+        };
+        isearch.backward = backward;
+        if backward {
+            self.search_back(false);
+        } else {
+            self.search_forward(false);
+        }
+    }
+
+    // End the incremental search. When it is not accepted, the cursor goes back to where the search started. The
+    // pattern before the search is restored in both cases.
+    #[cfg(feature = "search")]
+    fn end_isearch(&mut self, accept: bool) {
+        let Some(isearch) = self.isearch.take() else {
+            return;
+        };
+        if !accept {
+            let len = self.text.as_str().chars().count();
+            self.set_cursor(cmp::min(isearch.origin, len));
+        }
+        self.search
+            .restore(isearch.prev_pat, isearch.prev_query, isearch.prev_options);
+    }
+
     /// Handle a key input without default key mappings. This method handles only
     ///
     /// - Single character input without modifier keys
@@ -645,6 +1001,9 @@ impl<'a> MergeArea<'a> {
     /// This method is useful when you want to define your own key mappings and don't want default key mappings.
     /// See 'Define your own key mappings' section in [the module document](./index.html).
     pub fn input(&mut self, input: impl Into<Input>) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         match input.into() {
             Input {
                 key: Key::Char(c),
@@ -692,9 +1051,17 @@ impl<'a> MergeArea<'a> {
         }
     }
 
-    fn push_history(&mut self, kind: EditKindV2, offset: usize) {
-        let edit = EditV2::new(kind, offset);
-        self.history_v2.push(edit);
+    // Apply the edit at the char offset and record it in the history. The offset where the edit was applied is
+    // returned. It differs from `offset` when the document was modified through `MergeArea::doc_mut` before the edit
+    fn push_history(&mut self, kind: EditKind, offset: usize) -> usize {
+        let cursor_at_end = self.cursor() > offset;
+        let offset = self.text.catch_up().iter().fold(offset, |o, s| s.shift(o));
+        self.follow_caught_up();
+        if !kind.is_empty() {
+            let edit = Edit::new(kind, offset, &mut self.text).with_cursor_at_end(cursor_at_end);
+            self.history.push(edit);
+        }
+        offset
     }
 
     /// Insert a single character at current cursor position.
@@ -707,22 +1074,17 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "a");
     /// ```
     pub fn insert_char(&mut self, c: char) {
+        if self.scrubber.is_some() {
+            return;
+        }
         if c == '\n' || c == '\r' {
             self.insert_newline();
             return;
         }
 
         self.delete_selection(false);
-        let text = self.text.as_str();
-        let pos = text
-            .char_indices()
-            .nth(self.cursor_v2)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| text.len());
-        self.text.splice(pos, 0, c.to_string());
-        self.move_cursor(CursorMove::Forward);
-
-        self.push_history(EditKindV2::InsertChar(c), pos);
+        let at = self.push_history(EditKind::InsertChar(c), self.cursor());
+        self.set_cursor(at + 1);
     }
 
     /// Insert a string at current cursor position. This method returns if some text was inserted or not in the textarea.
@@ -738,6 +1100,9 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "hello, world\ngoodbye, world");
     /// ```
     pub fn insert_str<S: AsRef<str>>(&mut self, s: S) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         let deleted = self.delete_selection(false);
         let inserted = self.insert_piece(s.as_ref().to_string());
 
@@ -749,57 +1114,36 @@ impl<'a> MergeArea<'a> {
             return false;
         }
 
-        let pos = self.char_index(self.cursor_v2);
-        self.text.splice(pos, 0, &s);
-        self.cursor_v2 += s.chars().count();
-
-        self.push_history(EditKindV2::InsertStr(s), pos);
+        let len = s.chars().count();
+        let at = self.push_history(EditKind::InsertStr(s), self.cursor());
+        self.set_cursor(at + len);
 
         true
     }
 
-    fn delete_range_v2(&mut self, start: usize, end: usize, should_yank: bool) {
-        self.cursor_v2 = start;
-
+    // Delete the chars between the char offsets `start` and `end`. Returns whether any char was deleted
+    fn delete_range(&mut self, start: usize, end: usize, should_yank: bool) -> bool {
         let start_pos = self.char_index(start);
         let end_pos = self.char_index(end);
-        // let start_pos = self
-        //     .text
-        //     .as_str()
-        //     .char_indices()
-        //     .nth(start)
-        //     .map(|(i, _)| i)
-        //     .unwrap_or(0);
-        // let end_pos = self
-        //     .text
-        //     .as_str()
-        //     .char_indices()
-        //     .nth(end)
-        //     .map(|(i, _)| i)
-        //     .unwrap_or(self.text.as_str().len());
-
-        let diff = (end_pos as isize) - (start_pos as isize);
-        let deleted = &self.text().as_str().to_string()[start_pos..end_pos];
-        self.text.splice(start, diff, "");
+        let deleted = self.text.as_str()[start_pos..end_pos].to_string();
+        if deleted.is_empty() {
+            self.set_cursor(start);
+            return false;
+        }
 
         if should_yank {
-            self.yank = YankText::Piece(deleted.to_string());
+            self.yank = deleted.clone();
         }
 
-        self.push_history(EditKindV2::DeleteStr(deleted.to_string()), start);
-
-        // let edit = if deleted.len() == 1 {
-        //     EditKind::DeleteStr(deleted.remove(0))
-        // } else {
-        //     EditKind::DeleteChunk(deleted)
-        // };
-        // self.push_history(edit, end, start.offset);
+        let start = self.push_history(EditKind::DeleteStr(deleted), start);
+        self.set_cursor(start);
+        true
     }
 
     /// Delete a string from the current cursor position. The `chars` parameter means number of characters, not a byte
     /// length of the string. Newlines at the end of lines are counted in the number. This method returns if some text
     /// was deleted or not.
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::{MergeArea, CursorMove};
     ///
     /// let mut textarea = MergeArea::with_value("🐱🐶🐰🐮");
@@ -815,6 +1159,9 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "🐱\n🐮");
     /// ```
     pub fn delete_str(&mut self, chars: usize) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         if self.delete_selection(false) {
             return true;
         }
@@ -822,9 +1169,8 @@ impl<'a> MergeArea<'a> {
             return false;
         }
 
-        self.delete_range_v2(self.cursor_v2, self.cursor_v2 + chars, true);
-
-        true
+        let cursor = self.cursor();
+        self.delete_range(cursor, cursor + chars, true)
     }
 
     /// Insert a tab at current cursor position. Note that this method does nothing when the tab length is 0. This
@@ -842,6 +1188,9 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "hi      ");
     /// ```
     pub fn insert_tab(&mut self) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         let modified = self.delete_selection(false);
         if self.tab_len == 0 {
             return modified;
@@ -853,14 +1202,15 @@ impl<'a> MergeArea<'a> {
         }
 
         let chars = self.text.as_str().chars().collect::<Vec<char>>();
-        let line_start = util::find_line_start(self.cursor_v2, &chars);
+        let cursor = self.cursor();
+        let line_start = util::find_line_start(cursor, &chars);
 
         let width: usize = self
             .text
             .as_str()
             .chars()
             .skip(line_start.saturating_sub(1))
-            .take(self.cursor_v2.saturating_sub(line_start))
+            .take(cursor.saturating_sub(line_start))
             .map(|c| c.width().unwrap_or(0))
             .sum();
 
@@ -879,16 +1229,13 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "h\ni");
     /// ```
     pub fn insert_newline(&mut self) {
+        if self.scrubber.is_some() {
+            return;
+        }
         self.delete_selection(false);
 
-        let pos = self.cursor_v2;
-        // TODO: calculate possition properly
-        self.text.splice(self.cursor_v2, 0, "\n");
-        self.move_cursor(CursorMove::Forward);
-
-        // TODO
-        self.push_history(EditKindV2::InsertNewline, pos);
-        // self.push_history(EditKind::InsertNewline, Pos::new(row, col, offset), 0);
+        let at = self.push_history(EditKind::InsertNewline, self.cursor());
+        self.set_cursor(at + 1);
     }
 
     /// Delete a newline from **head** of current cursor line. This method returns if a newline was deleted or not in
@@ -903,19 +1250,25 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "helloworld");
     /// ```
     pub fn delete_newline(&mut self) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         if self.delete_selection(false) {
             return true;
         }
 
-        let mut prev_line: Option<usize> = None;
-        for (i, c) in self.text.as_str().char_indices() {
-            if c == '\n' && i < self.cursor_v2 {
-                prev_line = Some(i);
-            }
-        }
+        let prev_line = self
+            .text
+            .as_str()
+            .chars()
+            .take(self.cursor())
+            .enumerate()
+            .filter(|&(_, c)| c == '\n')
+            .map(|(i, _)| i)
+            .last();
 
         if let Some(i) = prev_line {
-            self.delete_range_v2(i, i + 1, false);
+            self.delete_range(i, i + 1, false);
         }
 
         prev_line.is_some()
@@ -934,19 +1287,26 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "bc");
     /// ```
     pub fn delete_char(&mut self) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         if self.delete_selection(false) {
             return true;
         }
 
-        if self.cursor_v2 == 0 {
+        let cursor = self.cursor();
+        if cursor == 0 {
             return false;
         }
 
-        if let Some((i, c)) = self.text.as_str().char_indices().nth(self.cursor_v2 - 1) {
-            self.text.splice(i, c.len_utf8() as isize, "");
-            self.cursor_v2 -= 1;
-
-            self.push_history(EditKindV2::DeleteChar(c), i);
+        if let Some(c) = self.text.as_str().chars().nth(cursor - 1) {
+            let kind = if c == '\n' {
+                EditKind::DeleteNewline
+            } else {
+                EditKind::DeleteChar(c)
+            };
+            let at = self.push_history(kind, cursor - 1);
+            self.set_cursor(at);
 
             true
         } else {
@@ -966,13 +1326,16 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "ac");
     /// ```
     pub fn delete_next_char(&mut self) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         if self.delete_selection(false) {
             return true;
         }
 
-        let before = self.cursor_v2;
+        let before = self.cursor();
         self.move_cursor_with_shift(CursorMove::Forward, false);
-        if before == self.cursor_v2 {
+        if before == self.cursor() {
             return false;
         }
 
@@ -994,15 +1357,19 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "ab");
     /// ```
     pub fn delete_line_by_end(&mut self) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         if self.delete_selection(false) {
             return true;
         }
 
+        let cursor = self.cursor();
         if self
             .text
             .as_str()
             .chars()
-            .nth(self.cursor_v2)
+            .nth(cursor)
             .is_some_and(|c| c == '\n')
         {
             self.delete_next_char();
@@ -1013,19 +1380,19 @@ impl<'a> MergeArea<'a> {
             .text
             .as_str()
             .chars()
-            .skip(self.cursor_v2)
+            .skip(cursor)
             .position(|c| c == '\n');
 
         let range = match next_newline {
-            Some(i) => (self.cursor_v2, self.cursor_v2 + i),
-            None => (self.cursor_v2, self.text.as_str().chars().count()),
+            Some(i) => (cursor, cursor + i),
+            None => (cursor, self.text.as_str().chars().count()),
         };
 
         if range.0 == range.1 {
             return false;
         }
 
-        self.delete_range_v2(range.0, range.1, true);
+        self.delete_range(range.0, range.1, true);
         true
     }
 
@@ -1044,15 +1411,19 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "cde");
     /// ```
     pub fn delete_line_by_head(&mut self) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         if self.delete_selection(false) {
             return true;
         }
 
+        let cursor = self.cursor();
         if self
             .text
             .as_str()
             .chars()
-            .nth(self.cursor_v2.saturating_sub(1))
+            .nth(cursor.saturating_sub(1))
             .is_some_and(|c| c == '\n')
         {
             return self.delete_char();
@@ -1063,19 +1434,19 @@ impl<'a> MergeArea<'a> {
             .as_str()
             .chars()
             .rev()
-            .skip(self.text.as_str().chars().count() - self.cursor_v2)
+            .skip(self.text.as_str().chars().count() - cursor)
             .position(|c| c == '\n');
 
         let range = match prev_newline {
-            Some(i) => (self.cursor_v2 - i, self.cursor_v2),
-            None => (0, self.cursor_v2),
+            Some(i) => (cursor - i, cursor),
+            None => (0, cursor),
         };
 
         if range.0 == range.1 {
             return false;
         }
 
-        self.delete_range_v2(range.0, range.1, true);
+        self.delete_range(range.0, range.1, true);
         true
     }
 
@@ -1085,7 +1456,7 @@ impl<'a> MergeArea<'a> {
     ///
     /// This method returns if some text was deleted or not in the textarea.
     ///
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::{MergeArea, CursorMove};
     ///
     /// let mut textarea = MergeArea::with_value("aaa bbb ccc");
@@ -1098,7 +1469,25 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "aaa ");
     /// ```
     pub fn delete_word(&mut self) -> bool {
-        todo!()
+        if self.scrubber.is_some() {
+            return false;
+        }
+        if self.delete_selection(false) {
+            return true;
+        }
+
+        let chars: Vec<char> = self.text.as_str().chars().collect();
+        let cursor = self.cursor();
+        let line_start = util::find_line_start(cursor, &chars);
+        let col = cursor - line_start;
+        if col == 0 {
+            return self.delete_char();
+        }
+
+        let line: String = chars[line_start..cursor].iter().collect();
+        let start = find_word_start_backward(&line, col).unwrap_or(0);
+        self.delete_range(line_start + start, cursor, true);
+        true
     }
 
     /// Delete a word next to cursor. Word boundary appears at spaces, punctuations, and others. For example `fn foo(a)`
@@ -1107,7 +1496,7 @@ impl<'a> MergeArea<'a> {
     ///
     /// This method returns if some text was deleted or not in the textarea.
     ///
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::MergeArea;
     ///
     /// let mut textarea = MergeArea::with_value("aaa bbb ccc");
@@ -1118,13 +1507,34 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), " ccc");
     /// ```
     pub fn delete_next_word(&mut self) -> bool {
-        todo!()
-    }
+        if self.scrubber.is_some() {
+            return false;
+        }
+        if self.delete_selection(false) {
+            return true;
+        }
 
-    /// Paste a string previously deleted by [`MergeArea::delete_line_by_head`], [`MergeArea::delete_line_by_end`],
+        let cursor = self.cursor();
+        let line: String = self
+            .text
+            .as_str()
+            .chars()
+            .skip(cursor)
+            .take_while(|&c| c != '\n')
+            .collect();
+        if line.is_empty() {
+            return self.delete_next_char();
+        }
+
+        let len = find_word_exclusive_end_forward(&line, 0).unwrap_or_else(|| line.chars().count());
+        self.delete_range(cursor, cursor + len, true);
+        true
+    }
+
+    /// Paste a string previously deleted by [`MergeArea::delete_line_by_head`], [`MergeArea::delete_line_by_end`],
     /// [`MergeArea::delete_word`], [`MergeArea::delete_next_word`]. This method returns if some text was inserted or not
     /// in the textarea.
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::{MergeArea, CursorMove};
     ///
     /// let mut textarea = MergeArea::with_value("aaa bbb ccc");
@@ -1135,12 +1545,12 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), " bbb cccaaa");
     /// ```
     pub fn paste(&mut self) -> bool {
-        self.delete_selection(false);
-        match self.yank.clone() {
-            YankText::Piece(s) => self.insert_piece(s),
-            _ => unreachable!(),
-            // YankText::Chunk(c) => self.insert_chunk(c),
+        if self.scrubber.is_some() {
+            return false;
         }
+        let deleted = self.delete_selection(false);
+        let inserted = self.insert_piece(self.yank.clone());
+        deleted || inserted
     }
 
     /// Start text selection at the cursor position. If text selection is already ongoing, the start position is reset.
@@ -1155,11 +1565,11 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.yank_text(), "aaa ");
     /// ```
     pub fn start_selection(&mut self) {
-        self.selection_start_v2 = Some(self.cursor_v2);
+        self.selection_start = Some(self.cursor.clone());
     }
 
     /// Stop the current text selection. This method does nothing if text selection is not ongoing.
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::{MergeArea, CursorMove};
     ///
     /// let mut textarea = MergeArea::with_value("aaa bbb ccc");
@@ -1178,10 +1588,6 @@ impl<'a> MergeArea<'a> {
         self.selection_start = None;
     }
 
-    pub fn cancel_selection_v2(&mut self) {
-        self.selection_start_v2 = None;
-    }
-
     /// Select the entire text. Cursor moves to the end of the text buffer. When text selection is already ongoing,
     /// it is canceled.
     /// ```
@@ -1199,11 +1605,11 @@ impl<'a> MergeArea<'a> {
     /// ```
     pub fn select_all(&mut self) {
         self.move_cursor(CursorMove::Jump(u16::MAX, u16::MAX));
-        self.selection_start_v2 = Some(0);
+        self.selection_start = Some(self.text.position(0));
     }
 
     /// Return if text selection is ongoing or not.
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::{MergeArea};
     ///
     /// let mut textarea = MergeArea::default();
@@ -1215,7 +1621,7 @@ impl<'a> MergeArea<'a> {
     /// assert!(!textarea.is_selecting());
     /// ```
     pub fn is_selecting(&self) -> bool {
-        self.selection_start_v2.is_some()
+        self.selection_start.is_some()
     }
 
     /// Set the style used for text selection. The default style is light blue.
@@ -1248,8 +1654,8 @@ impl<'a> MergeArea<'a> {
 
     // Get selection positions in chars
     fn selection_positions(&self) -> Option<(usize, usize)> {
-        let s = self.selection_start_v2?;
-        let e = self.cursor_v2;
+        let s = self.selection_start()?;
+        let e = self.cursor();
 
         match s.cmp(&e) {
             Ordering::Less => Some((s, e)),
@@ -1258,9 +1664,9 @@ impl<'a> MergeArea<'a> {
         }
     }
 
-    fn take_selection_positions_v2(&mut self) -> Option<(usize, usize)> {
+    fn take_selection_positions(&mut self) -> Option<(usize, usize)> {
         let range = self.selection_positions();
-        self.cancel_selection_v2();
+        self.cancel_selection();
         range
     }
 
@@ -1283,9 +1689,9 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "Hello World"); // Text does not change
     /// ```
     pub fn copy(&mut self) {
-        if let Some((s, e)) = self.take_selection_positions_v2() {
-            let text = &self.text.as_str().to_string()[s..e];
-            self.yank = YankText::Piece(text.to_string());
+        if let Some((s, e)) = self.take_selection_positions() {
+            let (s, e) = (self.char_index(s), self.char_index(e));
+            self.yank = self.text.as_str()[s..e].to_string();
         }
     }
 
@@ -1309,15 +1715,17 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "Hello ");
     /// ```
     pub fn cut(&mut self) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
         self.delete_selection(true)
     }
 
     fn delete_selection(&mut self, should_yank: bool) -> bool {
-        if let Some((s, e)) = self.take_selection_positions_v2() {
-            self.delete_range_v2(s, e, should_yank);
-            return true;
+        match self.take_selection_positions() {
+            Some((s, e)) => self.delete_range(s, e, should_yank),
+            None => false,
         }
-        false
     }
 
     /// Move the cursor to the position specified by the [`CursorMove`] parameter. For each kind of cursor moves, see
@@ -1333,24 +1741,47 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.cursor2(), (1, 1));
     /// ```
     pub fn move_cursor(&mut self, m: CursorMove) {
-        self.move_cursor_with_shift(m, self.selection_start_v2.is_some());
+        self.move_cursor_with_shift(m, self.selection_start.is_some());
     }
 
     pub fn move_cursor_with_shift(&mut self, m: CursorMove, shift: bool) {
-        if let Some(cursor) = m.next_cursor(self.cursor_v2, &self.text, &self.viewport) {
+        let concurrent_edits = self.concurrent_edits();
+        let diagnostics: Vec<_> = self.text.diagnostics().iter().map(|d| d.range).collect();
+        let folds = self.visible_folds();
+        let (_, _, width, _) = self.viewport.rect();
+        let wrapper = self.wrapper(width);
+        // Only vertical movements are by the rows on the screen
+        let vertical = matches!(
+            m,
+            CursorMove::Up | CursorMove::Down | CursorMove::InViewport
+        );
+        let ctx = CursorContext {
+            viewport: &self.viewport,
+            concurrent_edits: &concurrent_edits,
+            diagnostics: &diagnostics,
+            wrap: (vertical && (wrapper.wraps() || !folds.is_empty()))
+                .then(|| (wrapper, self.cursor_rows(wrapper))),
+        };
+        let current = self.cursor();
+        if let Some(cursor) = m.next_cursor(current, self.text.text(), &ctx) {
+            let cursor = self.step_over_folds(current, cursor, &folds);
             if shift {
-                if self.selection_start_v2.is_none() {
+                if self.selection_start.is_none() {
                     self.start_selection();
                 }
             } else {
-                self.cancel_selection_v2();
+                self.cancel_selection();
             }
-            self.cursor_v2 = cursor;
+            self.set_cursor(cursor);
         };
     }
 
     /// Undo the last modification. This method returns if the undo modified text contents or not in the textarea.
-    /// ```ignore
+    ///
+    /// Only modifications made through this textarea are recorded. When an automerge document is attached, the
+    /// modification is reverted at its current position, so text inserted or deleted by other peers since then is kept
+    /// as is.
+    /// ```
     /// use ratatui_mergearea::{MergeArea, CursorMove};
     ///
     /// let mut textarea = MergeArea::with_value("abc def");
@@ -1361,9 +1792,14 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), "abc def");
     /// ```
     pub fn undo(&mut self) -> bool {
-        if let Some(cursor) = self.history_v2.undo(&mut self.text) {
-            self.cancel_selection_v2();
-            self.cursor_v2 = cursor;
+        if self.scrubber.is_some() {
+            return false;
+        }
+        let cursor = self.history.undo(&mut self.text);
+        self.follow_caught_up();
+        if let Some(cursor) = cursor {
+            self.cancel_selection();
+            self.set_cursor(cursor);
             true
         } else {
             false
@@ -1371,7 +1807,7 @@ impl<'a> MergeArea<'a> {
     }
 
     /// Redo the last undo change. This method returns if the redo modified text contents or not in the textarea.
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::{MergeArea, CursorMove};
     ///
     /// let mut textarea = MergeArea::with_value("abc def");
@@ -1384,26 +1820,27 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.text().as_str(), " def");
     /// ```
     pub fn redo(&mut self) -> bool {
-        if let Some(cursor) = self.history.redo(&mut self.lines) {
-            self.cancel_selection();
-            self.cursor = cursor;
-            true
-        } else {
-            false
+        if self.scrubber.is_some() {
+            return false;
         }
-    }
-
-    pub fn redo_v2(&mut self) -> bool {
-        if let Some(cursor) = self.history_v2.redo(&mut self.text) {
-            self.cancel_selection_v2();
-            self.cursor_v2 = cursor;
+        let cursor = self.history.redo(&mut self.text);
+        self.follow_caught_up();
+        if let Some(cursor) = cursor {
+            self.cancel_selection();
+            self.set_cursor(cursor);
             true
         } else {
             false
         }
     }
 
-    pub(crate) fn line_spans<'b>(&'b self, line: &'b str, row: usize, lnum_len: u8) -> Line<'b> {
+    pub(crate) fn line_spans<'b>(
+        &'b self,
+        line: &'b str,
+        vrow: &VisualRow,
+        lnum_len: u8,
+        deco: &Decorations<'b>,
+    ) -> Line<'b> {
         let mut hl = LineHighlighter::new(
             line,
             self.cursor_style,
@@ -1412,53 +1849,989 @@ impl<'a> MergeArea<'a> {
             self.select_style,
         );
 
-        let cursor2 = self.cursor2();
-        if row == cursor2.0 {
-            hl.cursor_line(cursor2.1, self.cursor_line_style);
+        // The line number is shown only in the first row of a wrapped line
+        if let Some(style) = self.line_number_style {
+            if vrow.first {
+                hl.line_number(vrow.row, lnum_len, style);
+            } else {
+                hl.line_number_padding(lnum_len, style);
+            }
+        }
+        if !vrow.last {
+            hl.continued();
+        }
+        if let Some((hidden, _)) = vrow.folded {
+            let lines = if hidden == 1 { "line" } else { "lines" };
+            hl.folded(format!(" [{hidden} {lines}]"), self.fold_style);
         }
 
-        // if self.cursor() == self.text.as_str().len() {
-        //     hl.with_select_at_end();
-        // }
+        let (line_start, line_end) = (vrow.start, vrow.end);
+        hl.text_styles(line_start, line_end, &deco.syntax);
 
+        // The cursor and the selections are in the live text, not in the viewed history
+        if self.scrubber.is_some() {
+            return hl.into_spans();
+        }
+
+        // The gutter of the header of a fold stands for the hidden lines as well
+        let gutter_end = vrow.fold_end();
         if let Some(style) = self.line_number_style {
-            hl.line_number(row, lnum_len, style);
+            let commented = deco
+                .comments
+                .iter()
+                .any(|&(start, end)| start <= gutter_end && line_start < end);
+            if commented {
+                let style = Style {
+                    fg: self.comment_style.fg,
+                    ..style
+                };
+                hl.line_number_marker(COMMENT_MARKER, style);
+            }
+
+            // The most severe diagnostic touching this row is shown in the sign column
+            if self.has_sign_column() {
+                let severity = deco
+                    .diagnostics
+                    .iter()
+                    .filter(|&&(start, end, _)| start <= gutter_end && line_start <= end)
+                    .filter(|&&(start, end, _)| line_start < end || start == end)
+                    .map(|&(_, _, severity)| severity)
+                    .min();
+                match severity {
+                    Some(severity) => {
+                        let style = Style {
+                            fg: self.diagnostic_styles[severity.index()].fg,
+                            ..style
+                        };
+                        hl.sign(severity.sign(), style);
+                    }
+                    None => hl.sign(" ", style),
+                }
+            }
+        }
+
+        let (cursor, cursor_row) = deco.cursor;
+        if vrow.row == cursor_row {
+            if vrow.contains(cursor) {
+                hl.cursor_line(cursor - line_start, self.cursor_line_style);
+            } else {
+                hl.line_style(self.cursor_line_style);
+            }
+        }
+
+        #[cfg(feature = "search")]
+        {
+            let matches = &deco.search_matches;
+            hl.search(line_start, line_end, matches, self.search.style);
+            if let Some(i) = search::match_at(matches, cursor) {
+                hl.current_search(line_start, line_end, matches[i], self.search.current_style);
+            }
         }
 
-        // #[cfg(feature = "search")]
-        // if let Some(matches) = self.search.matches(line) {
-        //     hl.search(matches, self.search.style);
-        // }
+        hl.text_styles(line_start, line_end, &deco.text_styles);
 
-        if let Some((start, end)) = self.selection_positions() {
-            let line_start = self.find_row_start_offset(row);
-            let line_end = util::find_line_end(
-                line_start,
-                &self.text.as_str().chars().collect::<Vec<char>>(),
-            );
-            hl.selection(line_start, line_end, start, end);
+        // The selection takes the offset of the last char of the row
+        if let Some((start, end)) = deco.selection.filter(|&(start, _)| start <= line_end) {
+            hl.selection(line_start, line_end.saturating_sub(1), start, end);
+        }
+
+        for r in &deco.remotes {
+            if let Some((start, end)) = r.selection {
+                hl.remote_selection(line_start, line_end, start, end, r.remote.selection_style);
+            }
+            if vrow.contains(r.caret) {
+                let label = r.remote.label.as_deref();
+                hl.remote_cursor(r.caret - line_start, r.remote.style, label);
+            }
         }
 
         hl.into_spans()
     }
 
-    fn find_row_start_offset(&self, row: usize) -> usize {
-        if row == 0 {
-            return 0;
+    pub(crate) fn resolve_remote_cursors(&self) -> Vec<Resolved<'_>> {
+        self.remote_cursors
+            .iter()
+            .filter_map(|r| Resolved::new(r, &self.text))
+            .collect()
+    }
+
+    /// Set carets and selections of remote peers editing the same text. They are rendered with their own styles
+    /// beneath the local cursor and selection. The previously set remote cursors are replaced. Remote cursors whose
+    /// [`RemotePosition::Anchor`](crate::RemotePosition::Anchor) does not belong to the attached document are not
+    /// rendered.
+    /// ```
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui_mergearea::{MergeArea, RemoteCursor};
+    ///
+    /// let mut textarea = MergeArea::with_value("hello world");
+    ///
+    /// let mut peer = RemoteCursor::new("peer-1", 11, Style::default().bg(Color::Magenta));
+    /// peer.label = Some("alice".to_string());
+    /// peer.selection = Some(6.into()); // Selecting "world"
+    /// textarea.set_remote_cursors(vec![peer]);
+    ///
+    /// assert_eq!(textarea.remote_cursors().len(), 1);
+    /// ```
+    pub fn set_remote_cursors(&mut self, cursors: Vec<RemoteCursor>) {
+        self.remote_cursors = cursors;
+    }
+
+    /// Get the remote cursors set by [`MergeArea::set_remote_cursors`].
+    pub fn remote_cursors(&self) -> &[RemoteCursor] {
+        &self.remote_cursors
+    }
+
+    // Decorations of the chars between the char offsets `start` and `end`. Nothing is decorated while viewing the
+    // history since they are in the live text
+    pub(crate) fn decorations(&self, start: usize, end: usize) -> Decorations<'_> {
+        // The syntax is highlighted in the viewed history as well
+        #[allow(unused_mut)]
+        let mut syntax = match &self.syntax {
+            Some(syntax) => syntax.runs(self.displayed_text(), start, end),
+            None => vec![],
+        };
+        // The parse tree is of the live text
+        #[cfg(feature = "tree-sitter")]
+        if let (Some(tree), None) = (self.text.parse_tree(), &self.scrubber) {
+            syntax.extend(tree.runs(self.text.as_str(), start, end));
+        }
+        if self.scrubber.is_some() {
+            return Decorations {
+                syntax,
+                ..Decorations::default()
+            };
         }
 
-        let mut current_row = 0;
+        // Styles of marks are patched onto authors, and comments onto marks
+        let marks = self.text.marks(start, end).unwrap_or_default();
+        let mut text_styles = vec![];
+        if let Some(blame) = &self.blame {
+            text_styles.extend(blame.runs(&self.text, start, end));
+        }
+        if let Some(style) = &self.mark_style {
+            text_styles.extend(style.runs(&marks));
+        }
+        let comments: Vec<_> = comment::from_marks(marks)
+            .into_iter()
+            .map(|c| c.range)
+            .collect();
+        text_styles.extend(comments.iter().map(|&(s, e)| (s, e, self.comment_style)));
+        if let Some(style) = self.concurrent_edit_style {
+            let edits = self.concurrent_edits();
+            let visible = edits.into_iter().filter(|&(s, e)| s < end && start < e);
+            text_styles.extend(visible.map(|(s, e)| (s, e, style)));
+        }
+        let diagnostics: Vec<_> = self
+            .text
+            .diagnostics()
+            .iter()
+            .filter(|d| d.range.0 < end && start <= d.range.1)
+            .map(|d| (d.range.0, d.range.1, d.severity))
+            .collect();
+        text_styles.extend(
+            diagnostics
+                .iter()
+                .map(|&(s, e, severity)| (s, e, self.diagnostic_styles[severity.index()])),
+        );
 
-        for (i, ch) in self.text.as_str().char_indices() {
-            if ch == '\n' {
-                current_row += 1;
-                if current_row == row {
-                    return i + 1;
-                }
+        // Matches are searched in the whole text since a pattern may look at the text around the visible rows
+        #[cfg(feature = "search")]
+        let search_matches = self
+            .cached_search_matches()
+            .iter()
+            .copied()
+            .skip_while(|&(_, e)| e < start)
+            .take_while(|&(s, _)| s < end)
+            .collect();
+
+        let cursor = self.cursor();
+        Decorations {
+            cursor: (cursor, util::cursor_for(self.text.as_str(), cursor).0),
+            selection: self.selection_positions(),
+            syntax,
+            remotes: self.resolve_remote_cursors(),
+            text_styles,
+            comments,
+            diagnostics,
+            #[cfg(feature = "search")]
+            search_matches,
+        }
+    }
+
+    /// Style the text by the actor who inserted it. The style returned by `style` for each actor of the attached
+    /// automerge document is applied beneath the cursor, the selections and the search matches. Text merged from other
+    /// peers is styled by their actors. Nothing is styled when no document is attached or while viewing the history.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{AutoCommit, ObjType, ROOT};
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// let me = doc.get_actor().clone();
+    /// let mut textarea = MergeArea::from_doc(doc, obj).unwrap();
+    ///
+    /// // Show text written by others in yellow
+    /// textarea.set_blame_style(move |actor| {
+    ///     if *actor == me {
+    ///         Style::default()
+    ///     } else {
+    ///         Style::default().fg(Color::Yellow)
+    ///     }
+    /// });
+    /// assert!(textarea.has_blame());
+    /// ```
+    pub fn set_blame_style(&mut self, style: impl Fn(&ActorId) -> Style + Send + Sync + 'static) {
+        self.blame = Some(Blame::new(style));
+    }
+
+    /// Remove the style set by [`MergeArea::set_blame_style`]. After calling this method, the text is no longer
+    /// styled by its authors.
+    /// ```
+    /// use ratatui::style::Style;
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::default();
+    ///
+    /// textarea.set_blame_style(|_| Style::default());
+    /// textarea.remove_blame();
+    /// assert!(!textarea.has_blame());
+    /// ```
+    pub fn remove_blame(&mut self) {
+        self.blame = None;
+    }
+
+    /// Return whether the text is styled by its authors. See [`MergeArea::set_blame_style`].
+    pub fn has_blame(&self) -> bool {
+        self.blame.is_some()
+    }
+
+    /// Highlight the syntax of the text with the highlighter. The styles are applied beneath the cursor, the selections
+    /// and the search matches, and the styles of the authors and the marks are patched onto them. The text viewed in
+    /// the history is highlighted as well. See [`Highlighter`] for details.
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui::widgets::Widget as _;
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::with_value("let x = 1;");
+    ///
+    /// // Highlight the `let` keyword
+    /// textarea.set_highlighter(|line: &str, _row: usize| {
+    ///     let style = Style::default().fg(Color::Magenta);
+    ///     line.match_indices("let").map(|(i, s)| (i..i + s.len(), style)).collect()
+    /// });
+    /// assert!(textarea.has_highlighter());
+    ///
+    /// let area = Rect::new(0, 0, 10, 1);
+    /// let mut buf = Buffer::empty(area);
+    /// textarea.render(area, &mut buf);
+    /// assert_eq!(buf[(1, 0)].fg, Color::Magenta);
+    /// assert_eq!(buf[(4, 0)].fg, Color::Reset);
+    /// ```
+    pub fn set_highlighter(&mut self, highlighter: impl Highlighter + Send + Sync + 'static) {
+        self.syntax = Some(Syntax::new(highlighter));
+    }
+
+    /// Remove the highlighter set by [`MergeArea::set_highlighter`].
+    /// ```
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::default();
+    ///
+    /// textarea.set_highlighter(|_: &str, _: usize| vec![]);
+    /// textarea.remove_highlighter();
+    /// assert!(!textarea.has_highlighter());
+    /// ```
+    pub fn remove_highlighter(&mut self) {
+        self.syntax = None;
+    }
+
+    /// Return whether the syntax of the text is highlighted. See [`MergeArea::set_highlighter`].
+    pub fn has_highlighter(&self) -> bool {
+        self.syntax.is_some()
+    }
+
+    /// Parse the text with [tree-sitter](https://tree-sitter.github.io) and highlight it by the captures of the
+    /// highlight query. `style` maps the names of the captures such as `keyword` or `function.method` to styles. The
+    /// parse tree is updated incrementally on every modification of the text, including the ones merged from other
+    /// peers, so only the modified part of the text is parsed again. An error is returned when the query is invalid.
+    ///
+    /// The styles are applied beneath the cursor, the selections and the search matches as the styles of
+    /// [`MergeArea::set_highlighter`]. The text viewed in the history is not highlighted.
+    /// ```
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::with_value("fn main() {}");
+    /// let query = "\"fn\" @keyword (identifier) @function";
+    /// textarea
+    ///     .set_tree_sitter(tree_sitter_rust::LANGUAGE.into(), query, |name| match name {
+    ///         "keyword" => Style::default().fg(Color::Magenta),
+    ///         _ => Style::default(),
+    ///     })
+    ///     .unwrap();
+    ///
+    /// textarea.insert_str("pub ");
+    /// let tree = textarea.syntax_tree().unwrap();
+    /// assert_eq!(tree.root_node().child(0).unwrap().kind(), "function_item");
+    /// ```
+    #[cfg(feature = "tree-sitter")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tree-sitter")))]
+    pub fn set_tree_sitter(
+        &mut self,
+        language: tree_sitter::Language,
+        query: &str,
+        style: impl Fn(&str) -> Style + Send + Sync + 'static,
+    ) -> Result<(), tree_sitter::QueryError> {
+        let tree = ParseTree::new(language, query, style, self.text.as_str())?;
+        self.text.set_parse_tree(Some(tree));
+        Ok(())
+    }
+
+    /// Stop parsing the text set by [`MergeArea::set_tree_sitter`].
+    #[cfg(feature = "tree-sitter")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tree-sitter")))]
+    pub fn remove_tree_sitter(&mut self) {
+        self.text.set_parse_tree(None);
+    }
+
+    /// Get the parse tree of the current text when [`MergeArea::set_tree_sitter`] was called. `None` is returned when
+    /// the text is not parsed or the parser failed.
+    #[cfg(feature = "tree-sitter")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tree-sitter")))]
+    pub fn syntax_tree(&self) -> Option<&tree_sitter::Tree> {
+        self.text.parse_tree()?.tree()
+    }
+
+    /// Set a rich-text mark such as bold or link on the chars between the char offsets `range.0` and `range.1` in the
+    /// attached automerge document. Pass [`MergeArea::selection_range`] to mark the selected text. Marks are stored in
+    /// the document, so they are shared with and merged between peers. Text inserted at the end of the range is
+    /// marked as well. Nothing happens when no document is attached. Marks are not recorded in the undo history.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{AutoCommit, ObjType, ReadDoc as _, ROOT};
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "hello world").unwrap();
+    /// let mut textarea = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    ///
+    /// // Select "hello" and make it bold
+    /// textarea.start_selection();
+    /// textarea.move_cursor(CursorMove::Jump(0, 5));
+    /// textarea.set_mark(textarea.selection_range().unwrap(), "bold", true).unwrap();
+    ///
+    /// let marks = textarea.doc().unwrap().marks(&obj).unwrap();
+    /// assert_eq!((marks[0].name(), marks[0].start, marks[0].end), ("bold", 0, 5));
+    ///
+    /// textarea.remove_mark((0, 11), "bold").unwrap();
+    /// assert!(textarea.doc().unwrap().marks(&obj).unwrap().is_empty());
+    /// ```
+    pub fn set_mark(
+        &mut self,
+        range: (usize, usize),
+        name: &str,
+        value: impl Into<ScalarValue>,
+    ) -> Result<(), AutomergeError> {
+        let (start, end) = (cmp::min(range.0, range.1), cmp::max(range.0, range.1));
+        let marked = self
+            .text
+            .mark(start, end, name, value.into(), ExpandMark::After);
+        self.follow_caught_up();
+        marked
+    }
+
+    /// Remove the mark named `name` from the chars between the char offsets `range.0` and `range.1`. See
+    /// [`MergeArea::set_mark`].
+    pub fn remove_mark(&mut self, range: (usize, usize), name: &str) -> Result<(), AutomergeError> {
+        let (start, end) = (cmp::min(range.0, range.1), cmp::max(range.0, range.1));
+        let unmarked = self.text.unmark(start, end, name, ExpandMark::After);
+        self.follow_caught_up();
+        unmarked
+    }
+
+    /// Style the text by its rich-text marks. `style` maps the name and the value of each mark to its style. Styles of
+    /// overlapping marks are patched onto each other and onto the author styles set by
+    /// [`MergeArea::set_blame_style`]. The cursor, the selections and the search matches are rendered above them. Marks
+    /// of comments added by [`MergeArea::add_comment`] are not passed to `style`.
+    /// ```
+    /// use ratatui::style::{Modifier, Style};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::default();
+    ///
+    /// textarea.set_mark_style(|name, _| match name {
+    ///     "bold" => Style::default().add_modifier(Modifier::BOLD),
+    ///     "italic" => Style::default().add_modifier(Modifier::ITALIC),
+    ///     _ => Style::default(),
+    /// });
+    /// assert!(textarea.has_mark_style());
+    /// ```
+    pub fn set_mark_style(
+        &mut self,
+        style: impl Fn(&str, &ScalarValue) -> Style + Send + Sync + 'static,
+    ) {
+        self.mark_style = Some(MarkStyle::new(style));
+    }
+
+    /// Remove the style set by [`MergeArea::set_mark_style`]. After calling this method, marks are no longer rendered.
+    pub fn remove_mark_style(&mut self) {
+        self.mark_style = None;
+    }
+
+    /// Return whether marks are rendered. See [`MergeArea::set_mark_style`].
+    pub fn has_mark_style(&self) -> bool {
+        self.mark_style.is_some()
+    }
+
+    /// Anchor the comment thread `id` to the chars between the char offsets `range.0` and `range.1`. Pass
+    /// [`MergeArea::selection_range`] to comment on the selected text. The anchor is stored as a mark in the attached
+    /// automerge document, so it is shared with peers and follows the commented text through local edits and merges.
+    /// Text inserted at the edges of the range is not commented. Adding a comment with the same ID again extends its
+    /// range. Nothing happens when no document is attached.
+    ///
+    /// Commented text is rendered with [`MergeArea::comment_style`] and the line numbers of commented lines are marked.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{AutoCommit, ObjType, ROOT};
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "hello world").unwrap();
+    /// let mut textarea = MergeArea::from_doc(doc, obj).unwrap();
+    ///
+    /// // Comment on "world"
+    /// textarea.move_cursor(CursorMove::Jump(0, 6));
+    /// textarea.start_selection();
+    /// textarea.move_cursor(CursorMove::End);
+    /// textarea.add_comment(textarea.selection_range().unwrap(), "thread-1").unwrap();
+    ///
+    /// // Show the thread when the cursor is on the commented text
+    /// textarea.move_cursor(CursorMove::Jump(0, 8));
+    /// let comments = textarea.comments_at(textarea.cursor());
+    /// assert_eq!(comments[0].id, "thread-1");
+    /// assert_eq!(comments[0].range, (6, 11));
+    /// ```
+    pub fn add_comment(&mut self, range: (usize, usize), id: &str) -> Result<(), AutomergeError> {
+        let (start, end) = (cmp::min(range.0, range.1), cmp::max(range.0, range.1));
+        let name = comment::mark_name(id);
+        let marked = self
+            .text
+            .mark(start, end, &name, true.into(), ExpandMark::None);
+        self.follow_caught_up();
+        marked
+    }
+
+    /// Remove the comment thread `id` from the entire text. See [`MergeArea::add_comment`].
+    pub fn remove_comment(&mut self, id: &str) -> Result<(), AutomergeError> {
+        let len = self.text.as_str().chars().count();
+        let name = comment::mark_name(id);
+        let unmarked = self.text.unmark(0, len, &name, ExpandMark::None);
+        self.follow_caught_up();
+        unmarked
+    }
+
+    /// Get all comments on the text sorted by their ranges. See [`MergeArea::add_comment`].
+    pub fn comments(&self) -> Vec<Comment> {
+        let len = self.text.as_str().chars().count();
+        comment::from_marks(self.text.marks(0, len).unwrap_or_default())
+    }
+
+    /// Get the comments whose commented text contains the char offset, typically [`MergeArea::cursor`]. Comments which
+    /// end at the offset are also returned so that a thread is found when the cursor is just after the commented text.
+    pub fn comments_at(&self, offset: usize) -> Vec<Comment> {
+        let mut comments = self.comments();
+        comments.retain(|c| c.contains(offset));
+        comments
+    }
+
+    /// Set the style of commented text. The default style is yellow and underlined. The foreground color is also used
+    /// for the marker of commented lines next to the line numbers.
+    /// ```
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::default();
+    ///
+    /// let style = Style::default().bg(Color::Yellow);
+    /// textarea.set_comment_style(style);
+    /// assert_eq!(textarea.comment_style(), style);
+    /// ```
+    pub fn set_comment_style(&mut self, style: Style) {
+        self.comment_style = style;
+    }
+
+    /// Get the style of commented text.
+    pub fn comment_style(&self) -> Style {
+        self.comment_style
+    }
+
+    /// Highlight the regions of the text which were edited concurrently by more than one peer. When two peers type at
+    /// the same position, automerge interleaves their text without conflicts. After setting this style, each merge by
+    /// [`MergeArea::merge`] or a sync session which applies changes of other peers finds the runs of text inserted
+    /// since the last common heads of both peers, and the runs inserted by several actors are rendered with `style`
+    /// beneath the cursor, the selections and the search matches.
+    ///
+    /// The regions follow the text through later edits. List them with [`MergeArea::concurrent_edits`] and jump
+    /// between them with [`CursorMove::NextConcurrentEdit`] and [`CursorMove::PrevConcurrentEdit`].
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new().with_actor(ActorId::from(b"alice"));
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "Hi !").unwrap();
+    /// let mut remote = doc.fork().with_actor(ActorId::from(b"bob"));
+    ///
+    /// let mut textarea = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    /// textarea.set_concurrent_edit_style(Style::default().bg(Color::Red));
+    ///
+    /// // Both peers type at the same position
+    /// textarea.move_cursor(CursorMove::Jump(0, 3));
+    /// textarea.insert_str("Bob");
+    /// remote.splice_text(&obj, 3, 0, "Alice").unwrap();
+    /// textarea.merge(&mut remote).unwrap();
+    ///
+    /// assert_eq!(textarea.concurrent_edits(), [(3, 11)]);
+    /// ```
+    pub fn set_concurrent_edit_style(&mut self, style: Style) {
+        self.concurrent_edit_style = Some(style);
+    }
+
+    /// Stop highlighting the concurrently edited regions. The detected regions are cleared. See
+    /// [`MergeArea::set_concurrent_edit_style`].
+    pub fn remove_concurrent_edit_style(&mut self) {
+        self.concurrent_edit_style = None;
+        self.concurrent_edits.clear();
+    }
+
+    /// Get the style of concurrently edited regions. `None` is returned when they are not highlighted. See
+    /// [`MergeArea::set_concurrent_edit_style`].
+    pub fn concurrent_edit_style(&self) -> Option<Style> {
+        self.concurrent_edit_style
+    }
+
+    /// Get the regions edited concurrently by several peers which were detected on the last merge, as char offsets of
+    /// their starts and ends (exclusive). Regions are sorted and regions whose text was deleted entirely are omitted.
+    /// See [`MergeArea::set_concurrent_edit_style`].
+    pub fn concurrent_edits(&self) -> Vec<(usize, usize)> {
+        let mut edits: Vec<_> = self
+            .concurrent_edits
+            .iter()
+            .filter_map(|(first, last)| self.text.cursor_range(first, last))
+            .collect();
+        edits.sort_unstable();
+        edits
+    }
+
+    /// Forget the concurrently edited regions, for example after the user reviewed them. Regions are detected again
+    /// on the next merge.
+    pub fn clear_concurrent_edits(&mut self) {
+        self.concurrent_edits.clear();
+    }
+
+    /// Set the diagnostics of the text such as the errors reported by a linter. The previous diagnostics are replaced.
+    /// Diagnosed text is rendered with the style of each severity, and the sign of the most severe diagnostic on each
+    /// line is shown in a column next to the line number while line numbers are shown. The ranges follow the text as
+    /// it is edited until the next call of this method. Jump between the diagnostics with
+    /// [`CursorMove::NextDiagnostic`] and [`CursorMove::PrevDiagnostic`].
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui::widgets::Widget as _;
+    /// use ratatui_mergearea::{Diagnostic, MergeArea, Severity};
+    ///
+    /// let mut textarea = MergeArea::with_value("let x = y;");
+    /// textarea.set_line_number_style(Style::default());
+    /// textarea.set_diagnostics(vec![Diagnostic {
+    ///     range: (8, 9),
+    ///     severity: Severity::Error,
+    ///     message: "cannot find value `y`".to_string(),
+    /// }]);
+    ///
+    /// // The range follows the edit before it
+    /// textarea.insert_str("// ");
+    /// assert_eq!(textarea.diagnostics()[0].range, (11, 12));
+    ///
+    /// let area = Rect::new(0, 0, 20, 1);
+    /// let mut buf = Buffer::empty(area);
+    /// textarea.render(area, &mut buf);
+    /// let row: String = (0..20).map(|x| buf[(x, 0)].symbol()).collect();
+    /// assert_eq!(row, "E 1 // let x = y;   ");
+    /// assert_eq!(buf[(15, 0)].fg, Color::Red);
+    /// ```
+    pub fn set_diagnostics(&mut self, mut diagnostics: Vec<Diagnostic>) {
+        // Ranges past the end of the text are clamped to it
+        let len = self.text.as_str().chars().count();
+        for d in &mut diagnostics {
+            let (start, end) = d.range;
+            d.range = (cmp::min(start, end).min(len), cmp::max(start, end).min(len));
+        }
+        diagnostics.sort_by_key(|d| (d.range, d.severity));
+        self.text.set_diagnostics(diagnostics);
+    }
+
+    /// Get the diagnostics at their current positions, sorted by their ranges. See [`MergeArea::set_diagnostics`].
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        self.text.diagnostics()
+    }
+
+    /// Get the diagnostics whose diagnosed text contains the char offset, typically [`MergeArea::cursor`]. Diagnostics
+    /// which end at the offset are also returned.
+    pub fn diagnostics_at(&self, offset: usize) -> Vec<&Diagnostic> {
+        self.diagnostics()
+            .iter()
+            .filter(|d| d.contains(offset))
+            .collect()
+    }
+
+    /// Set the style of the text diagnosed with the severity. The foreground color is also used for the sign of the
+    /// severity. By default, the text is underlined in red, yellow, blue and cyan for errors, warnings, information and
+    /// hints.
+    /// ```
+    /// use ratatui::style::{Color, Modifier, Style};
+    /// use ratatui_mergearea::{MergeArea, Severity};
+    ///
+    /// let mut textarea = MergeArea::default();
+    ///
+    /// let style = Style::default().fg(Color::Magenta).add_modifier(Modifier::UNDERLINED);
+    /// textarea.set_diagnostic_style(Severity::Error, style);
+    /// assert_eq!(textarea.diagnostic_style(Severity::Error), style);
+    /// ```
+    pub fn set_diagnostic_style(&mut self, severity: Severity, style: Style) {
+        self.diagnostic_styles[severity.index()] = style;
+    }
+
+    /// Get the style of the text diagnosed with the severity.
+    pub fn diagnostic_style(&self, severity: Severity) -> Style {
+        self.diagnostic_styles[severity.index()]
+    }
+
+    // The sign column of diagnostics is shown next to the line numbers of the live text
+    pub(crate) fn has_sign_column(&self) -> bool {
+        self.line_number_style.is_some()
+            && self.scrubber.is_none()
+            && !self.text.diagnostics().is_empty()
+    }
+
+    /// Fold the range of rows. The first row of the range stays visible as the header of the fold and the other rows
+    /// are hidden. A placeholder with the number of the hidden lines is rendered after the header, and line numbers
+    /// keep counting the hidden lines. Vertical cursor movements step over the fold, and the cursor moved into the
+    /// hidden lines in other ways is put at the end of the header. A range of less than two rows is ignored.
+    ///
+    /// The fold follows the rows through later edits and merges. Overlapping folds are rendered as one fold.
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::widgets::Widget as _;
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut textarea = MergeArea::with_value("fn f() {\n    a;\n    b;\n}");
+    /// textarea.fold(0..3);
+    /// assert_eq!(textarea.folds(), [0..3]);
+    ///
+    /// let area = Rect::new(0, 0, 20, 2);
+    /// let mut buf = Buffer::empty(area);
+    /// textarea.render(area, &mut buf);
+    /// let rows: Vec<String> = (0..2)
+    ///     .map(|y| (0..20).map(|x| buf[(x, y)].symbol()).collect())
+    ///     .collect();
+    /// assert_eq!(rows, ["fn f() { [2 lines]  ", "}                   "]);
+    ///
+    /// // The cursor steps over the hidden lines
+    /// textarea.move_cursor(CursorMove::Down);
+    /// assert_eq!(textarea.cursor2(), (3, 0));
+    /// ```
+    pub fn fold(&mut self, rows: Range<usize>) {
+        let lines = self.text.as_str().matches('\n').count() + 1;
+        let rows = rows.start..rows.end.min(lines);
+        if rows.len() < 2 {
+            return;
+        }
+        let mut folds = self.text.folds().to_vec();
+        folds.push(rows);
+        self.text.set_folds(folds);
+        let cursor = self.cursor();
+        let cursor = self.step_over_folds(cursor, cursor, &self.visible_folds());
+        self.set_cursor(cursor);
+    }
+
+    /// Unfold the folds containing the row. This method returns whether any fold was removed.
+    /// ```
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::with_value("a\nb\nc\nd");
+    /// textarea.fold(0..2);
+    /// textarea.fold(2..4);
+    ///
+    /// assert!(textarea.unfold(3));
+    /// assert_eq!(textarea.folds(), [0..2]);
+    /// assert!(!textarea.unfold(3));
+    /// ```
+    pub fn unfold(&mut self, row: usize) -> bool {
+        let mut folds = self.text.folds().to_vec();
+        let len = folds.len();
+        folds.retain(|f| !f.contains(&row));
+        let unfolded = folds.len() < len;
+        self.text.set_folds(folds);
+        unfolded
+    }
+
+    /// Unfold all folds.
+    pub fn unfold_all(&mut self) {
+        self.text.set_folds(vec![]);
+    }
+
+    /// Get the folded ranges of rows at their current positions in the order they were folded. See
+    /// [`MergeArea::fold`].
+    pub fn folds(&self) -> &[Range<usize>] {
+        self.text.folds()
+    }
+
+    /// Unfold the folds containing the row of the cursor. When no fold contains it, fold the range which the provider
+    /// set by [`MergeArea::set_fold_provider`] finds at the row. This method returns whether anything was folded or
+    /// unfolded.
+    /// ```
+    /// use ratatui_mergearea::{CursorMove, IndentFolds, MergeArea};
+    ///
+    /// let mut textarea = MergeArea::with_value("if x {\n    y\n}");
+    /// textarea.set_fold_provider(IndentFolds::default());
+    ///
+    /// textarea.move_cursor(CursorMove::Down);
+    /// assert!(textarea.toggle_fold_at_cursor());
+    /// assert_eq!(textarea.folds(), [0..2]);
+    /// assert_eq!(textarea.cursor2(), (0, 6)); // The cursor was in the hidden line
+    ///
+    /// assert!(textarea.toggle_fold_at_cursor());
+    /// assert!(textarea.folds().is_empty());
+    /// ```
+    pub fn toggle_fold_at_cursor(&mut self) -> bool {
+        let row = self.cursor2().0;
+        if self.unfold(row) {
+            return true;
+        }
+        let range = self
+            .folder
+            .as_ref()
+            .and_then(|f| f.fold_range(self.text.as_str(), row));
+        match range {
+            Some(range) if range.len() > 1 => {
+                self.fold(range);
+                true
             }
+            _ => false,
+        }
+    }
+
+    /// Set the provider which finds the range to fold at the cursor on [`MergeArea::toggle_fold_at_cursor`]. See
+    /// [`FoldProvider`] and [`crate::IndentFolds`] for details.
+    pub fn set_fold_provider(&mut self, provider: impl FoldProvider + Send + Sync + 'static) {
+        self.folder = Some(Folder::new(provider));
+    }
+
+    /// Remove the provider set by [`MergeArea::set_fold_provider`].
+    /// ```
+    /// use ratatui_mergearea::{IndentFolds, MergeArea};
+    ///
+    /// let mut textarea = MergeArea::default();
+    ///
+    /// textarea.set_fold_provider(IndentFolds::default());
+    /// assert!(textarea.has_fold_provider());
+    /// textarea.remove_fold_provider();
+    /// assert!(!textarea.has_fold_provider());
+    /// ```
+    pub fn remove_fold_provider(&mut self) {
+        self.folder = None;
+    }
+
+    /// Return whether a provider of the ranges to fold is set. See [`MergeArea::set_fold_provider`].
+    pub fn has_fold_provider(&self) -> bool {
+        self.folder.is_some()
+    }
+
+    /// Set the style of the placeholder rendered after the header of a fold. The default style is dark gray.
+    /// ```
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::default();
+    ///
+    /// textarea.set_fold_style(Style::default().fg(Color::Blue));
+    /// assert_eq!(textarea.fold_style(), Style::default().fg(Color::Blue));
+    /// ```
+    pub fn set_fold_style(&mut self, style: Style) {
+        self.fold_style = style;
+    }
+
+    /// Get the style of the placeholder rendered after the header of a fold.
+    pub fn fold_style(&self) -> Style {
+        self.fold_style
+    }
+
+    // Folds rendered on the screen. The text viewed in the history is not folded
+    pub(crate) fn visible_folds(&self) -> Vec<Range<usize>> {
+        if self.scrubber.is_some() {
+            return vec![];
+        }
+        fold::merge(self.text.folds())
+    }
+
+    // Move the cursor out of the lines hidden by the folds. The cursor moving forward from the header of a fold steps
+    // over the fold to the next line. Otherwise it is put at the end of the header
+    fn step_over_folds(&self, prev: usize, next: usize, folds: &[Range<usize>]) -> usize {
+        let (row, _) = util::cursor_for(self.text.as_str(), next);
+        let Some(fold) = folds.iter().find(|f| f.start < row && row < f.end) else {
+            return next;
+        };
+        let lines: Vec<_> = self
+            .text
+            .as_str()
+            .split('\n')
+            .map(|l| l.chars().count())
+            .collect();
+        let line_start = |row: usize| lines[..row].iter().map(|len| len + 1).sum::<usize>();
+        let header_end = line_start(fold.start) + lines[fold.start];
+        if next > prev && prev <= header_end && fold.end < lines.len() {
+            line_start(fold.end)
+        } else {
+            header_end
+        }
+    }
+
+    // Text as of the `before` heads and the modifications of it until the `after` heads
+    pub(crate) fn diff(
+        &mut self,
+        before: &[ChangeHash],
+        after: &[ChangeHash],
+    ) -> Result<(String, Vec<Splice>), AutomergeError> {
+        self.text.diff(before, after)
+    }
+
+    // Identifies the text rendered by the widget so that values computed from it can be cached. While viewing the
+    // history, the viewed heads identify it
+    pub(crate) fn displayed_version(&self) -> (u64, Option<&[ChangeHash]>) {
+        (self.text.version(), self.scrubbed_heads())
+    }
+
+    // Text rendered by the widget. While viewing the history, it is the text as of the viewed heads
+    pub(crate) fn displayed_text(&self) -> &str {
+        match &self.scrubber {
+            Some(s) => s.text(),
+            None => self.text.as_str(),
+        }
+    }
+
+    /// Step the view of the history back to the previous change which modified the text. The first call starts
+    /// viewing the history of the attached document from the current state. While viewing the history, the widget
+    /// renders the text as of the viewed heads without the cursor and the selection. Key inputs and edits such as
+    /// [`MergeArea::insert_str`] and [`MergeArea::undo`] are ignored, while changes merged from other peers are added to
+    /// the viewed history. Call [`MergeArea::stop_scrubbing`] to return to live editing.
+    ///
+    /// This method returns `false` when there is no previous change or no document is attached.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{AutoCommit, ObjType, ROOT};
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// let mut textarea = MergeArea::from_doc(doc, obj).unwrap();
+    ///
+    /// textarea.insert_str("fox");
+    /// textarea.doc_mut().unwrap().commit();
+    /// textarea.move_cursor(CursorMove::Head);
+    /// textarea.insert_str("The ");
+    ///
+    /// assert!(textarea.scrub_back());
+    /// assert_eq!(textarea.scrubbed_text(), Some("fox"));
+    /// assert!(textarea.scrub_back());
+    /// assert_eq!(textarea.scrubbed_text(), Some(""));
+    /// assert!(!textarea.scrub_back());
+    ///
+    /// assert!(textarea.scrub_forward());
+    /// assert_eq!(textarea.scrubbed_text(), Some("fox"));
+    ///
+    /// textarea.stop_scrubbing();
+    /// assert_eq!(textarea.scrubbed_text(), None);
+    /// assert_eq!(textarea.text().as_str(), "The fox");
+    /// ```
+    pub fn scrub_back(&mut self) -> bool {
+        let started = self.scrubber.is_none();
+        if started {
+            self.scrubber = Scrubber::new(&mut self.text);
+        }
+        let Some(scrubber) = &mut self.scrubber else {
+            return false;
+        };
+        let moved = scrubber.back(&self.text);
+        if started && !moved {
+            self.scrubber = None;
+        }
+        moved
+    }
+
+    /// Step the view of the history forward to the next change which modified the text. This method returns `false`
+    /// when the viewed text is already the latest one or the history is not being viewed. Note that the view stays in
+    /// the history even after reaching the latest change until [`MergeArea::stop_scrubbing`] is called.
+    pub fn scrub_forward(&mut self) -> bool {
+        match &mut self.scrubber {
+            Some(scrubber) => scrubber.forward(&self.text),
+            None => false,
+        }
+    }
+
+    /// View the text as of the heads of the attached document. The heads can be obtained by
+    /// `AutoCommit::get_heads` at any point of the history. Stepping by [`MergeArea::scrub_back`] and
+    /// [`MergeArea::scrub_forward`] continues from the heads. An error is returned when some of the heads are not in
+    /// the document. When no document is attached, this method does nothing.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{AutoCommit, ObjType, ROOT};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "fox").unwrap();
+    /// let heads = doc.get_heads();
+    /// doc.splice_text(&obj, 0, 0, "The ").unwrap();
+    ///
+    /// let mut textarea = MergeArea::from_doc(doc, obj).unwrap();
+    /// textarea.scrub_to(&heads).unwrap();
+    /// assert_eq!(textarea.scrubbed_text(), Some("fox"));
+    /// assert_eq!(textarea.scrubbed_heads(), Some(&heads[..]));
+    /// ```
+    pub fn scrub_to(&mut self, heads: &[ChangeHash]) -> Result<(), AutomergeError> {
+        if self.scrubber.is_none() {
+            self.scrubber = Scrubber::new(&mut self.text);
         }
+        match &mut self.scrubber {
+            Some(scrubber) => scrubber.jump(&self.text, heads),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop viewing the history and return to live editing.
+    pub fn stop_scrubbing(&mut self) {
+        self.scrubber = None;
+    }
 
-        0
+    /// Get the heads of the document whose text is viewed. `None` is returned when the history is not being viewed.
+    pub fn scrubbed_heads(&self) -> Option<&[ChangeHash]> {
+        self.scrubber.as_ref().map(|s| s.heads())
+    }
+
+    /// Get the text as of the viewed heads. `None` is returned when the history is not being viewed.
+    pub fn scrubbed_text(&self) -> Option<&str> {
+        self.scrubber.as_ref().map(|s| s.text())
     }
 
     /// Set the style of textarea. By default, textarea is not styled.
@@ -1771,6 +3144,40 @@ impl<'a> MergeArea<'a> {
         self.mask
     }
 
+    /// Set how lines longer than the width of the textarea are rendered. By default, lines are not wrapped and the
+    /// textarea scrolls horizontally. When lines are wrapped, the line number is shown only in the first row of each
+    /// line, and [`CursorMove::Up`] and [`CursorMove::Down`] move the cursor by the rows on the screen. The rows are
+    /// computed from the size of the textarea on the last render.
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::widgets::Widget as _;
+    /// use ratatui_mergearea::{CursorMove, MergeArea, WrapMode};
+    ///
+    /// let mut textarea = MergeArea::with_value("hello world");
+    /// textarea.set_wrap_mode(WrapMode::Word);
+    /// assert_eq!(textarea.wrap_mode(), WrapMode::Word);
+    ///
+    /// let area = Rect::new(0, 0, 8, 2);
+    /// let mut buf = Buffer::empty(area);
+    /// textarea.render(area, &mut buf);
+    /// let row = |y| (0..8).map(|x| buf[(x, y)].symbol()).collect::<String>();
+    /// assert_eq!(row(0), "hello   ");
+    /// assert_eq!(row(1), "world   ");
+    ///
+    /// // The cursor moves to the next row on the screen in the same line
+    /// textarea.move_cursor(CursorMove::Down);
+    /// assert_eq!(textarea.cursor2(), (0, 6));
+    /// ```
+    pub fn set_wrap_mode(&mut self, mode: WrapMode) {
+        self.wrap_mode = mode;
+    }
+
+    /// Get how lines longer than the width of the textarea are rendered. The default value is [`WrapMode::None`].
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.wrap_mode
+    }
+
     /// Set the style of cursor. By default, a cursor is rendered in the reversed color. Setting the same style as
     /// cursor line hides a cursor.
     /// ```
@@ -1792,26 +3199,26 @@ impl<'a> MergeArea<'a> {
         self.cursor_style
     }
 
-    /// Get slice of line texts. This method borrows the content, but not moves. Note that the returned slice will
-    /// never be empty because an empty text means a slice containing one empty line. This is correct since any text
-    /// file must end with a newline.
+    /// Get an iterator over the lines of the text. Lines are split at `\n` and the newlines are not included. Note that
+    /// the iterator is never empty because an empty text has one empty line, and a text ending with a newline has an
+    /// empty last line.
     /// ```
     /// use ratatui_mergearea::MergeArea;
     ///
     /// let mut textarea = MergeArea::default();
-    /// assert_eq!(textarea.text().as_str(), "");
+    /// assert_eq!(textarea.lines().collect::<Vec<_>>(), [""]);
     ///
     /// textarea.insert_char('a');
-    /// assert_eq!(textarea.text().as_str(), "a");
+    /// assert_eq!(textarea.lines().collect::<Vec<_>>(), ["a"]);
     ///
     /// textarea.insert_newline();
-    /// assert_eq!(textarea.text().as_str(), "a\n");
+    /// assert_eq!(textarea.lines().collect::<Vec<_>>(), ["a", ""]);
     ///
     /// textarea.insert_char('b');
-    /// assert_eq!(textarea.text().as_str(), "a\nb");
+    /// assert_eq!(textarea.lines().collect::<Vec<_>>(), ["a", "b"]);
     /// ```
-    pub fn lines(&'a self) -> &'a [String] {
-        &self.lines
+    pub fn lines(&self) -> impl Iterator<Item = &str> + '_ {
+        self.text.as_str().split('\n')
     }
 
     /// Get the current cursor position. 0-base character-wise (row, col) cursor position.
@@ -1831,7 +3238,7 @@ impl<'a> MergeArea<'a> {
         let mut row = 0;
         let mut col = 0;
 
-        for c in self.text.as_str().chars().take(self.cursor_v2) {
+        for c in self.text.as_str().chars().take(self.cursor()) {
             match c {
                 '\n' => {
                     row += 1;
@@ -1846,8 +3253,38 @@ impl<'a> MergeArea<'a> {
         (row, col)
     }
 
+    /// Get the current cursor position as a char offset in the text. When an automerge document is attached, the
+    /// cursor and the start of the selection are kept as [`automerge::Cursor`]s of the document, so they stay at the
+    /// same chars through merges and modifications made through [`MergeArea::doc_mut`].
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "fox jumped").unwrap();
+    ///
+    /// let mut textarea = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    /// textarea.move_cursor(CursorMove::WordForward); // At 'j'
+    ///
+    /// // Edit a fork of the document and merge it
+    /// let mut fork = textarea.doc_mut().unwrap().fork().with_actor(ActorId::random());
+    /// fork.splice_text(&obj, 0, 0, "The quick ").unwrap();
+    /// textarea.merge(&mut fork).unwrap();
+    ///
+    /// assert_eq!(textarea.cursor(), 14); // Still at 'j'
+    /// ```
     pub fn cursor(&self) -> usize {
-        self.cursor_v2
+        self.text.offset(&self.cursor)
+    }
+
+    fn set_cursor(&mut self, offset: usize) {
+        self.cursor = self.text.position(offset);
+    }
+
+    fn selection_start(&self) -> Option<usize> {
+        self.selection_start.as_ref().map(|p| self.text.offset(p))
     }
 
     fn char_index(&self, nth: usize) -> usize {
@@ -1862,11 +3299,11 @@ impl<'a> MergeArea<'a> {
     /// inclusively below and exclusively above. The positions are 0-base character-wise (row, col) values.
     /// The first element of the pair is always smaller than the second one even when it is ahead of the cursor.
     /// When no text is selected, this method returns `None`.
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::MergeArea;
     /// use ratatui_mergearea::CursorMove;
     ///
-    /// let mut textarea = MergeArea::with_value("aaa\nbbb\nccc")
+    /// let mut textarea = MergeArea::with_value("aaa\nbbb\nccc");
     ///
     /// // It returns `None` when the text selection is not ongoing
     /// assert_eq!(textarea.selection_range2(), None);
@@ -1897,14 +3334,37 @@ impl<'a> MergeArea<'a> {
         })
     }
 
+    /// Get the range of the text selection as char offsets `(start, end)` in the text. The start is always smaller
+    /// than or equal to the end. `None` is returned when the text selection is not ongoing. When an automerge document
+    /// is attached, the range follows the chars it selects through merges as [`MergeArea::cursor`] does.
+    /// ```
+    /// use automerge::transaction::Transactable as _;
+    /// use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+    /// use ratatui_mergearea::{CursorMove, MergeArea};
+    ///
+    /// let mut doc = AutoCommit::new();
+    /// let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    /// doc.splice_text(&obj, 0, 0, "fox jumped").unwrap();
+    ///
+    /// let mut textarea = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    /// assert_eq!(textarea.selection_range(), None);
+    ///
+    /// textarea.move_cursor(CursorMove::End);
+    /// textarea.start_selection();
+    /// textarea.move_cursor(CursorMove::WordBack); // Select "jumped"
+    /// assert_eq!(textarea.selection_range(), Some((4, 10)));
+    ///
+    /// // Edit a fork of the document and merge it
+    /// let mut fork = textarea.doc_mut().unwrap().fork().with_actor(ActorId::random());
+    /// fork.splice_text(&obj, 0, 0, "The quick ").unwrap();
+    /// textarea.merge(&mut fork).unwrap();
+    ///
+    /// assert_eq!(textarea.selection_range(), Some((14, 20))); // Still "jumped"
+    /// ```
     pub fn selection_range(&self) -> Option<(usize, usize)> {
-        self.selection_start_v2.map(|off| {
-            if off > self.cursor_v2 {
-                (self.cursor_v2, off)
-            } else {
-                (off, self.cursor_v2)
-            }
-        })
+        let start = self.selection_start()?;
+        let cursor = self.cursor();
+        Some((cmp::min(start, cursor), cmp::max(start, cursor)))
     }
 
     /// Set text alignment. When [`Alignment::Center`] or [`Alignment::Right`] is set, line number is automatically
@@ -1956,7 +3416,7 @@ impl<'a> MergeArea<'a> {
     /// [`MergeArea::delete_line_by_end`], [`MergeArea::delete_word`], [`MergeArea::delete_next_word`],
     /// [`MergeArea::delete_str`], [`MergeArea::copy`], and [`MergeArea::cut`]. When multiple lines were yanked, they are
     /// always joined with `\n`.
-    /// ```ignore
+    /// ```
     /// use ratatui_mergearea::MergeArea;
     ///
     /// let mut textarea = MergeArea::with_value("abc");
@@ -1970,12 +3430,12 @@ impl<'a> MergeArea<'a> {
     /// ```
     #[doc(hidden)]
     pub fn yank_text(&self) -> String {
-        self.yank.to_string()
+        self.yank.clone()
     }
 
     /// Set a yanked text. The text can be inserted by [`MergeArea::paste`]. `\n` and `\r\n` are recognized as newline
-    /// but `\r` isn't.
-    /// ```ignore
+    /// but `\r` isn't. `\r\n` is pasted as `\n`.
+    /// ```
     /// use ratatui_mergearea::MergeArea;
     ///
     /// let mut textarea = MergeArea::default();
@@ -1986,37 +3446,37 @@ impl<'a> MergeArea<'a> {
     /// ```
     #[doc(hidden)]
     pub fn set_yank_text(&mut self, text: impl Into<String>) {
-        // `str::lines` is not available since it strips a newline at end
-        let lines: Vec<_> = text
-            .into()
-            .split('\n')
-            .map(|s| s.strip_suffix('\r').unwrap_or(s).to_string())
-            .collect();
-        self.yank = lines.into();
+        self.yank = text.into().replace("\r\n", "\n");
     }
 
-    /// Set a regular expression pattern for text search. Setting an empty string stops the text search.
+    /// Set a regular expression pattern for text search. Setting an empty string stops the text search. The pattern is
+    /// compiled with the options set by [`MergeArea::set_search_options`].
     /// When a valid pattern is set, all matches will be highlighted in the textarea. Note that the cursor does not
     /// move. To move the cursor, use [`MergeArea::search_forward`] and [`MergeArea::search_back`].
     ///
-    /// Grammar of regular expression follows [regex crate](https://docs.rs/regex/latest/regex). Patterns don't match
-    /// to newlines so match passes across no newline.
+    /// Grammar of regular expression follows [regex crate](https://docs.rs/regex/latest/regex). The pattern is matched
+    /// against the whole text, so a match can span newlines when the pattern contains them (e.g. `\n` or `\s`).
     ///
     /// When the pattern is invalid, the search pattern will not be updated and an error will be returned.
     ///
     /// ```
     /// use ratatui_mergearea::MergeArea;
     ///
-    /// let mut textarea = MergeArea::from(["hello, world", "goodbye, world"]);
+    /// let mut textarea = MergeArea::with_value("hello, world\ngoodbye, world");
     ///
     /// // Search "world"
     /// textarea.set_search_pattern("world").unwrap();
     ///
-    /// assert_eq!(textarea.cursor(), (0, 0));
+    /// assert_eq!(textarea.cursor2(), (0, 0));
     /// textarea.search_forward(false);
-    /// assert_eq!(textarea.cursor(), (0, 7));
+    /// assert_eq!(textarea.cursor2(), (0, 7));
     /// textarea.search_forward(false);
-    /// assert_eq!(textarea.cursor(), (1, 9));
+    /// assert_eq!(textarea.cursor2(), (1, 9));
+    ///
+    /// // Match across lines
+    /// textarea.set_search_pattern(r"world\ngood").unwrap();
+    /// textarea.search_back(false);
+    /// assert_eq!(textarea.cursor2(), (0, 7));
     ///
     /// // Stop the text search
     /// textarea.set_search_pattern("");
@@ -2031,7 +3491,7 @@ impl<'a> MergeArea<'a> {
     }
 
     /// Get a regular expression which was set by [`MergeArea::set_search_pattern`]. When no text search is ongoing, this
-    /// method returns `None`.
+    /// method returns `None`. The expression is compiled with the [`SearchOptions`], so it may differ from the query.
     ///
     /// ```
     /// use ratatui_mergearea::MergeArea;
@@ -2049,6 +3509,47 @@ impl<'a> MergeArea<'a> {
         self.search.pat.as_ref()
     }
 
+    /// Set options to compile the query of text search. The pattern set by [`MergeArea::set_search_pattern`] is
+    /// compiled again with the new options. See [`SearchOptions`] for the options. The default options compile the
+    /// query into a regular expression as is.
+    ///
+    /// When the pattern is invalid with the new options, nothing is updated and an error will be returned.
+    ///
+    /// ```
+    /// use ratatui_mergearea::{MergeArea, SearchOptions};
+    ///
+    /// let mut textarea = MergeArea::with_value("Foo(1) foo(2)");
+    ///
+    /// // Parentheses are matched literally
+    /// textarea.set_search_options(SearchOptions::default().literal(true)).unwrap();
+    /// textarea.set_search_pattern("foo(").unwrap();
+    /// assert!(textarea.search_forward(true));
+    /// assert_eq!(textarea.cursor2(), (0, 7));
+    ///
+    /// // The case is ignored since the query has no uppercase letter
+    /// let opts = SearchOptions::default().literal(true).smart_case(true);
+    /// textarea.set_search_options(opts).unwrap();
+    /// assert_eq!(textarea.search_match_count(), 2);
+    /// textarea.set_search_pattern("Foo(").unwrap();
+    /// assert_eq!(textarea.search_match_count(), 1);
+    ///
+    /// // "Foo(" is not a valid regular expression
+    /// assert!(textarea.set_search_options(SearchOptions::default()).is_err());
+    /// assert_eq!(textarea.search_options(), opts);
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_search_options(&mut self, options: SearchOptions) -> Result<(), regex::Error> {
+        self.search.set_options(options)
+    }
+
+    /// Get the options set by [`MergeArea::set_search_options`].
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn search_options(&self) -> SearchOptions {
+        self.search.options
+    }
+
     /// Search the pattern set by [`MergeArea::set_search_pattern`] forward and move the cursor to the next match
     /// position based on the current cursor position. Text search wraps around a text buffer. It returns `true` when
     /// some match was found. Otherwise it returns `false`.
@@ -2060,26 +3561,26 @@ impl<'a> MergeArea<'a> {
     /// ```
     /// use ratatui_mergearea::MergeArea;
     ///
-    /// let mut textarea = MergeArea::from(["hello", "helloo", "hellooo"]);
+    /// let mut textarea = MergeArea::with_value("hello\nhelloo\nhellooo");
     ///
     /// textarea.set_search_pattern("hello+").unwrap();
     ///
     /// // Move to next position
     /// let match_found = textarea.search_forward(false);
     /// assert!(match_found);
-    /// assert_eq!(textarea.cursor(), (1, 0));
+    /// assert_eq!(textarea.cursor2(), (1, 0));
     ///
     /// // Since the cursor position matches to "hello+", it does not move
     /// textarea.search_forward(true);
-    /// assert_eq!(textarea.cursor(), (1, 0));
+    /// assert_eq!(textarea.cursor2(), (1, 0));
     ///
     /// // When `match_current` parameter is set to `false`, match at the cursor position is ignored
     /// textarea.search_forward(false);
-    /// assert_eq!(textarea.cursor(), (2, 0));
+    /// assert_eq!(textarea.cursor2(), (2, 0));
     ///
     /// // Text search wrap around the buffer
     /// textarea.search_forward(false);
-    /// assert_eq!(textarea.cursor(), (0, 0));
+    /// assert_eq!(textarea.cursor2(), (0, 0));
     ///
     /// // `false` is returned when no match was found
     /// textarea.set_search_pattern("bye+").unwrap();
@@ -2089,8 +3590,11 @@ impl<'a> MergeArea<'a> {
     #[cfg(feature = "search")]
     #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
     pub fn search_forward(&mut self, match_cursor: bool) -> bool {
-        if let Some(cursor) = self.search.forward(&self.lines, self.cursor, match_cursor) {
-            self.cursor = cursor;
+        if let Some(offset) = self
+            .search
+            .forward(self.text.as_str(), self.cursor(), match_cursor)
+        {
+            self.set_cursor(offset);
             true
         } else {
             false
@@ -2133,14 +3637,149 @@ impl<'a> MergeArea<'a> {
     #[cfg(feature = "search")]
     #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
     pub fn search_back(&mut self, match_cursor: bool) -> bool {
-        if let Some(cursor) = self.search.back(&self.lines, self.cursor, match_cursor) {
-            self.cursor = cursor;
+        if let Some(offset) = self
+            .search
+            .back(self.text.as_str(), self.cursor(), match_cursor)
+        {
+            self.set_cursor(offset);
             true
         } else {
             false
         }
     }
 
+    /// Replace the next match of the pattern set by [`MergeArea::set_search_pattern`] with `replacement` and move the
+    /// cursor to the end of the replaced text. The match at the cursor position is also replaced, and text search
+    /// wraps around a text buffer. Capture groups such as `$1` and `${name}` in the replacement are expanded as
+    /// [`regex::Captures::expand`] does. The replacement is undone by a single [`MergeArea::undo`] call. It returns
+    /// `true` when some match was replaced.
+    ///
+    /// ```
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::with_value("foo = 1\nbar = 2");
+    /// textarea.set_search_pattern(r"(\w+) = (\d)").unwrap();
+    ///
+    /// assert!(textarea.replace_next("$1: $2"));
+    /// assert_eq!(textarea.text().as_str(), "foo: 1\nbar = 2");
+    /// assert_eq!(textarea.cursor2(), (0, 6));
+    ///
+    /// assert!(textarea.replace_next("$1: $2"));
+    /// assert_eq!(textarea.text().as_str(), "foo: 1\nbar: 2");
+    ///
+    /// textarea.undo();
+    /// assert_eq!(textarea.text().as_str(), "foo: 1\nbar = 2");
+    ///
+    /// // `false` is returned when no match was found
+    /// textarea.set_search_pattern("baz").unwrap();
+    /// assert!(!textarea.replace_next("qux"));
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn replace_next(&mut self, replacement: impl AsRef<str>) -> bool {
+        if self.scrubber.is_some() {
+            return false;
+        }
+        // Matches are searched in the text modified through `MergeArea::doc_mut`
+        self.text.catch_up();
+        self.follow_caught_up();
+        let text = self.text.as_str();
+        let Some((start, end, replaced)) =
+            self.search
+                .replacement(text, self.cursor(), replacement.as_ref())
+        else {
+            return false;
+        };
+        let len = replaced.chars().count();
+        self.replace_ranges(vec![(start, end, replaced)]);
+        self.set_cursor(start + len);
+        true
+    }
+
+    /// Replace all matches of the pattern set by [`MergeArea::set_search_pattern`] with `replacement`. Capture groups
+    /// in the replacement are expanded as [`MergeArea::replace_next`] does. All replacements are undone by a single
+    /// [`MergeArea::undo`] call. The cursor stays at the same text, or moves to the head of the replaced text when it
+    /// was in a match. It returns the number of replaced matches.
+    ///
+    /// ```
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::with_value("foo = 1\nbar = 2");
+    /// textarea.set_search_pattern(r"(\w+) = (\d)").unwrap();
+    ///
+    /// assert_eq!(textarea.replace_all("$2 -> $1"), 2);
+    /// assert_eq!(textarea.text().as_str(), "1 -> foo\n2 -> bar");
+    ///
+    /// textarea.undo();
+    /// assert_eq!(textarea.text().as_str(), "foo = 1\nbar = 2");
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn replace_all(&mut self, replacement: impl AsRef<str>) -> usize {
+        if self.scrubber.is_some() {
+            return 0;
+        }
+        self.text.catch_up();
+        self.follow_caught_up();
+        let ranges = self
+            .search
+            .replacements(self.text.as_str(), replacement.as_ref());
+        let count = ranges.len();
+
+        // Shift the cursor by the replacements before it
+        let current = self.cursor();
+        let mut cursor = current;
+        for (start, end, replaced) in &ranges {
+            if current < *start {
+                break;
+            }
+            if current < *end {
+                cursor -= current - start;
+                break;
+            }
+            cursor = cursor + replaced.chars().count() - (end - start);
+        }
+
+        self.replace_ranges(ranges);
+        self.set_cursor(cursor);
+        count
+    }
+
+    // Replace the ranges of char offsets with the strings as one history item. Ranges must be sorted and must not
+    // overlap. They are replaced from the last one so that the offsets of the rest are not shifted.
+    #[cfg(feature = "search")]
+    fn replace_ranges(&mut self, ranges: Vec<(usize, usize, String)>) {
+        self.cancel_selection();
+        let mut edits = vec![];
+        for (start, end, replaced) in ranges.into_iter().rev() {
+            let deleted: String = self
+                .text
+                .as_str()
+                .chars()
+                .skip(start)
+                .take(end - start)
+                .collect();
+            if deleted == replaced {
+                continue;
+            }
+            if !deleted.is_empty() {
+                edits.push(Edit::new(
+                    EditKind::DeleteStr(deleted),
+                    start,
+                    &mut self.text,
+                ));
+            }
+            if !replaced.is_empty() {
+                edits.push(Edit::new(
+                    EditKind::InsertStr(replaced),
+                    start,
+                    &mut self.text,
+                ));
+            }
+        }
+        self.history.push_group(edits);
+    }
+
     /// Get the text style at matches of text search. The default style is colored with blue in background.
     ///
     /// ```
@@ -2176,11 +3815,164 @@ impl<'a> MergeArea<'a> {
         self.search.style = style;
     }
 
+    /// Get the text style at the match of text search starting at the cursor position. The default style is colored
+    /// with yellow in background.
+    ///
+    /// ```
+    /// use ratatui::style::{Style, Color};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let textarea = MergeArea::default();
+    ///
+    /// assert_eq!(textarea.search_current_style(), Style::default().bg(Color::Yellow));
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn search_current_style(&self) -> Style {
+        self.search.current_style
+    }
+
+    /// Set the text style at the match of text search starting at the cursor position. It distinguishes the match the
+    /// cursor moved to by [`MergeArea::search_forward`] or by the incremental search from the other matches.
+    ///
+    /// ```
+    /// use ratatui::style::{Style, Color};
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::default();
+    ///
+    /// let green_bg = Style::default().bg(Color::Green);
+    /// textarea.set_search_current_style(green_bg);
+    ///
+    /// assert_eq!(textarea.search_current_style(), green_bg);
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn set_search_current_style(&mut self, style: Style) {
+        self.search.current_style = style;
+    }
+
+    /// Get the number of matches of the pattern set by [`MergeArea::set_search_pattern`] in the text. It returns 0 when
+    /// no text search is ongoing.
+    ///
+    /// ```
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::with_value("foo\nbar\nfoo");
+    /// assert_eq!(textarea.search_match_count(), 0);
+    ///
+    /// textarea.set_search_pattern("fo+").unwrap();
+    /// assert_eq!(textarea.search_match_count(), 2);
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn search_match_count(&self) -> usize {
+        self.cached_search_matches().len()
+    }
+
+    // Matches are searched again only when the text or the pattern changed
+    #[cfg(feature = "search")]
+    fn cached_search_matches(&self) -> std::sync::Arc<[(usize, usize)]> {
+        self.search
+            .cached_matches(self.text.as_str(), self.text.version())
+    }
+
+    /// Get the matches of the pattern set by [`MergeArea::set_search_pattern`] in the whole text. Each match is a pair
+    /// of the char offsets of its start and its end (exclusive) in order of the position. Matches are cached until the
+    /// text or the pattern is modified, so this method is cheap enough to call on every rendering.
+    ///
+    /// ```
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::with_value("foo\nbar\nfoo");
+    /// textarea.set_search_pattern("o+\n?").unwrap();
+    ///
+    /// let matches: Vec<_> = textarea.search_matches().collect();
+    /// assert_eq!(matches, [(1, 4), (9, 11)]);
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn search_matches(&self) -> impl Iterator<Item = (usize, usize)> + use<> {
+        let matches = self.cached_search_matches();
+        (0..matches.len()).map(move |i| matches[i])
+    }
+
+    /// Get the 1-based index of the match containing the cursor and the number of all matches, such as `(3, 17)` for
+    /// "3 of 17" in a status bar. It returns `None` when the cursor is not in any match.
+    ///
+    /// ```
+    /// use ratatui_mergearea::MergeArea;
+    ///
+    /// let mut textarea = MergeArea::with_value("foo bar foo baz foo");
+    /// textarea.set_search_pattern("foo").unwrap();
+    /// assert_eq!(textarea.current_match_index(), Some((1, 3)));
+    ///
+    /// textarea.search_forward(false);
+    /// assert_eq!(textarea.current_match_index(), Some((2, 3)));
+    ///
+    /// textarea.move_cursor(ratatui_mergearea::CursorMove::End);
+    /// assert_eq!(textarea.current_match_index(), None);
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn current_match_index(&self) -> Option<(usize, usize)> {
+        let matches = self.cached_search_matches();
+        let i = search::match_at(&matches, self.cursor())?;
+        Some((i + 1, matches.len()))
+    }
+
+    /// Get the query of the incremental search started by `Ctrl+S` in [`MergeArea::input_emacs`]. It returns `None`
+    /// when no incremental search is ongoing. Host applications can render it in a minibuffer with
+    /// [`MergeArea::is_isearch_backward`] and [`MergeArea::search_match_count`].
+    ///
+    /// While the incremental search is ongoing, typed chars are appended to the query and the cursor moves to the
+    /// match of the query. The query is matched literally. `Ctrl+S` and `Ctrl+R` move to the next and the previous
+    /// matches. `Enter` accepts the cursor position and `Esc` or `Ctrl+G` moves the cursor back to where the search
+    /// started. Other keys accept the cursor position and then they are handled as usual.
+    ///
+    /// ```
+    /// use ratatui_mergearea::{Input, Key, MergeArea};
+    ///
+    /// let mut textarea = MergeArea::with_value("foo bar\nfoo baz");
+    /// let ctrl = |c| Input { key: Key::Char(c), ctrl: true, ..Default::default() };
+    /// let key = |key| Input { key, ..Default::default() };
+    ///
+    /// textarea.input_emacs(ctrl('s'));
+    /// assert_eq!(textarea.isearch_query(), Some(""));
+    ///
+    /// textarea.input_emacs(key(Key::Char('b')));
+    /// textarea.input_emacs(key(Key::Char('a')));
+    /// assert_eq!(textarea.isearch_query(), Some("ba"));
+    /// assert_eq!(textarea.search_match_count(), 2);
+    /// assert_eq!(textarea.cursor2(), (0, 4));
+    ///
+    /// textarea.input_emacs(ctrl('s'));
+    /// assert_eq!(textarea.cursor2(), (1, 4));
+    ///
+    /// // Accept the position
+    /// textarea.input_emacs(key(Key::Enter));
+    /// assert_eq!(textarea.isearch_query(), None);
+    /// assert_eq!(textarea.cursor2(), (1, 4));
+    /// ```
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn isearch_query(&self) -> Option<&str> {
+        self.isearch.as_ref().map(|i| i.query.as_str())
+    }
+
+    /// Return if the incremental search is searching backward. `Ctrl+R` during the incremental search turns it
+    /// backward and `Ctrl+S` turns it forward. It returns `false` when no incremental search is ongoing.
+    #[cfg(feature = "search")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "search")))]
+    pub fn is_isearch_backward(&self) -> bool {
+        self.isearch.as_ref().is_some_and(|i| i.backward)
+    }
+
     /// Scroll the textarea. See [`Scrolling`] for the argument.
     /// The cursor will not move until it goes out the viewport. When the cursor position is outside the viewport after scroll,
     /// the cursor position will be adjusted to stay in the viewport using the same logic as [`CursorMove::InViewport`].
     ///
-    /// ```ignore
+    /// ```
     /// # use ratatui::buffer::Buffer;
     /// # use ratatui::layout::Rect;
     /// # use ratatui::widgets::Widget as _;
@@ -2189,7 +3981,8 @@ impl<'a> MergeArea<'a> {
     /// // Let's say terminal height is 8.
     ///
     /// // Create textarea with 20 lines "0", "1", "2", "3", ...
-    /// let mut textarea = MergeArea::with_value((0..20).into_iter().map(|i| i.to_string()).collect());
+    /// let text = (0..20).map(|i| i.to_string()).collect::<Vec<_>>().join("\n");
+    /// let mut textarea = MergeArea::with_value(text);
     /// # // Call `render` at least once to populate terminal size
     /// # let r = Rect { x: 0, y: 0, width: 24, height: 8 };
     /// # let mut b = Buffer::empty(r.clone());
@@ -2215,12 +4008,17 @@ impl<'a> MergeArea<'a> {
     /// assert_eq!(textarea.cursor2(), (12, 0));
     /// ```
     pub fn scroll(&mut self, scrolling: impl Into<Scrolling>) {
-        self.scroll_with_shift_v2(scrolling.into(), self.selection_start_v2.is_some());
+        self.scroll_with_shift(scrolling.into(), self.selection_start.is_some());
     }
 
-    fn scroll_with_shift_v2(&mut self, scrolling: Scrolling, shift: bool) {
-        if shift && self.selection_start_v2.is_none() {
-            self.selection_start_v2 = Some(self.cursor_v2);
+    fn scroll_with_shift(&mut self, scrolling: Scrolling, shift: bool) {
+        if self.scrubber.is_some() {
+            // Only the view of the history is scrolled
+            scrolling.scroll(&mut self.viewport);
+            return;
+        }
+        if shift && self.selection_start.is_none() {
+            self.start_selection();
         }
         scrolling.scroll(&mut self.viewport);
         self.move_cursor_with_shift(CursorMove::InViewport, shift);
@@ -1,3 +1,7 @@
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
 pub fn spaces(size: u8) -> &'static str {
     const SPACES: &str = "                                                                                                                                                                                                                                                                ";
     &SPACES[..size as usize]
@@ -7,19 +11,6 @@ pub fn num_digits(i: usize) -> u8 {
     f64::log10(i as f64) as u8 + 1
 }
 
-#[derive(Debug, Clone)]
-pub struct Pos {
-    pub row: usize,
-    pub col: usize,
-    pub offset: usize,
-}
-
-impl Pos {
-    pub fn new(row: usize, col: usize, offset: usize) -> Self {
-        Self { row, col, offset }
-    }
-}
-
 pub fn find_line_start(offset: usize, chars: &[char]) -> usize {
     let mut current_line_start = offset;
     while current_line_start > 0 && chars[current_line_start - 1] != '\n' {
@@ -33,7 +24,7 @@ pub fn find_line_end(offset: usize, chars: &[char]) -> usize {
     while line_end < chars.len() && chars[line_end] != '\n' {
         line_end += 1;
     }
-    line_end - 1
+    line_end
 }
 
 pub fn cursor_for(text: &str, offset: usize) -> (usize, usize) {
@@ -54,3 +45,32 @@ pub fn cursor_for(text: &str, offset: usize) -> (usize, usize) {
 
     (row, col)
 }
+
+// Callback given by the host application, such as a highlighter. It is shared so that the textarea can be cloned.
+pub struct SharedFn<T: ?Sized>(Arc<T>);
+
+impl<T: ?Sized> SharedFn<T> {
+    pub fn new(f: Arc<T>) -> Self {
+        Self(f)
+    }
+}
+
+impl<T: ?Sized> Clone for SharedFn<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for SharedFn<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedFn(..)")
+    }
+}
+
+impl<T: ?Sized> Deref for SharedFn<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
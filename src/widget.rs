@@ -4,8 +4,12 @@ use crate::ratatui::text::{Span, Text};
 use crate::ratatui::widgets::{Paragraph, Widget};
 use crate::textarea::MergeArea;
 use crate::util::num_digits;
+use crate::wrap::{self, ScreenRows, VisualRow, Wrapper};
+use automerge::ChangeHash;
 use ratatui::text::Line;
 use std::cmp;
+use std::ops::Range;
+use std::sync::Mutex;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 // &mut 'a (u16, u16, u16, u16) is not available since `render` method takes immutable reference of MergeArea
@@ -77,6 +81,34 @@ impl Viewport {
     }
 }
 
+// Rows of the displayed text on the screen. Wrapping and folding the whole text on every render is expensive, so the
+// rows are kept until the text, the wrapping or the folds change. The cache is filled while rendering, so it is behind
+// a mutex to keep the textarea `Sync`.
+#[derive(Debug, Default)]
+pub struct RowCache(Mutex<Option<CachedRows>>);
+
+impl Clone for RowCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().unwrap().clone()))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CachedRows {
+    // Version of the text and the heads viewed in the history
+    version: (u64, Option<Vec<ChangeHash>>),
+    lines: usize,
+    wrapper: Wrapper,
+    folds: Vec<Range<usize>>,
+    rows: ScreenRows,
+}
+
+impl CachedRows {
+    fn is_of(&self, (version, heads): (u64, Option<&[ChangeHash]>)) -> bool {
+        self.version.0 == version && self.version.1.as_deref() == heads
+    }
+}
+
 #[inline]
 fn next_scroll_top(prev_top: u16, cursor: u16, len: u16) -> u16 {
     if cursor < prev_top {
@@ -89,15 +121,87 @@ fn next_scroll_top(prev_top: u16, cursor: u16, len: u16) -> u16 {
 }
 
 impl<'a> MergeArea<'a> {
-    fn text_widget(&'a self, top_row: usize, height: usize) -> Text<'a> {
-        let text_lines = self.text().as_str().split("\n").collect::<Vec<&str>>();
+    // Width of the line numbers and the sign column on the left of the text
+    fn gutter_width(&self, lines: usize) -> u16 {
+        if self.line_number_style().is_none() {
+            return 0;
+        }
+        let signs = self.has_sign_column() as u16;
+        num_digits(lines) as u16 + 2 + signs // `+ 2` for margins
+    }
+
+    // Number of the lines of the displayed text
+    fn displayed_lines(&self) -> usize {
+        match &*self.rows.0.lock().unwrap() {
+            Some(c) if c.is_of(self.displayed_version()) => c.lines,
+            _ => self.displayed_text().split('\n').count(),
+        }
+    }
+
+    // Settings to wrap the lines in the viewport of the width. The line numbers are not counted in the width of text
+    pub(crate) fn wrapper(&self, width: u16) -> Wrapper {
+        let lines = self.displayed_lines();
+        let width = (width as usize).saturating_sub(self.gutter_width(lines) as usize);
+        Wrapper {
+            mode: self.wrap_mode(),
+            width,
+            tab_len: self.tab_length(),
+            mask: self.mask_char(),
+        }
+    }
+
+    // Rows of the displayed text on the screen
+    fn screen_rows(&self, wrapper: Wrapper) -> ScreenRows {
+        let version = self.displayed_version();
+        let folds = self.visible_folds();
+        let mut cache = self.rows.0.lock().unwrap();
+        match &*cache {
+            Some(c) if c.is_of(version) && c.wrapper == wrapper && c.folds == folds => {
+                return c.rows.clone();
+            }
+            _ => {}
+        }
+
+        let text = self.displayed_text();
+        let rows = ScreenRows::new(text, &wrapper, &folds);
+        *cache = Some(CachedRows {
+            version: (version.0, version.1.map(<[_]>::to_vec)),
+            lines: text.split('\n').count(),
+            wrapper,
+            folds,
+            rows: rows.clone(),
+        });
+        rows
+    }
+
+    // Rows of the text which the cursor moves over. While viewing the history, the rows on the screen are of another
+    // text, so the rows of the text are not cached
+    pub(crate) fn cursor_rows(&self, wrapper: Wrapper) -> ScreenRows {
+        if self.scrubbed_text().is_some() {
+            return ScreenRows::new(self.text().as_str(), &wrapper, &[]);
+        }
+        self.screen_rows(wrapper)
+    }
 
-        let lines_len = text_lines.len();
+    fn text_widget(&'a self, rows: &ScreenRows, top_row: usize, height: usize) -> Text<'a> {
+        let ScreenRows { rows, bytes } = rows;
+        let text = self.displayed_text();
+        let lines_len = rows
+            .last()
+            .map_or(1, |r| r.row + 1 + r.folded.map_or(0, |(n, _)| n));
         let lnum_len = num_digits(lines_len);
-        let bottom_row = cmp::min(top_row + height, lines_len);
-        let mut lines = Vec::with_capacity(bottom_row - top_row);
-        for (i, line) in text_lines[top_row..bottom_row].iter().enumerate() {
-            lines.push(self.line_spans(line, top_row + i, lnum_len));
+        let bottom_row = cmp::min(top_row + height, rows.len());
+        let visible = &rows[top_row..bottom_row];
+
+        // Decorations are looked up only for the visible rows. The last row is counted with its hidden lines and newline
+        let start = visible.first().map_or(0, |r| r.start);
+        let end = visible.last().map_or(start, |r| r.fold_end() + 1);
+        let deco = self.decorations(start, end);
+
+        let mut lines = Vec::with_capacity(visible.len());
+        for (row, bytes) in visible.iter().zip(&bytes[top_row..bottom_row]) {
+            let line = &text[bytes.clone()];
+            lines.push(self.line_spans(line, row, lnum_len, &deco));
         }
 
         Text::from(lines)
@@ -109,15 +213,27 @@ impl<'a> MergeArea<'a> {
         Text::from(Line::from(vec![cursor, text]))
     }
 
-    fn scroll_top_row(&self, prev_top: u16, height: u16) -> u16 {
-        next_scroll_top(prev_top, self.cursor2().0 as u16, height)
+    // The top row is counted in the rows on the screen, which are the lines of the text unless they are wrapped
+    fn scroll_top_row(&self, rows: &[VisualRow], prev_top: u16, height: u16) -> u16 {
+        if self.scrubbed_text().is_some() {
+            // No cursor to follow while viewing the history
+            return cmp::min(prev_top, (rows.len() as u16).saturating_sub(height));
+        }
+        let cursor = wrap::row_index(rows, self.cursor()) as u16;
+        next_scroll_top(prev_top, cursor, height)
     }
 
-    fn scroll_top_col(&self, prev_top: u16, width: u16) -> u16 {
+    fn scroll_top_col(&self, prev_top: u16, width: u16, wrapper: &Wrapper) -> u16 {
+        if wrapper.wraps() {
+            return 0;
+        }
+        if self.scrubbed_text().is_some() {
+            return prev_top;
+        }
         let mut cursor = self.cursor2().1 as u16;
         // Adjust the cursor position due to the width of line number.
         if self.line_number_style().is_some() {
-            let lnum = num_digits(self.lines().len()) as u16 + 2; // `+ 2` for margins
+            let lnum = self.gutter_width(self.displayed_lines());
             if cursor <= lnum {
                 cursor *= 2; // Smoothly slide the line number into the screen on scrolling left
             } else {
@@ -136,14 +252,20 @@ impl Widget for &MergeArea<'_> {
             area
         };
 
+        let wrapper = self.wrapper(width);
+        let rows = self.screen_rows(wrapper);
+
         let (top_row, top_col) = self.viewport.scroll_top();
-        let top_row = self.scroll_top_row(top_row, height);
-        let top_col = self.scroll_top_col(top_col, width);
+        let top_row = self.scroll_top_row(&rows.rows, top_row, height);
+        let top_col = self.scroll_top_col(top_col, width, &wrapper);
 
-        let (text, style) = if !self.placeholder.is_empty() && self.is_empty() {
+        let (text, style) = if !self.placeholder.is_empty() && self.displayed_text().is_empty() {
             (self.placeholder_widget(), self.placeholder_style)
         } else {
-            (self.text_widget(top_row as _, height as _), self.style())
+            (
+                self.text_widget(&rows, top_row as _, height as _),
+                self.style(),
+            )
         };
 
         // To get fine control over the text color and the surrrounding block they have to be rendered separately
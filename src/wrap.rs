@@ -0,0 +1,316 @@
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::Arc;
+use unicode_width::UnicodeWidthChar as _;
+
+/// Specify how lines longer than the width of the textarea are rendered. Set it with
+/// [`crate::MergeArea::set_wrap_mode`].
+///
+/// When lines are wrapped, the textarea does not scroll horizontally. [`crate::CursorMove::Up`] and
+/// [`crate::CursorMove::Down`] move the cursor by the rows on the screen instead of the lines of the text.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WrapMode {
+    /// Lines are not wrapped. The textarea scrolls horizontally to follow the cursor. This is the default.
+    #[default]
+    None,
+    /// Lines are wrapped at any character.
+    Char,
+    /// Lines are wrapped after whitespaces. Words longer than the width are wrapped at any character.
+    Word,
+}
+
+// Settings to split lines into the rows on the screen. Widths are counted in the same way as the text is rendered by
+// `LineHighlighter`: tabs are expanded from the start of each row and masked chars have the width of the mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Wrapper {
+    pub mode: WrapMode,
+    pub width: usize,
+    pub tab_len: u8,
+    pub mask: Option<char>,
+}
+
+// A row on the screen. It is a whole line of the text or a part of a wrapped line. `start` and `end` (exclusive) are
+// char offsets in the text. The newline at the end of the line belongs to the last row of the line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VisualRow {
+    pub row: usize,
+    pub start: usize,
+    pub end: usize,
+    pub first: bool,
+    pub last: bool,
+    // Set on the last row of the header of a fold. The number of the hidden lines and the offset of the end of them
+    pub folded: Option<(usize, usize)>,
+}
+
+impl VisualRow {
+    // Whether the cursor at the offset is rendered in this row. The offset at the end of a wrapped row is rendered at
+    // the head of the next row. Offsets in the lines hidden by a fold are rendered in the header of the fold.
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && (offset < self.end || self.last && offset <= self.fold_end())
+    }
+
+    // The end of the text this row stands for, including the lines hidden after it
+    pub fn fold_end(&self) -> usize {
+        self.folded.map_or(self.end, |(_, end)| end)
+    }
+}
+
+// Rows of the text on the screen with the byte ranges of them in the text. They are shared by the renders and the cursor
+// movements until the text, the wrapping or the folds change.
+#[derive(Clone, Debug)]
+pub struct ScreenRows {
+    pub rows: Arc<[VisualRow]>,
+    pub bytes: Arc<[Range<usize>]>,
+}
+
+impl ScreenRows {
+    pub fn new(text: &str, wrapper: &Wrapper, folds: &[Range<usize>]) -> Self {
+        let chars = text.chars().collect::<Vec<_>>();
+        let rows: Arc<[VisualRow]> = wrapper.rows(&chars, folds).into();
+
+        // Byte indices of the chars to slice the rows out of the text
+        let indices = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([text.len()])
+            .collect::<Vec<_>>();
+        let bytes = rows
+            .iter()
+            .map(|r| indices[r.start]..indices[r.end])
+            .collect();
+        Self { rows, bytes }
+    }
+
+    // Chars of the i-th row
+    pub fn chars(&self, text: &str, i: usize) -> Vec<char> {
+        text[self.bytes[i].clone()].chars().collect()
+    }
+}
+
+impl Wrapper {
+    pub fn wraps(&self) -> bool {
+        self.mode != WrapMode::None && self.width > 0
+    }
+
+    fn char_width(&self, c: char, col: usize) -> usize {
+        if let Some(mask) = self.mask {
+            return mask.width().unwrap_or(0);
+        }
+        if c == '\t' && self.tab_len > 0 {
+            let tab_len = self.tab_len as usize;
+            return tab_len - col % tab_len;
+        }
+        c.width().unwrap_or(0)
+    }
+
+    fn width_of(&self, chars: &[char]) -> usize {
+        chars
+            .iter()
+            .fold(0, |col, &c| col + self.char_width(c, col))
+    }
+
+    // Offsets of the heads of the rows in the line, relative to the start of the line. The first row starts at 0.
+    fn wrap_line(&self, line: &[char]) -> Vec<usize> {
+        let mut starts = vec![0];
+        if !self.wraps() {
+            return starts;
+        }
+
+        let mut col = 0;
+        let mut brk = None; // Offset after the last whitespace in the current row
+        for (i, &c) in line.iter().enumerate() {
+            loop {
+                let start = *starts.last().unwrap();
+                if col + self.char_width(c, col) <= self.width || i == start {
+                    break;
+                }
+                let next = match (self.mode, brk) {
+                    (WrapMode::Word, Some(b)) if b > start => b,
+                    _ => i,
+                };
+                starts.push(next);
+                col = self.width_of(&line[next..i]);
+                brk = None;
+            }
+            col += self.char_width(c, col);
+            if self.mode == WrapMode::Word && c.is_whitespace() {
+                brk = Some(i + 1);
+            }
+        }
+        starts
+    }
+
+    // All rows of the text on the screen. The lines hidden by the folds are skipped. The folds must be merged by
+    // `fold::merge`.
+    pub fn rows(&self, chars: &[char], folds: &[Range<usize>]) -> Vec<VisualRow> {
+        let mut rows: Vec<VisualRow> = vec![];
+        let mut line_start = 0;
+        let mut folds = folds.iter().peekable();
+        for (row, line) in chars.split(|&c| c == '\n').enumerate() {
+            while folds.next_if(|f| f.end <= row).is_some() {}
+            if folds.peek().is_some_and(|f| f.start < row) {
+                let last = rows.last_mut().expect("header of the fold was pushed");
+                let hidden = last.folded.map_or(0, |(n, _)| n);
+                last.folded = Some((hidden + 1, line_start + line.len()));
+                line_start += line.len() + 1;
+                continue;
+            }
+
+            let starts = self.wrap_line(line);
+            for (i, &start) in starts.iter().enumerate() {
+                let end = starts.get(i + 1).copied().unwrap_or(line.len());
+                rows.push(VisualRow {
+                    row,
+                    start: line_start + start,
+                    end: line_start + end,
+                    first: i == 0,
+                    last: i + 1 == starts.len(),
+                    folded: None,
+                });
+            }
+            line_start += line.len() + 1;
+        }
+        rows
+    }
+
+    // Display column of the offset in the row. `chars` start at the head of the row
+    pub fn col_at(&self, chars: &[char], row: &VisualRow, offset: usize) -> usize {
+        self.width_of(&chars[..offset - row.start])
+    }
+
+    // Offset of the char at the display column in the row. The column past the end of the row is clamped to the last
+    // position the cursor can be rendered at in the row. `chars` start at the head of the row.
+    pub fn offset_at(&self, chars: &[char], row: &VisualRow, col: usize) -> usize {
+        let max = if row.last || row.start == row.end {
+            row.end
+        } else {
+            row.end - 1
+        };
+        let mut width = 0;
+        for i in row.start..max {
+            width += self.char_width(chars[i - row.start], width);
+            if width > col {
+                return i;
+            }
+        }
+        max
+    }
+}
+
+// Index of the row on the screen where the cursor at the offset is rendered. Offsets hidden by a fold are in its header
+pub fn row_index(rows: &[VisualRow], offset: usize) -> usize {
+    let i = rows.partition_point(|r| r.fold_end() < offset || r.end == offset && !r.last);
+    i.min(rows.len().saturating_sub(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrapper(mode: WrapMode, width: usize) -> Wrapper {
+        Wrapper {
+            mode,
+            width,
+            tab_len: 4,
+            mask: None,
+        }
+    }
+
+    fn wrap(w: &Wrapper, text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        w.rows(&chars, &[])
+            .iter()
+            .map(|r| chars[r.start..r.end].iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn wrap_lines() {
+        let tests = [
+            (WrapMode::None, 3, "abcdef", &["abcdef"][..]),
+            (WrapMode::Char, 0, "abcdef", &["abcdef"]),
+            (WrapMode::Char, 3, "", &[""]),
+            (WrapMode::Char, 3, "abc", &["abc"]),
+            (WrapMode::Char, 3, "abcdefg", &["abc", "def", "g"]),
+            (WrapMode::Char, 3, "ab\ncdef", &["ab", "cde", "f"]),
+            (WrapMode::Char, 3, "aあい", &["aあ", "い"]),
+            (WrapMode::Char, 1, "あ", &["あ"]),
+            (WrapMode::Char, 4, "a\tb", &["a\t", "b"]),
+            (WrapMode::Char, 2, "ab\t", &["ab", "\t"]),
+            (WrapMode::Word, 6, "ab cd ef", &["ab cd ", "ef"]),
+            (WrapMode::Word, 5, "ab cd ef", &["ab ", "cd ef"]),
+            (WrapMode::Word, 4, "abcdefg h", &["abcd", "efg ", "h"]),
+            (WrapMode::Word, 4, "a bcdefg", &["a ", "bcde", "fg"]),
+            (WrapMode::Word, 3, "a\nbc de", &["a", "bc ", "de"]),
+        ];
+
+        for (mode, width, text, want) in tests {
+            let have = wrap(&wrapper(mode, width), text);
+            assert_eq!(have, want, "mode={mode:?}, width={width}, text={text:?}");
+        }
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let w = wrapper(WrapMode::Char, 3);
+        let chars: Vec<char> = "abcdefg\nh".chars().collect();
+        let rows = w.rows(&chars, &[]);
+        let have: Vec<_> = rows
+            .iter()
+            .map(|r| (r.row, r.start, r.end, r.first, r.last))
+            .collect();
+        let want = [
+            (0, 0, 3, true, false),
+            (0, 3, 6, false, false),
+            (0, 6, 7, false, true),
+            (1, 8, 9, true, true),
+        ];
+        assert_eq!(have, want);
+
+        // The end of a wrapped row is at the head of the next row
+        assert_eq!(row_index(&rows, 2), 0);
+        assert_eq!(row_index(&rows, 3), 1);
+        assert_eq!(row_index(&rows, 7), 2);
+        assert_eq!(row_index(&rows, 8), 3);
+        assert_eq!(row_index(&rows, 9), 3);
+
+        assert_eq!(w.col_at(&chars[3..6], &rows[1], 5), 2);
+        assert_eq!(w.offset_at(&chars[3..6], &rows[1], 1), 4);
+        assert_eq!(w.offset_at(&chars[3..6], &rows[1], 9), 5);
+        assert_eq!(w.offset_at(&chars[6..7], &rows[2], 9), 7);
+    }
+
+    #[test]
+    fn fold_rows() {
+        let w = wrapper(WrapMode::Char, 3);
+        let chars: Vec<char> = "a\nb\nc\nd\nefgh\ni".chars().collect();
+        let rows = w.rows(&chars, &[1..3, 4..6]);
+        let have: Vec<_> = rows
+            .iter()
+            .map(|r| (r.row, r.start, r.end, r.folded))
+            .collect();
+        let want = [
+            (0, 0, 1, None),
+            (1, 2, 3, Some((1, 5))),
+            (3, 6, 7, None),
+            (4, 8, 11, None),
+            (4, 11, 12, Some((1, 14))),
+        ];
+        assert_eq!(have, want);
+
+        // Offsets in the hidden lines are in the header
+        assert_eq!(row_index(&rows, 4), 1);
+        assert_eq!(row_index(&rows, 5), 1);
+        assert_eq!(row_index(&rows, 6), 2);
+        assert_eq!(row_index(&rows, 13), 4);
+        assert!(rows[1].contains(5));
+        assert!(!rows[3].contains(11));
+        assert!(rows[4].contains(14));
+    }
+}
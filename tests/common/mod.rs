@@ -0,0 +1,24 @@
+// Helpers shared by the integration tests. Each test crate uses some of them
+#![allow(dead_code)]
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::Widget;
+use ratatui_mergearea::MergeArea;
+
+pub fn render(widget: impl Widget, width: u16, height: u16) -> Buffer {
+    let area = Rect::new(0, 0, width, height);
+    let mut buf = Buffer::empty(area);
+    widget.render(area, &mut buf);
+    buf
+}
+
+// Symbols of the rendered row
+pub fn row(buf: &Buffer, y: u16) -> String {
+    (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect()
+}
+
+pub fn render_rows(textarea: &MergeArea<'_>, width: u16, height: u16) -> Vec<String> {
+    let buf = render(textarea, width, height);
+    (0..height).map(|y| row(&buf, y)).collect()
+}
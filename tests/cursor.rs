@@ -200,7 +200,7 @@ fn head() {
 fn end() {
     for text in [["efg", "h", ""], ["あいう", "👪", ""]] {
         let mut t = MergeArea::with_value(text.join("\n"));
-        for row in 0..t.lines().len() {
+        for row in 0..t.lines().count() {
             let len = match row {
                 0 => 3,
                 1 => 1,
@@ -281,7 +281,7 @@ fn bottom_trim() {
         let mut t = MergeArea::with_value(lines.join("\n"));
         t.move_cursor(CursorMove::Jump(0, u16::MAX));
         t.move_cursor(CursorMove::Bottom);
-        let text_lines = t.text().as_str().lines().collect::<Vec<&str>>();
+        let text_lines = t.lines().collect::<Vec<_>>();
         let col = text_lines.last().unwrap().chars().count();
         assert_eq!(t.cursor2(), (text_lines.len() - 1, col), "{:?}", t.text());
     }
@@ -0,0 +1,138 @@
+mod common;
+
+use automerge::transaction::Transactable as _;
+use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+use common::{render, row};
+use ratatui::style::{Color, Style};
+use ratatui_mergearea::{CursorMove, Diagnostic, MergeArea, Severity};
+
+fn diag(range: (usize, usize), severity: Severity) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity,
+        message: format!("{severity:?}"),
+    }
+}
+
+#[test]
+fn signs_and_styles() {
+    let mut t = MergeArea::with_value("ab\ncd\nef");
+    t.move_cursor(CursorMove::Jump(1, 1)); // Keep the cursor off the diagnosed text
+    t.set_diagnostics(vec![
+        diag((0, 1), Severity::Hint),
+        diag((1, 2), Severity::Warning),
+        diag((6, 7), Severity::Info),
+    ]);
+
+    // Without line numbers no sign column is shown
+    let buf = render(&t, 8, 3);
+    assert_eq!(row(&buf, 0), "ab      ");
+    assert_eq!(buf[(0, 0)].fg, Color::Cyan);
+    assert_eq!(buf[(1, 0)].fg, Color::Yellow);
+    assert_eq!(buf[(0, 1)].fg, Color::Reset);
+    assert_eq!(buf[(0, 2)].fg, Color::Blue);
+
+    // The most severe diagnostic on each line is shown
+    t.set_line_number_style(Style::default());
+    let buf = render(&t, 8, 3);
+    assert_eq!(row(&buf, 0), "W 1 ab  ");
+    assert_eq!(row(&buf, 1), "  2 cd  ");
+    assert_eq!(row(&buf, 2), "I 3 ef  ");
+    assert_eq!(buf[(0, 0)].fg, Color::Yellow);
+    assert_eq!(buf[(4, 0)].fg, Color::Cyan);
+
+    t.set_diagnostic_style(Severity::Warning, Style::default().fg(Color::Magenta));
+    let buf = render(&t, 8, 3);
+    assert_eq!(buf[(0, 0)].fg, Color::Magenta);
+    assert_eq!(buf[(5, 0)].fg, Color::Magenta);
+
+    t.set_diagnostics(vec![]);
+    let buf = render(&t, 8, 3);
+    assert_eq!(row(&buf, 0), " 1 ab   ");
+}
+
+#[test]
+fn follow_local_edits() {
+    let mut t = MergeArea::with_value("one two\nthree");
+    t.set_diagnostics(vec![
+        diag((4, 7), Severity::Error),
+        diag((8, 13), Severity::Warning),
+    ]);
+
+    t.insert_str("zero ");
+    let ranges: Vec<_> = t.diagnostics().iter().map(|d| d.range).collect();
+    assert_eq!(ranges, [(9, 12), (13, 18)]);
+    assert_eq!(t.diagnostics_at(9)[0].severity, Severity::Error);
+    assert!(t.diagnostics_at(5).is_empty());
+
+    // Deleting the diagnosed text leaves an empty range
+    t.move_cursor(CursorMove::Jump(0, 9));
+    t.delete_next_word();
+    let ranges: Vec<_> = t.diagnostics().iter().map(|d| d.range).collect();
+    assert_eq!(ranges, [(9, 9), (10, 15)]);
+
+    // The text restored by undo is inserted before the empty range
+    t.undo();
+    let ranges: Vec<_> = t.diagnostics().iter().map(|d| d.range).collect();
+    assert_eq!(ranges, [(12, 12), (13, 18)]);
+
+    t.select_all();
+    t.cut();
+    let ranges: Vec<_> = t.diagnostics().iter().map(|d| d.range).collect();
+    assert_eq!(ranges, [(0, 0), (0, 0)]);
+}
+
+#[test]
+fn follow_merged_edits() {
+    let mut doc = AutoCommit::new();
+    let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    doc.splice_text(&obj, 0, 0, "let x = y;").unwrap();
+    let mut t = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    t.set_diagnostics(vec![diag((8, 9), Severity::Error)]);
+
+    let mut remote = t.doc_mut().unwrap().fork().with_actor(ActorId::random());
+    remote.splice_text(&obj, 0, 0, "// ").unwrap();
+    t.merge(&mut remote).unwrap();
+
+    assert_eq!(t.text().as_str(), "// let x = y;");
+    assert_eq!(t.diagnostics()[0].range, (11, 12));
+}
+
+#[test]
+fn jump_between_diagnostics() {
+    let mut t = MergeArea::with_value("a\nb\nc\nd");
+    t.set_diagnostics(vec![
+        diag((6, 7), Severity::Hint),
+        diag((2, 3), Severity::Error),
+    ]);
+
+    t.move_cursor(CursorMove::NextDiagnostic);
+    assert_eq!(t.cursor2(), (1, 0));
+    t.move_cursor(CursorMove::NextDiagnostic);
+    assert_eq!(t.cursor2(), (3, 0));
+    t.move_cursor(CursorMove::PrevDiagnostic);
+    assert_eq!(t.cursor2(), (1, 0));
+    t.move_cursor(CursorMove::PrevDiagnostic);
+    assert_eq!(t.cursor2(), (1, 0));
+}
+
+#[test]
+fn diagnostics_past_end_of_text() {
+    let mut t = MergeArea::with_value("ab\ncd");
+    t.set_diagnostics(vec![
+        diag((4, 10), Severity::Error),
+        diag((20, 30), Severity::Warning),
+    ]);
+    let ranges: Vec<_> = t.diagnostics().iter().map(|d| d.range).collect();
+    assert_eq!(ranges, [(4, 5), (5, 5)]);
+
+    t.move_cursor(CursorMove::NextDiagnostic);
+    assert_eq!(t.cursor2(), (1, 1));
+    t.move_cursor(CursorMove::NextDiagnostic);
+    assert_eq!(t.cursor2(), (1, 2));
+    assert!(t.delete_line_by_head());
+    assert_eq!(t.lines().collect::<Vec<_>>(), ["ab", ""]);
+
+    let buf = render(&t, 5, 2);
+    assert_eq!(row(&buf, 0), "ab   ");
+}
@@ -0,0 +1,902 @@
+mod common;
+
+use automerge::transaction::Transactable as _;
+use automerge::{ActorId, AutoCommit, ObjId, ObjType, ROOT, ReadDoc as _, TextEncoding};
+use common::{render, row};
+use ratatui::buffer::Buffer;
+use ratatui_mergearea::{CursorMove, MergeArea};
+
+fn doc_with_text(text: &str) -> (AutoCommit, ObjId) {
+    let mut doc = AutoCommit::new();
+    let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    doc.splice_text(&obj, 0, 0, text).unwrap();
+    (doc, obj)
+}
+
+fn doc_text(t: &MergeArea<'_>) -> String {
+    t.doc().unwrap().text(t.text_obj().unwrap()).unwrap()
+}
+
+#[test]
+fn from_doc_not_text_object() {
+    let mut doc = AutoCommit::new();
+    let obj = doc.put_object(ROOT, "map", ObjType::Map).unwrap();
+    assert!(MergeArea::from_doc(doc, obj).is_err());
+}
+
+#[test]
+fn from_doc_encodings() {
+    for encoding in [TextEncoding::Utf8CodeUnit, TextEncoding::Utf16CodeUnit] {
+        let mut doc = AutoCommit::new_with_encoding(encoding);
+        let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+        doc.splice_text(&obj, 0, 0, "ab").unwrap();
+        let mut t = MergeArea::from_doc(doc, obj).unwrap();
+
+        t.move_cursor(CursorMove::End);
+        t.insert_str("e\u{301}🐱");
+        t.move_cursor(CursorMove::Head);
+        t.insert_char('x');
+        assert_eq!(doc_text(&t), "xabe\u{301}🐱", "encoding={encoding:?}");
+        assert!(t.undo());
+        assert!(t.undo());
+        assert_eq!(doc_text(&t), "ab", "encoding={encoding:?}");
+        assert!(t.redo());
+        assert_eq!(doc_text(&t), "abe\u{301}🐱", "encoding={encoding:?}");
+    }
+
+    // Indices of graphemes can't be mapped to the chars of the text
+    let mut doc = AutoCommit::new_with_encoding(TextEncoding::GraphemeCluster);
+    let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    assert!(MergeArea::from_doc(doc, obj).is_err());
+}
+
+#[test]
+fn edits_are_written_to_doc() {
+    let (doc, obj) = doc_with_text("🐱🐶\nabc");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    assert_eq!(t.text().as_str(), "🐱🐶\nabc");
+
+    t.move_cursor(CursorMove::Forward);
+    t.insert_char('x');
+    assert_eq!(doc_text(&t), "🐱x🐶\nabc");
+
+    t.insert_newline();
+    assert_eq!(doc_text(&t), "🐱x\n🐶\nabc");
+
+    t.delete_char();
+    t.delete_next_char();
+    assert_eq!(doc_text(&t), "🐱x\nabc");
+
+    t.move_cursor(CursorMove::Jump(1, 1));
+    t.delete_line_by_head();
+    assert_eq!(doc_text(&t), "🐱x\nbc");
+    assert_eq!(t.text().as_str(), doc_text(&t));
+
+    t.set_text("hello".into());
+    assert_eq!(doc_text(&t), "hello");
+    assert_eq!(t.text().as_str(), "hello");
+}
+
+#[test]
+fn undo_redo_are_written_to_doc() {
+    let (doc, obj) = doc_with_text("abc");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+
+    t.insert_str("xyz");
+    assert_eq!(doc_text(&t), "xyzabc");
+    assert!(t.undo());
+    assert_eq!(doc_text(&t), "abc");
+    assert!(t.redo());
+    assert_eq!(doc_text(&t), "xyzabc");
+}
+
+#[test]
+fn merge_concurrent_edits() {
+    let (mut doc, obj) = doc_with_text("The fox jumped.");
+    let fork = doc.fork().with_actor(ActorId::random());
+    let mut left = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    let mut right = MergeArea::from_doc(fork, obj).unwrap();
+
+    left.move_cursor(CursorMove::End);
+    left.insert_str(" Twice.");
+    right.move_cursor(CursorMove::WordForward);
+    right.insert_str("quick ");
+
+    left.merge(right.doc_mut().unwrap()).unwrap();
+    assert_eq!(left.text().as_str(), "The quick fox jumped. Twice.");
+    assert_eq!(doc_text(&left), "The quick fox jumped. Twice.");
+
+    // Merging again after local edits must not fail with stale heads
+    left.move_cursor(CursorMove::End);
+    left.insert_char('!');
+    right.merge(left.doc_mut().unwrap()).unwrap();
+    assert_eq!(right.text().as_str(), "The quick fox jumped. Twice.!");
+}
+
+#[test]
+fn merge_without_doc() {
+    let mut t = MergeArea::with_value("abc");
+    let mut other = AutoCommit::new();
+    assert_eq!(t.merge(&mut other).unwrap(), vec![]);
+    assert!(t.doc().is_none());
+}
+
+fn merge_remote(t: &mut MergeArea<'_>, edit: impl FnOnce(&mut AutoCommit, &ObjId)) {
+    let obj = t.text_obj().unwrap().clone();
+    let mut remote = t.doc_mut().unwrap().fork().with_actor(ActorId::random());
+    edit(&mut remote, &obj);
+    t.merge(&mut remote).unwrap();
+}
+
+#[test]
+fn remote_edits_keep_cursor() {
+    let (doc, obj) = doc_with_text("abc\ndef\nghi");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::Jump(1, 1)); // At 'e'
+
+    // Insertion before the cursor
+    merge_remote(&mut t, |d, o| d.splice_text(o, 0, 0, "🐶\n").unwrap());
+    assert_eq!(t.text().as_str(), "🐶\nabc\ndef\nghi");
+    assert_eq!(t.cursor2(), (2, 1));
+
+    // Insertion after the cursor
+    merge_remote(&mut t, |d, o| d.splice_text(o, 11, 0, "xyz").unwrap());
+    assert_eq!(t.text().as_str(), "🐶\nabc\ndef\ngxyzhi");
+    assert_eq!(t.cursor2(), (2, 1));
+
+    // Deletion before the cursor
+    merge_remote(&mut t, |d, o| d.splice_text(o, 2, 4, "").unwrap());
+    assert_eq!(t.text().as_str(), "🐶\ndef\ngxyzhi");
+    assert_eq!(t.cursor2(), (1, 1));
+
+    // Deletion including the cursor
+    merge_remote(&mut t, |d, o| d.splice_text(o, 2, 3, "").unwrap());
+    assert_eq!(t.text().as_str(), "🐶\n\ngxyzhi");
+    assert_eq!(t.cursor2(), (1, 0));
+}
+
+#[test]
+fn remote_edits_keep_selection() {
+    let (doc, obj) = doc_with_text("hello world");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::WordForward);
+    t.start_selection();
+    t.move_cursor(CursorMove::End);
+
+    merge_remote(&mut t, |d, o| d.splice_text(o, 0, 5, "goodbye").unwrap());
+    assert_eq!(t.text().as_str(), "goodbye world");
+    assert_eq!(t.selection_range(), Some((8, 13)));
+    t.copy();
+    assert_eq!(t.yank_text(), "world");
+}
+
+#[test]
+fn remote_edits_keep_viewport() {
+    let text = (0..20)
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (doc, obj) = doc_with_text(&text);
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::Jump(10, 0));
+    let b = render(&t, 8, 4);
+    assert_eq!(b.cell((0, 3)).unwrap().symbol(), "1"); // Line "10" at the bottom
+
+    merge_remote(&mut t, |d, o| d.splice_text(o, 0, 0, "a\nb\nc\n").unwrap());
+    assert_eq!(t.cursor2(), (13, 0));
+    let b = render(&t, 8, 4);
+    assert_eq!(b.cell((0, 3)).unwrap().symbol(), "1");
+    assert_eq!(b.cell((1, 3)).unwrap().symbol(), "0");
+    assert_eq!(b.cell((0, 0)).unwrap().symbol(), "7");
+}
+
+#[test]
+fn reload_after_doc_mut() {
+    let (doc, obj) = doc_with_text("fox");
+    let mut t = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    t.move_cursor(CursorMove::End);
+
+    let doc = t.doc_mut().unwrap();
+    doc.splice_text(&obj, 0, 0, "The ").unwrap();
+    t.reload();
+    assert_eq!(t.text().as_str(), "The fox");
+    assert_eq!(t.cursor2(), (0, 7));
+}
+
+#[test]
+fn local_edit_after_doc_mut() {
+    let (doc, obj) = doc_with_text("abc");
+    let mut t = MergeArea::from_doc(doc, obj.clone()).unwrap();
+
+    // The local edit is not applied again on reload
+    t.doc_mut().unwrap();
+    t.insert_char('x');
+    t.reload();
+    assert_eq!(t.text().as_str(), "xabc");
+    assert_eq!(doc_text(&t), "xabc");
+
+    // Modifications made through the document are applied before the local edit
+    t.doc_mut()
+        .unwrap()
+        .splice_text(&obj, 0, 0, "The ")
+        .unwrap();
+    t.insert_char('y');
+    assert_eq!(t.text().as_str(), "The xyabc");
+    assert_eq!(doc_text(&t), "The xyabc");
+    assert_eq!(t.cursor2(), (0, 6));
+    t.reload();
+    assert_eq!(t.text().as_str(), "The xyabc");
+
+    t.undo();
+    assert_eq!(t.text().as_str(), "The xabc");
+    assert_eq!(doc_text(&t), "The xabc");
+}
+
+#[test]
+fn empty_edits_are_not_recorded() {
+    let (doc, obj) = doc_with_text("abc");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.insert_char('x');
+    t.move_cursor(CursorMove::End);
+
+    assert!(!t.delete_str(2));
+    assert!(!t.insert_str(""));
+    assert!(t.undo());
+    assert_eq!(t.text().as_str(), "abc");
+    assert!(!t.undo());
+}
+
+#[test]
+fn set_text_keeps_cursor() {
+    let (doc, obj) = doc_with_text("fox jumped");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::WordForward); // At 'j'
+    t.start_selection();
+    t.move_cursor(CursorMove::End);
+
+    t.set_text("The quick fox jumped".into());
+    assert_eq!(t.cursor2(), (0, 20));
+    assert_eq!(t.selection_range(), Some((14, 20)));
+}
+
+#[test]
+fn cursor_follows_doc_mut() {
+    let (doc, obj) = doc_with_text("hello world");
+    let mut t = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    t.move_cursor(CursorMove::WordForward);
+    t.start_selection();
+    t.move_cursor(CursorMove::End);
+
+    // The positions are as of the text until it is reloaded
+    t.doc_mut()
+        .unwrap()
+        .splice_text(&obj, 0, 5, "goodbye")
+        .unwrap();
+    assert_eq!(t.selection_range(), Some((6, 11)));
+
+    t.reload();
+    assert_eq!(t.selection_range(), Some((8, 13)));
+
+    // The cursor at the end of text follows the end
+    merge_remote(&mut t, |d, o| d.splice_text(o, 13, 0, "!").unwrap());
+    assert_eq!(t.cursor(), 14);
+    assert_eq!(t.selection_range(), Some((8, 14)));
+}
+
+#[test]
+fn remote_cursors_follow_merge() {
+    use automerge::CursorPosition;
+    use ratatui::style::{Color, Style};
+    use ratatui_mergearea::{RemoteCursor, RemotePosition};
+
+    let (doc, obj) = doc_with_text("hello world");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    let obj = t.text_obj().unwrap().clone();
+    let mut peer = MergeArea::from_doc(t.doc().unwrap().clone(), obj.clone()).unwrap();
+    peer.move_cursor(CursorMove::WordForward); // At 'w'
+    peer.start_selection();
+    peer.move_cursor(CursorMove::End);
+
+    let style = Style::default().bg(Color::Magenta);
+    let anchor = |pos| peer.doc().unwrap().get_cursor(&obj, pos, None).unwrap();
+    let (start, end) = peer.selection_range().unwrap();
+    assert_eq!(end, peer.text().as_str().len());
+    let mut remote = RemoteCursor::new("peer", anchor(CursorPosition::Index(start)), style);
+    remote.selection_style = Style::default().bg(Color::Cyan);
+    remote.selection = Some(RemotePosition::Anchor(anchor(CursorPosition::End)));
+    remote.label = Some("bob".to_string());
+    t.set_remote_cursors(vec![remote]);
+
+    merge_remote(&mut t, |d, o| d.splice_text(o, 0, 5, "goodbye").unwrap());
+
+    let b = render(&t, 20, 1);
+    let bg = |x: u16| b.cell((x, 0)).unwrap().bg;
+    assert_eq!(bg(7), Color::Reset); // ' '
+    assert_eq!(bg(8), Color::Magenta); // 'w'
+    assert_eq!(bg(9), Color::Cyan); // 'o'
+    assert_eq!(bg(12), Color::Cyan); // 'd'
+    assert_eq!(b.cell((13, 0)).unwrap().bg, Color::Cyan); // End of selection
+    assert_eq!(b.cell((15, 0)).unwrap().symbol(), "b"); // Label
+}
+
+#[test]
+fn remote_cursors_without_doc() {
+    use ratatui::style::{Color, Style};
+    use ratatui_mergearea::RemoteCursor;
+
+    let mut t = MergeArea::with_value("ab\ncd");
+    let style = Style::default().bg(Color::Magenta);
+    let (doc, obj) = doc_with_text("ab\ncd");
+    let anchor = doc.get_cursor(&obj, 0, None);
+    t.set_remote_cursors(vec![
+        RemoteCursor::new("offset", 4, style),
+        RemoteCursor::new("clamped", 100, style),
+        RemoteCursor::new("anchor", anchor.unwrap(), style),
+    ]);
+
+    let b = render(&t, 4, 2);
+    assert_eq!(b.cell((1, 1)).unwrap().bg, Color::Magenta); // 'd'
+    assert_eq!(b.cell((2, 1)).unwrap().bg, Color::Magenta); // End of text
+    assert_eq!(b.cell((0, 0)).unwrap().bg, Color::Reset); // Anchor of unknown document is ignored
+}
+
+#[test]
+fn undo_after_remote_edits() {
+    let (doc, obj) = doc_with_text("The fox jumped.");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::WordForward);
+    t.insert_str("quick ");
+
+    merge_remote(&mut t, |d, o| d.splice_text(o, 0, 0, "Look! ").unwrap());
+    assert_eq!(t.text().as_str(), "Look! The quick fox jumped.");
+
+    // Only the local insertion is reverted at its current position
+    assert!(t.undo());
+    assert_eq!(t.text().as_str(), "Look! The fox jumped.");
+    assert_eq!(doc_text(&t), "Look! The fox jumped.");
+    assert_eq!(t.cursor(), 10);
+    assert!(!t.undo()); // Remote edits are not in the history
+
+    merge_remote(&mut t, |d, o| d.splice_text(o, 0, 6, "").unwrap());
+    assert!(t.redo());
+    assert_eq!(t.text().as_str(), "The quick fox jumped.");
+    assert_eq!(t.cursor(), 10);
+}
+
+#[test]
+fn undo_keeps_text_inserted_by_others() {
+    let (doc, obj) = doc_with_text("🐶");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.insert_str("abcd");
+
+    // Another peer inserts text in the middle of the local insertion and deletes a part of it
+    merge_remote(&mut t, |d, o| {
+        d.splice_text(o, 2, 0, "XY").unwrap();
+        d.splice_text(o, 5, 1, "").unwrap();
+    });
+    assert_eq!(t.text().as_str(), "abXYc🐶");
+
+    assert!(t.undo());
+    assert_eq!(t.text().as_str(), "XY🐶");
+    assert_eq!(doc_text(&t), "XY🐶");
+
+    assert!(t.redo());
+    assert_eq!(t.text().as_str(), "abcdXY🐶");
+    assert!(t.undo());
+    assert_eq!(t.text().as_str(), "XY🐶");
+}
+
+#[test]
+fn undo_deletion_after_remote_edits() {
+    let (doc, obj) = doc_with_text("hello big world");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::WordForward);
+    t.delete_str(4);
+    assert_eq!(t.text().as_str(), "hello world");
+
+    merge_remote(&mut t, |d, o| {
+        d.splice_text(o, 0, 5, "goodbye").unwrap();
+        d.splice_text(o, 13, 0, "!").unwrap();
+    });
+    assert_eq!(t.text().as_str(), "goodbye world!");
+
+    // The deleted text is restored where it was
+    assert!(t.undo());
+    assert_eq!(t.text().as_str(), "goodbye big world!");
+    assert_eq!(t.cursor(), 8);
+
+    assert!(t.redo());
+    assert_eq!(t.text().as_str(), "goodbye world!");
+    assert_eq!(doc_text(&t), "goodbye world!");
+}
+
+#[test]
+fn scrub_history() {
+    use ratatui_mergearea::{Input, Key};
+
+    let (mut doc, obj) = doc_with_text("fox");
+    doc.commit();
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::End);
+    t.insert_str(" jumped");
+    merge_remote(&mut t, |d, o| d.splice_text(o, 0, 0, "The ").unwrap());
+    let live = t.doc_mut().unwrap().get_heads();
+
+    assert!(t.scrub_back());
+    assert_eq!(t.scrubbed_text(), Some("fox jumped"));
+    assert!(t.scrub_back());
+    assert_eq!(t.scrubbed_text(), Some("fox"));
+
+    // Key inputs don't modify the text while viewing the history
+    let input = Input {
+        key: Key::Char('x'),
+        ..Default::default()
+    };
+    assert!(!t.input(input.clone()));
+    assert_eq!(t.text().as_str(), "The fox jumped");
+
+    let b = render(&t, 4, 1);
+    assert_eq!(b, Buffer::with_lines(["fox "]));
+
+    assert!(t.scrub_forward());
+    assert!(t.scrub_forward());
+    assert_eq!(t.scrubbed_text(), Some("The fox jumped"));
+    assert_eq!(t.scrubbed_heads(), Some(&live[..]));
+    assert!(!t.scrub_forward());
+
+    t.stop_scrubbing();
+    assert!(t.input(input));
+    assert_eq!(t.text().as_str(), "The fox jumpedx");
+}
+
+#[test]
+fn scrub_is_read_only() {
+    let (mut doc, obj) = doc_with_text("fox");
+    doc.commit();
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::End);
+    t.insert_str(" jumped");
+    t.select_all();
+
+    assert!(t.scrub_back());
+    assert_eq!(t.scrubbed_text(), Some("fox"));
+
+    // Edits are ignored while viewing the history
+    t.insert_char('x');
+    assert!(!t.insert_str("x"));
+    assert!(!t.delete_char());
+    assert!(!t.delete_line_by_head());
+    assert!(!t.cut());
+    assert!(!t.undo());
+    t.set_text("x".into());
+    assert_eq!(t.text().as_str(), "fox jumped");
+    assert_eq!(doc_text(&t), "fox jumped");
+
+    // Merged changes are added to the viewed history
+    merge_remote(&mut t, |d, o| d.splice_text(o, 0, 0, "The ").unwrap());
+    assert_eq!(t.scrubbed_text(), Some("fox"));
+    assert!(t.scrub_forward());
+    assert_eq!(t.scrubbed_text(), Some("fox jumped"));
+    assert!(t.scrub_forward());
+    assert_eq!(t.scrubbed_text(), Some("The fox jumped"));
+    assert!(!t.scrub_forward());
+
+    t.stop_scrubbing();
+    assert!(t.undo());
+    assert_eq!(t.text().as_str(), "The fox");
+}
+
+#[test]
+fn scrub_without_doc() {
+    let mut t = MergeArea::with_value("abc");
+    assert!(!t.scrub_back());
+    assert!(!t.scrub_forward());
+    assert!(t.scrub_to(&[]).is_ok());
+    assert_eq!(t.scrubbed_heads(), None);
+
+    let (doc, obj) = doc_with_text("abc");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    let mut other = AutoCommit::new();
+    other.put(ROOT, "a", 1).unwrap();
+    let heads = other.get_heads();
+    assert!(t.scrub_to(&heads).is_err());
+}
+
+#[test]
+fn blame_by_actor() {
+    use ratatui::style::{Color, Modifier, Style};
+
+    let (doc, obj) = doc_with_text("ab\ncd");
+    let me = doc.get_actor().clone();
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.set_cursor_line_style(Style::default());
+    t.set_blame_style(move |actor| {
+        let fg = if *actor == me {
+            Color::Green
+        } else {
+            Color::Red
+        };
+        Style::default().fg(fg)
+    });
+    merge_remote(&mut t, |d, o| d.splice_text(o, 4, 0, "xy").unwrap());
+    assert_eq!(t.text().as_str(), "ab\ncxyd");
+
+    let b = render(&t, 6, 2);
+    let fg = |x: u16, y: u16| b.cell((x, y)).unwrap().fg;
+    assert_eq!(fg(1, 0), Color::Green); // 'b'
+    assert_eq!(fg(0, 1), Color::Green); // 'c'
+    assert_eq!(fg(1, 1), Color::Red); // 'x'
+    assert_eq!(fg(2, 1), Color::Red); // 'y'
+    assert_eq!(fg(3, 1), Color::Green); // 'd'
+    assert!(
+        b.cell((0, 0))
+            .unwrap()
+            .modifier
+            .contains(Modifier::REVERSED)
+    ); // Cursor is above blame
+
+    // Only the visible rows are looked up
+    t.scroll((1, 0));
+    let b = render(&t, 6, 2);
+    assert_eq!(b.cell((1, 0)).unwrap().fg, Color::Red); // 'x'
+
+    t.remove_blame();
+    let b = render(&t, 6, 2);
+    assert_eq!(b.cell((1, 0)).unwrap().fg, Color::Reset);
+}
+
+#[test]
+fn blame_without_doc() {
+    use ratatui::style::{Color, Style};
+
+    let mut t = MergeArea::with_value("abc");
+    t.set_blame_style(|_| Style::default().fg(Color::Red));
+    let b = render(&t, 3, 1);
+    assert_eq!(b.cell((1, 0)).unwrap().fg, Color::Reset);
+}
+
+#[test]
+fn diff_between_heads() {
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui_mergearea::DiffView;
+
+    let (mut doc, obj) = doc_with_text("one\ntwo\nthree");
+    let before = doc.get_heads();
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.set_line_number_style(Style::default());
+    t.move_cursor(CursorMove::Jump(0, 3));
+    t.delete_next_char(); // Join "one" and "two"
+    merge_remote(&mut t, |d, o| d.splice_text(o, 12, 0, "\nfour").unwrap());
+    assert_eq!(t.text().as_str(), "onetwo\nthree\nfour");
+    let after = t.doc_mut().unwrap().get_heads();
+
+    let view = DiffView::new(&mut t, &before, &after).unwrap();
+    assert_eq!(view.line_count(), 4);
+    let b = render(&view, 9, 4);
+    let lines: Vec<_> = (0..4).map(|y| row(&b, y)).collect();
+    assert_eq!(lines, [" 1 one   ", " 2 two   ", " 3 three ", " 4 four  "]);
+    assert!(b[(6, 0)].modifier.contains(Modifier::CROSSED_OUT)); // Deleted newline
+    assert_eq!(b[(3, 1)].fg, Color::Reset); // 't'
+    assert_eq!(b[(8, 2)].fg, Color::Green); // Inserted newline
+    assert_eq!(b[(3, 3)].fg, Color::Green); // 'f'
+
+    // Scroll is clamped to the last row
+    let view = view
+        .scroll((10, 0))
+        .insert_style(Style::default().bg(Color::Blue));
+    let b = render(&view, 9, 2);
+    assert_eq!(b[(1, 0)].symbol(), "3");
+    assert_eq!(b[(3, 1)].bg, Color::Blue); // 'f'
+
+    // Unknown heads
+    let mut other = AutoCommit::new();
+    other.put(ROOT, "a", 1).unwrap();
+    let unknown = other.get_heads();
+    assert!(DiffView::new(&mut t, &before, &unknown).is_err());
+}
+
+#[test]
+fn diff_without_doc() {
+    use ratatui::style::Color;
+    use ratatui_mergearea::DiffView;
+
+    let mut t = MergeArea::with_value("abc");
+    let view = DiffView::new(&mut t, &[], &[]).unwrap();
+    let b = render(&view, 3, 1);
+    assert_eq!(b, Buffer::with_lines(["abc"]));
+    assert_eq!(b[(0, 0)].fg, Color::Reset);
+}
+
+#[test]
+fn marks_are_merged_and_rendered() {
+    use automerge::marks::{ExpandMark, Mark};
+    use ratatui::style::{Color, Modifier, Style};
+
+    let (doc, obj) = doc_with_text("abcdef");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.set_cursor_style(Style::default());
+    t.set_cursor_line_style(Style::default());
+    t.set_mark_style(|name, value| match name {
+        "bold" => Style::default().add_modifier(Modifier::BOLD),
+        "color" if value.to_str() == Some("red") => Style::default().fg(Color::Red),
+        _ => Style::default(),
+    });
+
+    t.move_cursor(CursorMove::Jump(0, 1));
+    t.start_selection();
+    t.move_cursor(CursorMove::Jump(0, 4));
+    t.set_mark(t.selection_range().unwrap(), "bold", true)
+        .unwrap();
+    t.cancel_selection();
+    t.move_cursor(CursorMove::Head);
+    merge_remote(&mut t, |d, o| {
+        let mark = Mark::new("color".to_string(), "red", 3, 6);
+        d.mark(o, mark, ExpandMark::None).unwrap();
+    });
+
+    let styles = |t: &MergeArea<'_>| {
+        let b = render(t, 6, 1);
+        (0..6)
+            .map(|x| (b[(x, 0)].modifier.contains(Modifier::BOLD), b[(x, 0)].fg))
+            .collect::<Vec<_>>()
+    };
+    let (n, bold, red, reset) = (false, true, Color::Red, Color::Reset);
+    assert_eq!(
+        styles(&t),
+        [
+            (n, reset),
+            (bold, reset),
+            (bold, reset),
+            (bold, red),
+            (n, red),
+            (n, red)
+        ],
+    );
+
+    // Text typed at the end of the mark is marked as well
+    t.move_cursor(CursorMove::Jump(0, 4));
+    t.insert_char('x');
+    t.move_cursor(CursorMove::Head);
+    assert_eq!(t.text().as_str(), "abcdxef");
+    assert!(styles(&t)[4].0);
+
+    t.remove_mark((0, 7), "bold").unwrap();
+    assert!(styles(&t).iter().all(|(b, _)| !b));
+
+    t.remove_mark_style();
+    assert!(styles(&t).iter().all(|(_, fg)| *fg == reset));
+}
+
+#[test]
+fn marks_without_doc() {
+    let mut t = MergeArea::with_value("abc");
+    assert!(t.set_mark((0, 2), "bold", true).is_ok());
+    assert!(t.remove_mark((0, 2), "bold").is_ok());
+}
+
+#[test]
+fn comments_follow_merges() {
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui_mergearea::Comment;
+
+    let (doc, obj) = doc_with_text("hello world");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.move_cursor(CursorMove::Jump(0, 6));
+    t.start_selection();
+    t.move_cursor(CursorMove::End);
+    t.add_comment(t.selection_range().unwrap(), "c1").unwrap();
+    t.cancel_selection();
+    t.add_comment((0, 8), "c2").unwrap();
+
+    // Concurrent edits before the comment, inside the comment and at the end of the comment
+    merge_remote(&mut t, |d, o| {
+        d.splice_text(o, 0, 0, "oh\n").unwrap();
+        d.splice_text(o, 11, 0, "--").unwrap();
+        d.splice_text(o, 16, 0, "!").unwrap();
+    });
+    assert_eq!(t.text().as_str(), "oh\nhello wo--rld!");
+
+    let c1 = Comment {
+        id: "c1".to_string(),
+        range: (9, 16),
+    };
+    let c2 = Comment {
+        id: "c2".to_string(),
+        range: (3, 11), // Text inserted at the end is not commented
+    };
+    assert_eq!(t.comments(), [c2.clone(), c1.clone()]);
+    let ids = |offset| {
+        t.comments_at(offset)
+            .into_iter()
+            .map(|c| c.id)
+            .collect::<Vec<_>>()
+    };
+    assert!(ids(0).is_empty());
+    assert_eq!(ids(3), ["c2"]);
+    assert_eq!(ids(10), ["c2", "c1"]);
+    assert_eq!(ids(16), ["c1"]); // Just after the commented text
+    assert!(ids(17).is_empty());
+
+    t.set_line_number_style(Style::default());
+    t.set_comment_style(Style::default().fg(Color::Yellow).bg(Color::Blue));
+    t.move_cursor(CursorMove::Top);
+    let b = render(&t, 20, 2);
+    assert_eq!(b[(0, 0)].symbol(), " ");
+    assert_eq!(b[(0, 1)].symbol(), "▎"); // Marker of commented line
+    assert_eq!(b[(0, 1)].fg, Color::Yellow);
+    assert_eq!(b[(3, 0)].bg, Color::Reset); // 'o'
+    assert_eq!(b[(3, 1)].bg, Color::Blue); // 'h'
+    assert_eq!(b[(9, 1)].bg, Color::Blue); // 'w'
+    assert_eq!(b[(15, 1)].bg, Color::Blue); // 'd'
+    assert_eq!(b[(16, 1)].bg, Color::Reset); // '!'
+    assert!(!b[(16, 1)].modifier.contains(Modifier::UNDERLINED));
+
+    t.remove_comment("c2").unwrap();
+    assert_eq!(t.comments(), [c1]);
+}
+
+#[test]
+fn comments_without_doc() {
+    let mut t = MergeArea::with_value("abc");
+    assert!(t.add_comment((0, 2), "c").is_ok());
+    assert!(t.comments().is_empty());
+    assert!(t.comments_at(1).is_empty());
+    assert!(t.remove_comment("c").is_ok());
+}
+
+#[test]
+fn concurrent_edits_are_highlighted() {
+    use ratatui::style::{Color, Style};
+
+    let (doc, obj) = doc_with_text("ab\ncd\nef");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    let mut remote = t.doc_mut().unwrap().fork().with_actor(ActorId::random());
+    let obj = t.text_obj().unwrap().clone();
+
+    // Disabled by default
+    t.move_cursor(CursorMove::Jump(0, 1));
+    t.insert_str("xx");
+    remote.splice_text(&obj, 1, 0, "yy").unwrap();
+    t.merge(&mut remote).unwrap();
+    assert_eq!(t.concurrent_edit_style(), None);
+    assert!(t.concurrent_edits().is_empty());
+
+    let style = Style::default().bg(Color::Red);
+    t.set_concurrent_edit_style(style);
+
+    // Concurrent insertions at the same position of the second line, and an insertion by only the remote peer at
+    // the third line
+    t.move_cursor(CursorMove::Jump(1, 1));
+    t.insert_str("12");
+    remote.splice_text(&obj, 6, 0, "34").unwrap();
+    remote.splice_text(&obj, 11, 0, "56").unwrap();
+    t.merge(&mut remote).unwrap();
+    let text = t.text().as_str().to_string();
+    assert!(
+        text == "axxyyb\nc1234d\ne56f" || text == "ayyxxb\nc3412d\ne56f",
+        "{text:?}",
+    );
+    assert_eq!(t.concurrent_edits(), [(8, 12)]);
+
+    // Merging no change keeps the regions
+    t.merge(&mut remote).unwrap();
+    assert_eq!(t.concurrent_edits(), [(8, 12)]);
+
+    t.move_cursor(CursorMove::Top);
+    let b = render(&t, 8, 3);
+    assert_eq!(b[(1, 0)].bg, Color::Reset); // Edits of previous merges are not highlighted
+    assert_eq!(b[(0, 1)].bg, Color::Reset);
+    for x in 1..5 {
+        assert_eq!(b[(x, 1)].bg, Color::Red);
+    }
+    assert_eq!(b[(5, 1)].bg, Color::Reset);
+    assert_eq!(b[(1, 2)].bg, Color::Reset); // Edited by one peer
+
+    // Jump between the regions
+    t.move_cursor(CursorMove::NextConcurrentEdit);
+    assert_eq!(t.cursor2(), (1, 1));
+    t.move_cursor(CursorMove::NextConcurrentEdit);
+    assert_eq!(t.cursor2(), (1, 1));
+    t.move_cursor(CursorMove::Bottom);
+    t.move_cursor(CursorMove::PrevConcurrentEdit);
+    assert_eq!(t.cursor2(), (1, 1));
+
+    // Regions follow local edits
+    t.move_cursor(CursorMove::Jump(0, 0));
+    t.insert_str("!\n");
+    assert_eq!(t.concurrent_edits(), [(10, 14)]);
+    t.move_cursor(CursorMove::Jump(2, 3));
+    for _ in 0..3 {
+        t.delete_next_char();
+    }
+    assert_eq!(t.concurrent_edits(), [(10, 12)]);
+    t.move_cursor(CursorMove::Jump(2, 1));
+    t.delete_next_char();
+    t.delete_next_char();
+    assert!(t.concurrent_edits().is_empty());
+
+    t.move_cursor(CursorMove::Jump(0, 0));
+    t.insert_str("12");
+    remote.splice_text(&obj, 0, 0, "34").unwrap();
+    t.merge(&mut remote).unwrap();
+    // "!\n" inserted locally is also unknown to the remote peer
+    assert_eq!(t.concurrent_edits(), [(0, 6)]);
+    t.clear_concurrent_edits();
+    assert!(t.concurrent_edits().is_empty());
+
+    t.remove_concurrent_edit_style();
+    assert_eq!(t.concurrent_edit_style(), None);
+}
+
+#[test]
+fn concurrent_edits_without_doc() {
+    use ratatui::style::Style;
+
+    let mut t = MergeArea::with_value("abc");
+    t.set_concurrent_edit_style(Style::default());
+    assert!(t.concurrent_edits().is_empty());
+    t.move_cursor(CursorMove::NextConcurrentEdit);
+    assert_eq!(t.cursor(), 0);
+}
+
+#[cfg(feature = "search")]
+#[test]
+fn replace_all_with_concurrent_edits() {
+    let (doc, obj) = doc_with_text("foo bar foo");
+    let mut t = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    let mut remote = t.doc_mut().unwrap().fork().with_actor(ActorId::random());
+    remote.splice_text(&obj, 4, 0, "!").unwrap();
+
+    t.set_search_pattern("f(o+)").unwrap();
+    assert_eq!(t.replace_all("b${1}m"), 2);
+    assert_eq!(doc_text(&t), "boom bar boom");
+
+    // Only the matched text was replaced, so the concurrent insertion is kept in place
+    t.merge(&mut remote).unwrap();
+    assert_eq!(t.text().as_str(), "boom !bar boom");
+
+    // All replacements are undone at once without reverting the merged text
+    assert!(t.undo());
+    assert_eq!(doc_text(&t), "foo !bar foo");
+    assert!(t.redo());
+    assert_eq!(doc_text(&t), "boom !bar boom");
+    assert_eq!(t.text().as_str(), doc_text(&t));
+}
+
+#[cfg(feature = "search")]
+#[test]
+fn replace_after_doc_mut() {
+    let (doc, obj) = doc_with_text("foo foo");
+    let mut t = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    t.doc_mut().unwrap().splice_text(&obj, 0, 0, "XX").unwrap();
+
+    t.set_search_pattern("foo").unwrap();
+    assert_eq!(t.replace_all("bar"), 2);
+    assert_eq!(t.text().as_str(), "XXbar bar");
+    assert_eq!(doc_text(&t), "XXbar bar");
+
+    t.doc_mut().unwrap().splice_text(&obj, 0, 2, "").unwrap();
+    t.set_search_pattern("bar").unwrap();
+    assert!(t.replace_next("baz"));
+    assert_eq!(t.text().as_str(), "baz bar");
+    assert_eq!(doc_text(&t), "baz bar");
+    assert_eq!(t.cursor2(), (0, 3));
+}
+
+#[cfg(feature = "search")]
+#[test]
+fn search_matches_follow_merge() {
+    let (doc, obj) = doc_with_text("foo bar");
+    let mut t = MergeArea::from_doc(doc, obj).unwrap();
+    t.set_search_pattern("o+").unwrap();
+    assert_eq!(t.search_matches().collect::<Vec<_>>(), [(1, 3)]);
+
+    merge_remote(&mut t, |d, o| d.splice_text(o, 4, 1, "boo").unwrap());
+    assert_eq!(t.text().as_str(), "foo booar");
+    assert_eq!(t.search_matches().collect::<Vec<_>>(), [(1, 3), (5, 7)]);
+
+    let obj = t.text_obj().unwrap().clone();
+    t.doc_mut().unwrap().splice_text(&obj, 0, 3, "").unwrap();
+    t.reload();
+    assert_eq!(t.search_matches().collect::<Vec<_>>(), [(2, 4)]);
+}
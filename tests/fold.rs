@@ -0,0 +1,138 @@
+mod common;
+
+use common::{render, render_rows};
+use ratatui::style::{Color, Style};
+use ratatui_mergearea::{CursorMove, IndentFolds, MergeArea, WrapMode};
+
+fn folds(textarea: &MergeArea<'_>) -> Vec<(usize, usize)> {
+    textarea.folds().iter().map(|f| (f.start, f.end)).collect()
+}
+
+#[test]
+fn placeholder_and_line_numbers() {
+    let mut t = MergeArea::with_value("a\nb\nc\nd\ne");
+    t.set_line_number_style(Style::default());
+    t.fold(1..4);
+    // Line numbers count the hidden lines
+    assert_eq!(
+        render_rows(&t, 16, 3),
+        [" 1 a            ", " 2 b [2 lines]  ", " 5 e            "],
+    );
+
+    t.set_fold_style(Style::default().fg(Color::Blue));
+    assert_eq!(render(&t, 16, 3)[(6, 1)].fg, Color::Blue);
+
+    // Overlapping folds are rendered as one fold
+    t.fold(3..5);
+    assert_eq!(
+        render_rows(&t, 16, 2),
+        [" 1 a            ", " 2 b [3 lines]  "]
+    );
+    t.unfold_all();
+    assert_eq!(
+        render_rows(&t, 16, 2),
+        [" 1 a            ", " 2 b            "]
+    );
+}
+
+#[test]
+fn step_over_folds() {
+    let mut t = MergeArea::with_value("ab\ncd\nef\ngh");
+    t.fold(0..3);
+
+    t.move_cursor(CursorMove::End);
+    t.move_cursor(CursorMove::Forward);
+    assert_eq!(t.cursor2(), (3, 0));
+    t.move_cursor(CursorMove::Back);
+    assert_eq!(t.cursor2(), (0, 2));
+    t.move_cursor(CursorMove::Down);
+    assert_eq!(t.cursor2(), (3, 2));
+    t.move_cursor(CursorMove::Up);
+    assert_eq!(t.cursor2(), (0, 2));
+
+    // Jumping into the hidden lines puts the cursor on the header
+    t.move_cursor(CursorMove::Jump(3, 1));
+    t.move_cursor(CursorMove::Jump(1, 1));
+    assert_eq!(t.cursor2(), (0, 2));
+    t.move_cursor(CursorMove::WordBack);
+    assert_eq!(t.cursor2(), (0, 0));
+
+    // A fold at the end of the text has no line to step to
+    t.unfold(0);
+    t.fold(2..4);
+    t.move_cursor(CursorMove::Jump(2, 2));
+    t.move_cursor(CursorMove::Forward);
+    assert_eq!(t.cursor2(), (2, 2));
+}
+
+#[test]
+fn fold_wrapped_lines() {
+    let mut t = MergeArea::with_value("abcdef\nx\ny");
+    t.set_wrap_mode(WrapMode::Char);
+    t.fold(0..2);
+    assert_eq!(render_rows(&t, 4, 3), ["abcd", "ef [", "y   "]);
+
+    t.move_cursor(CursorMove::Down);
+    assert_eq!(t.cursor2(), (0, 4));
+    t.move_cursor(CursorMove::Down);
+    assert_eq!(t.cursor2(), (2, 0));
+}
+
+#[test]
+fn scroll_over_folds() {
+    let mut t = MergeArea::with_value("0\n1\n2\n3\n4\n5\n6");
+    t.fold(1..5);
+    t.move_cursor(CursorMove::Bottom);
+    // Rows on the screen are 0, 1, 5 and 6
+    assert_eq!(render_rows(&t, 12, 2), ["5           ", "6           "]);
+    t.move_cursor(CursorMove::Up);
+    t.move_cursor(CursorMove::Up);
+    assert_eq!(t.cursor2(), (1, 0));
+    assert_eq!(render_rows(&t, 12, 2), ["1 [3 lines] ", "5           "]);
+}
+
+#[test]
+fn follow_edits() {
+    let mut t = MergeArea::with_value("a\nb\nc\nd");
+    t.fold(1..3);
+
+    t.insert_newline();
+    assert_eq!(folds(&t), [(2, 4)]);
+
+    // Lines inserted in the header are folded
+    t.move_cursor(CursorMove::Jump(2, 1));
+    t.insert_str("\nx");
+    assert_eq!(folds(&t), [(2, 5)]);
+
+    // The fold is dropped when the hidden lines are deleted
+    t.undo();
+    t.move_cursor(CursorMove::Jump(2, 1));
+    t.start_selection();
+    t.move_cursor(CursorMove::Jump(5, 0));
+    t.cut();
+    assert!(t.folds().is_empty());
+}
+
+#[test]
+fn toggle_indent_folds() {
+    let mut t = MergeArea::with_value("fn f() {\n    if x {\n        y();\n    }\n}");
+    assert!(!t.toggle_fold_at_cursor());
+
+    t.set_fold_provider(IndentFolds::default());
+    t.move_cursor(CursorMove::Jump(2, 0));
+    assert!(t.toggle_fold_at_cursor());
+    assert_eq!(folds(&t), [(1, 3)]);
+    assert_eq!(t.cursor2(), (1, 10));
+
+    t.move_cursor(CursorMove::Top);
+    assert_eq!(t.cursor2(), (0, 8)); // The cursor is rendered before the placeholder
+    assert!(t.toggle_fold_at_cursor());
+    assert_eq!(folds(&t), [(1, 3), (0, 4)]);
+    assert_eq!(
+        render_rows(&t, 20, 2),
+        ["fn f() {  [3 lines] ", "}                   "]
+    );
+
+    assert!(t.toggle_fold_at_cursor());
+    assert_eq!(folds(&t), [(1, 3)]);
+}
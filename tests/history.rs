@@ -6,4 +6,5 @@ fn disable_history() {
     t.set_max_histories(0);
     assert!(t.insert_str("hello"));
     assert_eq!(t.text().as_str(), "hello");
+    assert!(!t.undo());
 }
@@ -1,15 +1,13 @@
 #![cfg(feature = "search")]
 
-use ratatui_mergearea::{CursorMove, MergeArea};
+mod common;
+
+use common::render;
+use ratatui_mergearea::{CursorMove, Input, Key, MergeArea, SearchOptions};
 
 #[test]
 fn search_lines_forward() {
-    #[rustfmt::skip]
-    let mut textarea = MergeArea::from([
-        "fooo foo",
-        "foo fo foo fooo",
-        "foooo",
-    ]);
+    let mut textarea = MergeArea::with_value("fooo foo\nfoo fo foo fooo\nfoooo");
 
     // Move to 'f' on 'fo' at line 2
     textarea.move_cursor(CursorMove::Jump(1, 4));
@@ -19,7 +17,7 @@ fn search_lines_forward() {
     let expected = [(1, 7), (1, 11), (2, 0), (0, 0), (0, 5), (1, 0), (1, 4)];
     for (i, pos) in expected.into_iter().enumerate() {
         let moved = textarea.search_forward(false);
-        let cursor = textarea.cursor();
+        let cursor = textarea.cursor2();
         assert!(moved, "{}th move didn't happen: {:?}", i + 1, cursor);
         assert_eq!(pos, cursor, "{}th position is unexpected", i + 1);
     }
@@ -27,12 +25,7 @@ fn search_lines_forward() {
 
 #[test]
 fn search_lines_backward() {
-    #[rustfmt::skip]
-    let mut textarea = MergeArea::from([
-        "fooo foo",
-        "foo fo foo fooo",
-        "foooo",
-    ]);
+    let mut textarea = MergeArea::with_value("fooo foo\nfoo fo foo fooo\nfoooo");
 
     // Move to 'f' on 'fo' at line 2
     textarea.move_cursor(CursorMove::Jump(1, 4));
@@ -42,7 +35,7 @@ fn search_lines_backward() {
     let expected = [(1, 0), (0, 5), (0, 0), (2, 0), (1, 11), (1, 7), (1, 4)];
     for (i, pos) in expected.into_iter().enumerate() {
         let moved = textarea.search_back(false);
-        let cursor = textarea.cursor();
+        let cursor = textarea.cursor2();
         assert!(moved, "{}th move didn't happen: {:?}", i + 1, cursor);
         assert_eq!(pos, cursor, "{}th position is unexpected", i + 1);
     }
@@ -50,7 +43,7 @@ fn search_lines_backward() {
 
 #[test]
 fn search_forward_within_line() {
-    let mut textarea = MergeArea::from(["foo fo foo fooo"]);
+    let mut textarea = MergeArea::with_value("foo fo foo fooo");
 
     // Move to 'f' on 'fo'
     textarea.move_cursor(CursorMove::Jump(0, 4));
@@ -60,7 +53,7 @@ fn search_forward_within_line() {
     let expected = [(0, 7), (0, 11), (0, 0), (0, 4)];
     for (i, pos) in expected.into_iter().enumerate() {
         let moved = textarea.search_forward(false);
-        let cursor = textarea.cursor();
+        let cursor = textarea.cursor2();
         assert!(moved, "{}th move didn't happen: {:?}", i + 1, cursor);
         assert_eq!(pos, cursor, "{}th position is unexpected", i + 1);
     }
@@ -68,7 +61,7 @@ fn search_forward_within_line() {
 
 #[test]
 fn search_backward_within_line() {
-    let mut textarea = MergeArea::from(["foo fo foo fooo"]);
+    let mut textarea = MergeArea::with_value("foo fo foo fooo");
 
     // Move to 'f' on 'fo'
     textarea.move_cursor(CursorMove::Jump(0, 4));
@@ -78,7 +71,7 @@ fn search_backward_within_line() {
     let expected = [(0, 0), (0, 11), (0, 7), (0, 4)];
     for (i, pos) in expected.into_iter().enumerate() {
         let moved = textarea.search_back(false);
-        let cursor = textarea.cursor();
+        let cursor = textarea.cursor2();
         assert!(moved, "{}th move didn't happen: {:?}", i + 1, cursor);
         assert_eq!(pos, cursor, "{}th position is unexpected", i + 1);
     }
@@ -86,7 +79,7 @@ fn search_backward_within_line() {
 
 #[test]
 fn search_not_found() {
-    let mut textarea = MergeArea::from(["fo fo fo fo"]);
+    let mut textarea = MergeArea::with_value("fo fo fo fo");
     textarea.set_search_pattern("foo+").unwrap();
 
     assert!(!textarea.search_forward(false));
@@ -95,19 +88,19 @@ fn search_not_found() {
 
 #[test]
 fn accept_cursor_position() {
-    let mut textarea = MergeArea::from(["foooo fooooooo"]);
+    let mut textarea = MergeArea::with_value("foooo fooooooo");
     textarea.set_search_pattern("foo+").unwrap();
 
-    let cursor = textarea.cursor();
+    let cursor = textarea.cursor2();
     assert!(textarea.search_forward(true));
-    assert_eq!(textarea.cursor(), cursor);
+    assert_eq!(textarea.cursor2(), cursor);
     assert!(textarea.search_back(true));
-    assert_eq!(textarea.cursor(), cursor);
+    assert_eq!(textarea.cursor2(), cursor);
 }
 
 #[test]
 fn set_search_pattern() {
-    let mut textarea = MergeArea::from(["foo"]);
+    let mut textarea = MergeArea::with_value("foo");
 
     assert!(textarea.search_pattern().is_none());
     assert!(!textarea.search_forward(true));
@@ -130,3 +123,246 @@ fn set_search_pattern() {
     assert!(!textarea.search_back(true));
     assert!(!textarea.search_back(false));
 }
+
+#[test]
+fn search_across_lines() {
+    let mut textarea = MergeArea::with_value("foo\nbar\nfoo\nbar");
+    textarea.set_search_pattern("o\nb").unwrap();
+
+    let expected = [(0, 2), (2, 2), (0, 2)];
+    for (i, pos) in expected.into_iter().enumerate() {
+        assert!(textarea.search_forward(false));
+        assert_eq!(
+            pos,
+            textarea.cursor2(),
+            "{}th position is unexpected",
+            i + 1
+        );
+    }
+
+    assert!(textarea.search_back(false));
+    assert_eq!(textarea.cursor2(), (2, 2));
+}
+
+#[test]
+fn highlight_matches() {
+    use ratatui::style::Color;
+
+    let mut textarea = MergeArea::with_value("foo\nbar\nbaz");
+    textarea.set_search_pattern("o\nba").unwrap();
+    textarea.move_cursor(CursorMove::Bottom);
+
+    let b = render(&textarea, 5, 3);
+
+    let bg = |x, y| b.cell((x, y)).unwrap().bg;
+    assert_eq!(bg(1, 0), Color::Reset);
+    assert_eq!(bg(2, 0), Color::Blue);
+    assert_eq!(bg(3, 0), Color::Blue); // Newline in the match
+    assert_eq!(bg(0, 1), Color::Blue);
+    assert_eq!(bg(1, 1), Color::Blue);
+    assert_eq!(bg(2, 1), Color::Reset);
+    assert_eq!(bg(3, 1), Color::Reset);
+    assert_eq!(bg(1, 2), Color::Reset);
+}
+
+#[test]
+fn replace_next_wraps() {
+    let mut textarea = MergeArea::with_value("🐱 cat\n🐶 dog\n🐱 cat");
+    textarea.set_search_pattern("cat").unwrap();
+    textarea.move_cursor(CursorMove::Jump(1, 0));
+
+    assert!(textarea.replace_next("ねこ"));
+    assert_eq!(textarea.text().as_str(), "🐱 cat\n🐶 dog\n🐱 ねこ");
+    assert_eq!(textarea.cursor2(), (2, 4));
+
+    assert!(textarea.replace_next("ねこ"));
+    assert_eq!(textarea.text().as_str(), "🐱 ねこ\n🐶 dog\n🐱 ねこ");
+    assert_eq!(textarea.cursor2(), (0, 4));
+
+    assert!(!textarea.replace_next("ねこ"));
+
+    assert!(textarea.undo());
+    assert_eq!(textarea.text().as_str(), "🐱 cat\n🐶 dog\n🐱 ねこ");
+    assert_eq!(textarea.cursor2(), (0, 2));
+    assert!(textarea.redo());
+    assert_eq!(textarea.text().as_str(), "🐱 ねこ\n🐶 dog\n🐱 ねこ");
+}
+
+#[test]
+fn replace_all_keeps_cursor() {
+    let tests = [
+        // (cursor, pattern, replacement, text after replacement, cursor after replacement)
+        ((0, 0), "o+", "0", "f0 b0\nb0", (0, 0)),
+        ((0, 4), "o+", "0", "f0 b0\nb0", (0, 3)), // In a match
+        ((1, 0), "o+", "0", "f0 b0\nb0", (1, 0)),
+        ((1, 3), "o+", "0", "f0 b0\nb0", (1, 2)), // At the end of text
+        ((1, 0), "o+\n", "", "foo bboo", (0, 5)), // Match across lines
+        ((0, 3), "o+", "", "f b\nb", (0, 1)),
+        ((0, 0), "x", "y", "foo boo\nboo", (0, 0)), // No match
+    ];
+
+    for (cursor, pat, rep, want, moved) in tests {
+        let mut textarea = MergeArea::with_value("foo boo\nboo");
+        textarea.move_cursor(CursorMove::Jump(cursor.0, cursor.1));
+        textarea.set_search_pattern(pat).unwrap();
+
+        textarea.replace_all(rep);
+        let ctx = (cursor, pat, rep);
+        assert_eq!(textarea.text().as_str(), want, "{ctx:?}");
+        assert_eq!(textarea.cursor2(), moved, "{ctx:?}");
+
+        textarea.undo();
+        assert_eq!(textarea.text().as_str(), "foo boo\nboo", "{ctx:?}");
+    }
+}
+
+fn key(key: Key) -> Input {
+    Input {
+        key,
+        ..Default::default()
+    }
+}
+
+fn ctrl(c: char) -> Input {
+    Input {
+        key: Key::Char(c),
+        ctrl: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn isearch_cycle_and_cancel() {
+    let mut textarea = MergeArea::with_value("a.b axb\na.b");
+    textarea.set_search_pattern("x").unwrap();
+    textarea.move_cursor(CursorMove::Jump(0, 2));
+
+    assert!(!textarea.input_emacs(ctrl('s')));
+    for c in "a.".chars() {
+        assert!(!textarea.input_emacs(key(Key::Char(c))));
+    }
+    // The query is matched literally
+    assert_eq!(textarea.isearch_query(), Some("a."));
+    assert_eq!(textarea.search_match_count(), 2);
+    assert_eq!(textarea.cursor2(), (1, 0));
+
+    textarea.input_emacs(ctrl('s')); // Wrap
+    assert_eq!(textarea.cursor2(), (0, 0));
+    textarea.input_emacs(ctrl('r'));
+    assert!(textarea.is_isearch_backward());
+    assert_eq!(textarea.cursor2(), (1, 0));
+
+    // Shrinking the query searches again from the start position
+    textarea.input_emacs(key(Key::Backspace));
+    assert_eq!(textarea.isearch_query(), Some("a"));
+    assert_eq!(textarea.cursor2(), (0, 0));
+
+    textarea.input_emacs(key(Key::Esc));
+    assert_eq!(textarea.isearch_query(), None);
+    assert!(!textarea.is_isearch_backward());
+    assert_eq!(textarea.cursor2(), (0, 2));
+    assert_eq!(textarea.search_pattern().unwrap().as_str(), "x");
+    assert_eq!(textarea.text().as_str(), "a.b axb\na.b");
+}
+
+#[test]
+fn isearch_ends_with_other_keys() {
+    let mut textarea = MergeArea::with_value("foo bar");
+
+    textarea.input_emacs(ctrl('s'));
+    textarea.input_emacs(key(Key::Char('b')));
+    assert_eq!(textarea.cursor2(), (0, 4));
+
+    // The key ending the search is handled as usual
+    assert!(textarea.input_emacs(ctrl('d')));
+    assert_eq!(textarea.isearch_query(), None);
+    assert!(textarea.search_pattern().is_none());
+    assert_eq!(textarea.text().as_str(), "foo ar");
+
+    // Not found
+    textarea.input_emacs(ctrl('s'));
+    textarea.input_emacs(key(Key::Char('x')));
+    assert_eq!(textarea.search_match_count(), 0);
+    assert_eq!(textarea.cursor2(), (0, 4));
+    textarea.input_emacs(ctrl('g'));
+    assert_eq!(textarea.isearch_query(), None);
+}
+
+#[test]
+fn highlight_current_match() {
+    use ratatui::style::Color;
+
+    let mut textarea = MergeArea::with_value("ab ab ab");
+    textarea.input_emacs(ctrl('s'));
+    textarea.input_emacs(key(Key::Char('a')));
+    textarea.input_emacs(key(Key::Char('b')));
+    textarea.input_emacs(ctrl('s'));
+    assert_eq!(textarea.cursor2(), (0, 3));
+
+    let b = render(&textarea, 8, 1);
+
+    let bg = |x| b.cell((x, 0)).unwrap().bg;
+    assert_eq!(bg(0), Color::Blue);
+    assert_eq!(bg(1), Color::Blue);
+    assert_eq!(bg(4), Color::Yellow); // The cursor is at (0, 3)
+    assert_eq!(bg(6), Color::Blue);
+}
+
+#[test]
+fn isearch_with_options() {
+    let mut textarea = MergeArea::with_value("a+b A+B");
+    let opts = SearchOptions::default().case_insensitive(true);
+    textarea.set_search_options(opts).unwrap();
+    textarea.set_search_pattern("b+").unwrap();
+    textarea.move_cursor(CursorMove::End);
+
+    // The query is matched literally while the case is ignored
+    textarea.input_emacs(ctrl('s'));
+    for c in "A+".chars() {
+        textarea.input_emacs(key(Key::Char(c)));
+    }
+    assert_eq!(textarea.search_match_count(), 2);
+    assert_eq!(textarea.cursor2(), (0, 0));
+
+    textarea.input_emacs(key(Key::Enter));
+    assert_eq!(textarea.search_options(), opts);
+    assert_eq!(textarea.search_pattern().unwrap().as_str(), "b+");
+    assert_eq!(textarea.search_match_count(), 2);
+}
+
+#[test]
+fn search_matches_follow_edits() {
+    let mut textarea = MergeArea::with_value("ab\nab");
+    assert_eq!(textarea.search_matches().count(), 0);
+    assert_eq!(textarea.current_match_index(), None);
+
+    textarea.set_search_pattern("ab").unwrap();
+    assert_eq!(
+        textarea.search_matches().collect::<Vec<_>>(),
+        [(0, 2), (3, 5)]
+    );
+    assert_eq!(textarea.current_match_index(), Some((1, 2)));
+
+    textarea.insert_str("ab");
+    assert_eq!(
+        textarea.search_matches().collect::<Vec<_>>(),
+        [(0, 2), (2, 4), (5, 7)],
+    );
+    assert_eq!(textarea.current_match_index(), Some((2, 3)));
+
+    textarea.undo();
+    assert_eq!(textarea.search_match_count(), 2);
+    textarea.set_search_pattern("b\na").unwrap();
+    assert_eq!(textarea.search_matches().collect::<Vec<_>>(), [(1, 4)]);
+    assert_eq!(textarea.current_match_index(), None);
+    textarea.move_cursor(CursorMove::Jump(1, 0));
+    assert_eq!(textarea.current_match_index(), Some((1, 1)));
+
+    // Incremental search is reflected as well
+    textarea.input_emacs(ctrl('s'));
+    assert_eq!(textarea.search_match_count(), 0);
+    textarea.input_emacs(key(Key::Char('a')));
+    assert_eq!(textarea.current_match_index(), Some((2, 2)));
+    textarea.input_emacs(key(Key::Esc));
+    assert_eq!(textarea.search_matches().collect::<Vec<_>>(), [(1, 4)]);
+}
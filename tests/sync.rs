@@ -0,0 +1,165 @@
+#![cfg(feature = "sync")]
+
+use automerge::transaction::Transactable as _;
+use automerge::{ActorId, AutoCommit, ObjType, ROOT, ReadDoc as _};
+use ratatui_mergearea::{CursorMove, SyncError, SyncSession};
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+struct Peer {
+    session: SyncSession<'static>,
+    tx: Sender<Vec<u8>>,
+    rx: Receiver<Vec<u8>>,
+}
+
+fn connected_peers(text: &str) -> (Peer, Peer) {
+    let mut doc = AutoCommit::new();
+    let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    doc.splice_text(&obj, 0, 0, text).unwrap();
+    let fork = doc.fork().with_actor(ActorId::random());
+
+    let (left_tx, right_rx) = channel();
+    let (right_tx, left_rx) = channel();
+    let left = Peer {
+        session: SyncSession::new(doc, obj.clone()).unwrap(),
+        tx: left_tx,
+        rx: left_rx,
+    };
+    let right = Peer {
+        session: SyncSession::new(fork, obj).unwrap(),
+        tx: right_tx,
+        rx: right_rx,
+    };
+    (left, right)
+}
+
+// Exchange messages over the channels until both peers have nothing to send
+fn sync(left: &mut Peer, right: &mut Peer) {
+    loop {
+        let mut sent = false;
+        for p in [&mut *left, &mut *right] {
+            if let Some(msg) = p.session.generate_message() {
+                p.tx.send(msg).unwrap();
+                sent = true;
+            }
+        }
+        for p in [&mut *left, &mut *right] {
+            while let Ok(msg) = p.rx.try_recv() {
+                p.session.receive_message(&msg).unwrap();
+            }
+        }
+        if !sent {
+            return;
+        }
+    }
+}
+
+fn text(p: &Peer) -> &str {
+    p.session.textarea().text().as_str()
+}
+
+#[test]
+fn sync_local_edits() {
+    let (mut left, mut right) = connected_peers("");
+    left.session.textarea_mut().insert_str("hello");
+    sync(&mut left, &mut right);
+    assert_eq!(text(&right), "hello");
+
+    right.session.textarea_mut().move_cursor(CursorMove::End);
+    right.session.textarea_mut().insert_str(" world");
+    sync(&mut left, &mut right);
+    assert_eq!(text(&left), "hello world");
+
+    let t = left.session.textarea();
+    let doc_text = t.doc().unwrap().text(t.text_obj().unwrap()).unwrap();
+    assert_eq!(doc_text, "hello world");
+}
+
+#[test]
+fn sync_concurrent_edits_keep_cursors() {
+    let (mut left, mut right) = connected_peers("The fox jumped.");
+    sync(&mut left, &mut right);
+
+    let l = left.session.textarea_mut();
+    l.move_cursor(CursorMove::WordForward); // At 'f'
+    l.insert_str("quick ");
+    let r = right.session.textarea_mut();
+    r.move_cursor(CursorMove::End);
+    r.move_cursor(CursorMove::Back); // At '.'
+    r.insert_str(" high");
+
+    sync(&mut left, &mut right);
+    assert_eq!(text(&left), "The quick fox jumped high.");
+    assert_eq!(text(&right), "The quick fox jumped high.");
+    assert_eq!(left.session.textarea().cursor(), 10); // Still at 'f'
+    assert_eq!(right.session.textarea().cursor(), 25); // Still at '.'
+}
+
+#[test]
+fn sync_after_doc_mut() {
+    let (mut left, mut right) = connected_peers("abc");
+    sync(&mut left, &mut right);
+
+    // The local edit after lending out the document is not applied again
+    left.session.textarea_mut().doc_mut().unwrap();
+    left.session.textarea_mut().insert_str("L");
+    right.session.textarea_mut().insert_str("R");
+    sync(&mut left, &mut right);
+    let t = left.session.textarea();
+    let doc_text = t.doc().unwrap().text(t.text_obj().unwrap()).unwrap();
+    assert_eq!(text(&left), doc_text);
+    assert_eq!(text(&right), doc_text);
+
+    // Modifications made through the document are applied on sync without reloading
+    let t = left.session.textarea_mut();
+    let obj = t.text_obj().unwrap().clone();
+    t.doc_mut().unwrap().splice_text(&obj, 0, 0, "D").unwrap();
+    right.session.textarea_mut().insert_str("E");
+    sync(&mut left, &mut right);
+    let t = left.session.textarea();
+    let doc_text = t.doc().unwrap().text(t.text_obj().unwrap()).unwrap();
+    assert_eq!(doc_text.len(), 7);
+    assert_eq!(text(&left), doc_text);
+    assert_eq!(text(&right), doc_text);
+}
+
+#[test]
+fn sync_after_reset() {
+    let (mut left, mut right) = connected_peers("abc");
+    sync(&mut left, &mut right);
+
+    // Reconnect after the right peer edited offline
+    right.session.textarea_mut().insert_str("xyz");
+    left.session.reset();
+    right.session.reset();
+    sync(&mut left, &mut right);
+    assert_eq!(text(&left), "xyzabc");
+}
+
+#[test]
+fn receive_invalid_message() {
+    let (mut left, _) = connected_peers("abc");
+    let err = left.session.receive_message(&[1, 2, 3]).unwrap_err();
+    assert!(matches!(err, SyncError::Decode(_)), "{err:?}");
+    assert_eq!(text(&left), "abc");
+}
+
+#[test]
+fn sync_detects_concurrent_edits() {
+    use ratatui::style::Style;
+
+    let (mut left, mut right) = connected_peers("ab");
+    sync(&mut left, &mut right);
+    left.session
+        .textarea_mut()
+        .set_concurrent_edit_style(Style::default());
+
+    for (p, s) in [(&mut left, "xy"), (&mut right, "z")] {
+        let t = p.session.textarea_mut();
+        t.move_cursor(CursorMove::Forward);
+        t.insert_str(s);
+    }
+    sync(&mut left, &mut right);
+    assert_eq!(text(&left).len(), 5);
+    assert_eq!(left.session.textarea().concurrent_edits(), [(1, 4)]);
+    assert!(right.session.textarea().concurrent_edits().is_empty());
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::render;
+use ratatui::style::{Color, Style};
+use ratatui_mergearea::{CursorMove, Highlighter, MergeArea};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+fn digits(line: &str, _row: usize) -> Vec<(Range<usize>, Style)> {
+    line.char_indices()
+        .filter(|(_, c)| c.is_ascii_digit())
+        .map(|(i, _)| (i..i + 1, Style::default().fg(Color::Cyan)))
+        .collect()
+}
+
+#[test]
+fn highlight_beneath_selection() {
+    let mut textarea = MergeArea::with_value("a1b2\nあ3");
+    textarea.set_highlighter(digits);
+    textarea.set_selection_style(Style::default().fg(Color::Blue));
+    textarea.move_cursor(CursorMove::Down);
+    textarea.start_selection();
+    textarea.move_cursor(CursorMove::End);
+
+    let buf = render(&textarea, 5, 2);
+    assert_eq!(buf[(0, 0)].fg, Color::Reset);
+    assert_eq!(buf[(1, 0)].fg, Color::Cyan);
+    assert_eq!(buf[(3, 0)].fg, Color::Cyan);
+    // Chars are counted in display width
+    assert_eq!(buf[(2, 1)].fg, Color::Blue);
+}
+
+#[test]
+fn highlight_lines_in_order() {
+    // Records the rows passed to a stateful highlighter
+    struct Rows(Arc<Mutex<Vec<usize>>>);
+    impl Highlighter for Rows {
+        fn highlight(&self, line: &str, row: usize) -> Vec<(Range<usize>, Style)> {
+            self.0.lock().unwrap().push(row);
+            digits(line, row)
+        }
+    }
+
+    let rows = Arc::new(Mutex::new(vec![]));
+    let mut textarea = MergeArea::with_value("1\n2\n3\n4\n5");
+    textarea.set_highlighter(Rows(rows.clone()));
+
+    textarea.move_cursor(CursorMove::Jump(2, 0));
+    render(&textarea, 3, 2);
+    let buf = render(&textarea, 3, 2);
+    assert_eq!(buf[(0, 0)].fg, Color::Cyan);
+
+    // Lines above the viewport are passed as well, but not lines below it
+    assert_eq!(rows.lock().unwrap().as_slice(), [0, 1, 2, 0, 1, 2]);
+
+    // Closures are stateless, so only the visible lines are passed
+    rows.lock().unwrap().clear();
+    let seen = rows.clone();
+    textarea.set_highlighter(move |line: &str, row: usize| {
+        seen.lock().unwrap().push(row);
+        digits(line, row)
+    });
+    let buf = render(&textarea, 3, 2);
+    assert_eq!(buf[(0, 0)].fg, Color::Cyan);
+    assert_eq!(rows.lock().unwrap().as_slice(), [1, 2]);
+}
+
+#[cfg(feature = "syntect")]
+#[test]
+fn syntect_state_across_lines() {
+    use ratatui_mergearea::{Highlighter as _, SyntectHighlighter};
+
+    let hl = SyntectHighlighter::new("rs");
+    let code = hl.highlight("let x = 1;", 0);
+
+    // A block comment continues on the next line
+    let first = hl.highlight("/* a", 0);
+    let second = hl.highlight("let x = 1; */", 1);
+    let comment = first[0].1;
+    assert!(second.iter().all(|(_, style)| *style == comment));
+    assert!(code.iter().all(|(_, style)| *style != comment));
+
+    // The cached state is discarded when the previous line is modified
+    hl.highlight("// a", 0);
+    assert_eq!(hl.highlight("let x = 1;", 1), code);
+}
@@ -15,9 +15,9 @@ fn assert_undo_redo<T: Debug>(
     assert_eq!(t.undo(), modified, "undo modification: {context:?}");
     assert_eq!(t.text().as_str(), before_buf, "buf after undo: {context:?}");
     assert_eq!(t.cursor2(), before_pos, "pos after undo: {context:?}");
-    assert_eq!(t.redo_v2(), modified, "redo modification: {context:?}");
+    assert_eq!(t.redo(), modified, "redo modification: {context:?}");
     assert_eq!(t.text().as_str(), after_buf, "buf after redo: {context:?}");
-    // assert_eq!(t.cursor2(), after_pos, "pos after redo: {context:?}");
+    assert_eq!(t.cursor2(), after_pos, "pos after redo: {context:?}");
 }
 
 fn assert_no_undo_redo<T: Debug>(t: &mut MergeArea<'_>, context: T) {
@@ -30,7 +30,7 @@ fn assert_no_undo_redo<T: Debug>(t: &mut MergeArea<'_>, context: T) {
         "buf after undo: {context:?}"
     );
     assert_eq!(t.cursor2(), pos, "pos after undo: {context:?}");
-    assert!(!t.redo_v2(), "redo modification: {context:?}");
+    assert!(!t.redo(), "redo modification: {context:?}");
     assert_eq!(
         t.text().as_str(),
         buf.as_str(),
@@ -933,7 +933,8 @@ fn test_delete_str_multiple_lines() {
         let mut t = MergeArea::with_value(&before);
         t.move_cursor(CursorMove::Jump(row as _, col as _));
 
-        assert!(t.delete_str(chars), "{test:?}");
+        // Nothing is deleted at the end of the text
+        assert_eq!(t.delete_str(chars), !deleted.is_empty(), "{test:?}");
         assert_eq!(t.cursor2(), (row, col), "{test:?}");
         assert_eq!(t.text().as_str(), after, "{test:?}");
         assert_eq!(t.yank_text(), deleted, "{test:?}");
@@ -966,365 +967,253 @@ fn test_copy_single_line() {
     }
 }
 
-// #[test]
-// fn test_cut_single_line() {
-//     for i in 0.."abc".len() {
-//         for j in i + 1.."abc".len() {
-//             let mut t = MergeArea::with_value("abc");
-
-//             t.move_cursor(CursorMove::Jump(0, i as u16));
-//             t.start_selection_v2();
-//             t.move_cursor(CursorMove::Jump(0, j as u16));
-//             t.cut();
-
-//             assert_eq!(t.yank_text(), &"abc"[i..j], "from {i} to {j}");
-
-//             let mut after = "abc".to_string();
-//             after.replace_range(i..j, "");
-//             let after = after.as_str();
-//             assert_eq!(t.text().as_str(), after, "from {i} to {j}");
-//             assert_eq!(t.cursor2(), (0, i));
-//             assert_undo_redo((0, j), "abc", after, &mut t, (i, j));
-
-//             t.paste();
-//             assert_eq!(t.text().as_str(), "abc", "from {i} to {j}");
-//             assert_undo_redo((0, i), after, "abc", &mut t, (i, j));
-//         }
-//     }
-// }
-
-// #[test]
-// fn test_copy_cut_empty() {
-//     for row in 0..=2 {
-//         for col in 0..=2 {
-//             let check = |f: fn(&mut MergeArea<'_>)| {
-//                 let mut t = MergeArea::from(["ab", "cd", "ef"]);
-//                 t.move_cursor(CursorMove::Jump(row, col));
-//                 t.start_selection();
-//                 t.move_cursor(CursorMove::Jump(row, col));
-//                 f(&mut t);
-//                 assert!(!t.is_selecting());
-//                 assert_eq!(t.cursor(), (row as _, col as _));
-//                 assert_eq!(t.lines(), ["ab", "cd", "ef"]);
-//                 assert_no_undo_redo(&mut t, "");
-//             };
-
-//             check(|t| {
-//                 assert!(!t.cut());
-//             });
-//             check(|t| t.copy());
-//         }
-//     }
-// }
-
-// #[test]
-// fn test_copy_cut_paste_multi_lines() {
-//     #[rustfmt::skip]
-//     let tests = [
-//         (
-//             // Initial text
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             // Start position of selection
-//             (0, 0),
-//             // End position of selection
-//             (1, 0),
-//             // Expected yanked text
-//             "ab\n",
-//             // Text buffer after cut
-//             &[
-//                 "cd",
-//                 "ef",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 0),
-//             (1, 1),
-//             "ab\nc",
-//             &[
-//                 "d",
-//                 "ef",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 0),
-//             (1, 2),
-//             "ab\ncd",
-//             &[
-//                 "",
-//                 "ef",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 0),
-//             (2, 0),
-//             "ab\ncd\n",
-//             &[
-//                 "ef",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 0),
-//             (2, 1),
-//             "ab\ncd\ne",
-//             &[
-//                 "f",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 0),
-//             (2, 2),
-//             "ab\ncd\nef",
-//             &[
-//                 "",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 1),
-//             (1, 1),
-//             "b\nc",
-//             &[
-//                 "ad",
-//                 "ef",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 2),
-//             (1, 1),
-//             "\nc",
-//             &[
-//                 "abd",
-//                 "ef",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (1, 0),
-//             (2, 1),
-//             "cd\ne",
-//             &[
-//                 "ab",
-//                 "f",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 2),
-//             (1, 0),
-//             "\n",
-//             &[
-//                 "abcd",
-//                 "ef",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "ab",
-//                 "cd",
-//                 "ef",
-//             ][..],
-//             (0, 2),
-//             (2, 0),
-//             "\ncd\n",
-//             &[
-//                 "abef",
-//             ][..]
-//         ),
-//         // Multi-byte characters
-//         (
-//             &[
-//                 "あい",
-//                 "うえ",
-//                 "おか",
-//             ][..],
-//             (0, 0),
-//             (2, 2),
-//             "あい\nうえ\nおか",
-//             &[
-//                 "",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "あい",
-//                 "うえ",
-//                 "おか",
-//             ][..],
-//             (0, 1),
-//             (2, 1),
-//             "い\nうえ\nお",
-//             &[
-//                 "あか",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "あい",
-//                 "うえ",
-//                 "おか",
-//             ][..],
-//             (0, 2),
-//             (2, 0),
-//             "\nうえ\n",
-//             &[
-//                 "あいおか",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "あい",
-//                 "うえ",
-//                 "おか",
-//             ][..],
-//             (0, 2),
-//             (1, 2),
-//             "\nうえ",
-//             &[
-//                 "あい",
-//                 "おか",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "あい",
-//                 "うえ",
-//                 "おか",
-//             ][..],
-//             (0, 2),
-//             (1, 1),
-//             "\nう",
-//             &[
-//                 "あいえ",
-//                 "おか",
-//             ][..]
-//         ),
-//         (
-//             &[
-//                 "あい",
-//                 "うえ",
-//                 "おか",
-//             ][..],
-//             (0, 2),
-//             (1, 0),
-//             "\n",
-//             &[
-//                 "あいうえ",
-//                 "おか",
-//             ][..]
-//         ),
-//     ];
-
-//     for test in tests {
-//         let (init_text, (srow, scol), (erow, ecol), yanked, after_cut) = test;
-
-//         {
-//             let mut t = MergeArea::from(init_text.iter().map(|s| s.to_string()));
-//             t.move_cursor(CursorMove::Jump(srow as _, scol as _));
-//             t.start_selection();
-//             t.move_cursor(CursorMove::Jump(erow as _, ecol as _));
-//             t.copy();
-
-//             assert_eq!(t.cursor(), (erow, ecol), "{test:?}");
-//             assert_eq!(t.yank_text(), yanked, "{test:?}");
-//             assert_eq!(t.lines(), init_text, "{test:?}");
-//             assert_no_undo_redo(&mut t, test);
-//         }
-
-//         {
-//             let mut t = MergeArea::from(init_text.iter().map(|s| s.to_string()));
-//             t.move_cursor(CursorMove::Jump(srow as _, scol as _));
-//             t.start_selection();
-//             t.move_cursor(CursorMove::Jump(erow as _, ecol as _));
-//             t.cut();
-
-//             assert_eq!(t.cursor(), (srow, scol), "{test:?}");
-//             assert_eq!(t.yank_text(), yanked, "{test:?}");
-//             assert_eq!(t.lines(), after_cut, "{test:?}");
-//             assert_undo_redo((erow, ecol), init_text, after_cut, &mut t, test);
-
-//             t.paste();
-//             assert_eq!(t.lines(), init_text, "{test:?}");
-//             assert_undo_redo((srow, scol), after_cut, init_text, &mut t, test);
-//         }
-
-//         // Reverse positions
-//         {
-//             let mut t = MergeArea::from(init_text.iter().map(|s| s.to_string()));
-//             t.move_cursor(CursorMove::Jump(erow as _, ecol as _));
-//             t.start_selection();
-//             t.move_cursor(CursorMove::Jump(srow as _, scol as _));
-//             t.copy();
-
-//             assert_eq!(t.cursor(), (srow, scol), "{test:?}");
-//             assert_eq!(t.yank_text(), yanked, "{test:?}");
-//             assert_eq!(t.lines(), init_text, "{test:?}");
-//             assert_no_undo_redo(&mut t, test);
-//         }
-
-//         {
-//             let mut t = MergeArea::from(init_text.iter().map(|s| s.to_string()));
-//             t.move_cursor(CursorMove::Jump(erow as _, ecol as _));
-//             t.start_selection();
-//             t.move_cursor(CursorMove::Jump(srow as _, scol as _));
-//             t.cut();
-
-//             assert_eq!(t.cursor(), (srow, scol), "{test:?}");
-//             assert_eq!(t.yank_text(), yanked, "{test:?}");
-//             assert_eq!(t.lines(), after_cut, "{test:?}");
-//             assert_undo_redo((erow, ecol), init_text, after_cut, &mut t, test);
-
-//             t.paste();
-//             assert_eq!(t.lines(), init_text, "{test:?}");
-//             assert_undo_redo((srow, scol), after_cut, init_text, &mut t, test);
-//         }
-//     }
-// }
+#[test]
+fn test_cut_single_line() {
+    for i in 0.."abc".len() {
+        for j in i + 1.."abc".len() {
+            let mut t = MergeArea::with_value("abc");
+
+            t.move_cursor(CursorMove::Jump(0, i as u16));
+            t.start_selection();
+            t.move_cursor(CursorMove::Jump(0, j as u16));
+            t.cut();
+
+            assert_eq!(t.yank_text(), &"abc"[i..j], "from {i} to {j}");
+
+            let mut after = "abc".to_string();
+            after.replace_range(i..j, "");
+            let after = after.as_str();
+            assert_eq!(t.text().as_str(), after, "from {i} to {j}");
+            assert_eq!(t.cursor2(), (0, i));
+            assert_undo_redo((0, j), "abc", after, &mut t, (i, j));
+
+            t.paste();
+            assert_eq!(t.text().as_str(), "abc", "from {i} to {j}");
+            assert_undo_redo((0, i), after, "abc", &mut t, (i, j));
+        }
+    }
+}
+
+#[test]
+fn test_copy_cut_empty() {
+    for row in 0..=2 {
+        for col in 0..=2 {
+            let check = |f: fn(&mut MergeArea<'_>)| {
+                let mut t = MergeArea::with_value("ab\ncd\nef");
+                t.move_cursor(CursorMove::Jump(row, col));
+                t.start_selection();
+                t.move_cursor(CursorMove::Jump(row, col));
+                f(&mut t);
+                assert!(!t.is_selecting());
+                assert_eq!(t.cursor2(), (row as _, col as _));
+                assert_eq!(t.text().as_str(), "ab\ncd\nef");
+                assert_no_undo_redo(&mut t, "");
+            };
+
+            check(|t| {
+                assert!(!t.cut());
+            });
+            check(|t| t.copy());
+        }
+    }
+}
+
+#[test]
+fn test_copy_cut_paste_multi_lines() {
+    #[rustfmt::skip]
+    let tests = [
+        (
+            // Initial text
+            "ab\ncd\nef",
+            // Start position of selection
+            (0, 0),
+            // End position of selection
+            (1, 0),
+            // Expected yanked text
+            "ab\n",
+            // Text buffer after cut
+            "cd\nef"
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 0),
+            (1, 1),
+            "ab\nc",
+            "d\nef"
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 0),
+            (1, 2),
+            "ab\ncd",
+            "\nef"
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 0),
+            (2, 0),
+            "ab\ncd\n",
+            "ef"
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 0),
+            (2, 1),
+            "ab\ncd\ne",
+            "f"
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 0),
+            (2, 2),
+            "ab\ncd\nef",
+            ""
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 1),
+            (1, 1),
+            "b\nc",
+            "ad\nef"
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 2),
+            (1, 1),
+            "\nc",
+            "abd\nef"
+        ),
+        (
+            "ab\ncd\nef",
+            (1, 0),
+            (2, 1),
+            "cd\ne",
+            "ab\nf"
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 2),
+            (1, 0),
+            "\n",
+            "abcd\nef"
+        ),
+        (
+            "ab\ncd\nef",
+            (0, 2),
+            (2, 0),
+            "\ncd\n",
+            "abef"
+        ),
+        // Multi-byte characters
+        (
+            "あい\nうえ\nおか",
+            (0, 0),
+            (2, 2),
+            "あい\nうえ\nおか",
+            ""
+        ),
+        (
+            "あい\nうえ\nおか",
+            (0, 1),
+            (2, 1),
+            "い\nうえ\nお",
+            "あか"
+        ),
+        (
+            "あい\nうえ\nおか",
+            (0, 2),
+            (2, 0),
+            "\nうえ\n",
+            "あいおか"
+        ),
+        (
+            "あい\nうえ\nおか",
+            (0, 2),
+            (1, 2),
+            "\nうえ",
+            "あい\nおか"
+        ),
+        (
+            "あい\nうえ\nおか",
+            (0, 2),
+            (1, 1),
+            "\nう",
+            "あいえ\nおか"
+        ),
+        (
+            "あい\nうえ\nおか",
+            (0, 2),
+            (1, 0),
+            "\n",
+            "あいうえ\nおか"
+        ),
+    ];
+
+    for test in tests {
+        let (init_text, (srow, scol), (erow, ecol), yanked, after_cut) = test;
+
+        {
+            let mut t = MergeArea::with_value(init_text);
+            t.move_cursor(CursorMove::Jump(srow as _, scol as _));
+            t.start_selection();
+            t.move_cursor(CursorMove::Jump(erow as _, ecol as _));
+            t.copy();
+
+            assert_eq!(t.cursor2(), (erow, ecol), "{test:?}");
+            assert_eq!(t.yank_text(), yanked, "{test:?}");
+            assert_eq!(t.text().as_str(), init_text, "{test:?}");
+            assert_no_undo_redo(&mut t, test);
+        }
+
+        {
+            let mut t = MergeArea::with_value(init_text);
+            t.move_cursor(CursorMove::Jump(srow as _, scol as _));
+            t.start_selection();
+            t.move_cursor(CursorMove::Jump(erow as _, ecol as _));
+            t.cut();
+
+            assert_eq!(t.cursor2(), (srow, scol), "{test:?}");
+            assert_eq!(t.yank_text(), yanked, "{test:?}");
+            assert_eq!(t.text().as_str(), after_cut, "{test:?}");
+            assert_undo_redo((erow, ecol), init_text, after_cut, &mut t, test);
+
+            t.paste();
+            assert_eq!(t.text().as_str(), init_text, "{test:?}");
+            assert_undo_redo((srow, scol), after_cut, init_text, &mut t, test);
+        }
+
+        // Reverse positions
+        {
+            let mut t = MergeArea::with_value(init_text);
+            t.move_cursor(CursorMove::Jump(erow as _, ecol as _));
+            t.start_selection();
+            t.move_cursor(CursorMove::Jump(srow as _, scol as _));
+            t.copy();
+
+            assert_eq!(t.cursor2(), (srow, scol), "{test:?}");
+            assert_eq!(t.yank_text(), yanked, "{test:?}");
+            assert_eq!(t.text().as_str(), init_text, "{test:?}");
+            assert_no_undo_redo(&mut t, test);
+        }
+
+        {
+            let mut t = MergeArea::with_value(init_text);
+            t.move_cursor(CursorMove::Jump(erow as _, ecol as _));
+            t.start_selection();
+            t.move_cursor(CursorMove::Jump(srow as _, scol as _));
+            t.cut();
+
+            assert_eq!(t.cursor2(), (srow, scol), "{test:?}");
+            assert_eq!(t.yank_text(), yanked, "{test:?}");
+            assert_eq!(t.text().as_str(), after_cut, "{test:?}");
+            assert_undo_redo((srow, scol), init_text, after_cut, &mut t, test);
+
+            t.paste();
+            assert_eq!(t.text().as_str(), init_text, "{test:?}");
+            assert_undo_redo((srow, scol), after_cut, init_text, &mut t, test);
+        }
+    }
+}
 
 #[test]
 fn test_delete_selection_on_delete_operations() {
@@ -1342,8 +1231,8 @@ fn test_delete_selection_on_delete_operations() {
         test_case!(delete_next_char()),
         test_case!(delete_line_by_end()),
         test_case!(delete_line_by_head()),
-        // test_case!(delete_word()),
-        // test_case!(delete_next_word()),
+        test_case!(delete_word()),
+        test_case!(delete_next_word()),
         test_case!(delete_str(3)),
     ];
 
@@ -1384,10 +1273,10 @@ fn test_delete_selection_on_delete_edge_cases() {
         test_case!(delete_line_by_end(), (2, 2)),
         test_case!(delete_line_by_head(), (0, 0)),
         test_case!(delete_line_by_head(), (1, 0)),
-        // test_case!(delete_word(), (0, 0)),
-        // test_case!(delete_word(), (1, 0)),
-        // test_case!(delete_next_word(), (2, 2)),
-        // test_case!(delete_next_word(), (1, 2)),
+        test_case!(delete_word(), (0, 0)),
+        test_case!(delete_word(), (1, 0)),
+        test_case!(delete_next_word(), (2, 2)),
+        test_case!(delete_next_word(), (1, 2)),
         test_case!(delete_str(0), (0, 0)),
         test_case!(delete_str(100), (2, 2)),
     ];
@@ -1406,101 +1295,103 @@ fn test_delete_selection_on_delete_edge_cases() {
     }
 }
 
-// #[test]
-// fn test_delete_selection_before_insert() {
-//     macro_rules! test_case {
-//         ($name:ident($($args:expr),*), $want:expr) => {
-//             (
-//                 stringify!($name),
-//                 (|t| {
-//                     t.$name($($args),*);
-//                 }) as fn(&mut MergeArea),
-//                 &$want as &str,
-//             )
-//         };
-//     }
-
-//     let tests = [
-//         test_case!(insert_newline_v2(), "a\nf"),
-//         test_case!(insert_char_v2('x'), "axf"),
-//         test_case!(insert_tab_v2(), "a   f"), // Default tab is 4 spaces
-//         // test_case!(insert_str("xyz"), ["axyzf"]),
-//     ];
-
-//     for (n, f, after) in tests {
-//         let mut t = MergeArea::with_value("ab\ncd\nef");
-//         t.move_cursor(CursorMove::Jump(0, 1));
-//         t.start_selection_v2();
-//         t.move_cursor(CursorMove::Jump(2, 1));
-
-//         f(&mut t);
-//         assert_eq!(t.text().as_str(), after, "{n}");
-
-//         // XXX: Deleting selection and inserting text are separate undo units for now
-//         // t.undo();
-//         // t.undo();
-//         // assert_eq!(t.lines(), ["ab", "cd", "ef"], "{n}");
-//     }
-// }
-
-// #[test]
-// fn test_undo_redo_stop_selection() {
-//     fn check(t: &mut MergeArea, f: fn(&mut MergeArea) -> bool) {
-//         t.move_cursor(CursorMove::Jump(0, 0));
-//         t.start_selection();
-//         t.move_cursor(CursorMove::Jump(0, 1));
-//         assert!(t.is_selecting());
-//         assert!(f(t));
-//         assert!(!t.is_selecting());
-//     }
-
-//     let mut t = MergeArea::default();
-//     t.insert_char('a');
-
-//     check(&mut t, |t| t.undo());
-//     assert_eq!(t.lines(), [""]);
-//     check(&mut t, |t| t.redo());
-//     assert_eq!(t.lines(), ["a"]);
-// }
-
-// #[test]
-// fn test_set_yank_paste_text() {
-//     let tests = [
-//         ("", &[""][..], (0, 0)),
-//         ("abc", &["abc"][..], (0, 3)),
-//         ("abc\ndef", &["abc", "def"][..], (1, 3)),
-//         ("\n\n", &["", "", ""][..], (2, 0)),
-//     ];
-
-//     for test in tests {
-//         let (text, want, pos) = test;
-//         let mut t = MergeArea::default();
-//         t.set_yank_text(text);
-//         t.paste();
-//         assert_eq!(t.lines(), want, "{test:?}");
-//         assert_eq!(t.yank_text(), text, "{test:?}");
-//         assert_eq!(t.cursor(), pos, "{test:?}");
-//         assert_undo_redo((0, 0), &[""], want, &mut t, test);
-//     }
-// }
-
-// #[test]
-// fn test_set_yank_crlf() {
-//     let tests = [
-//         ("\r\n", &["", ""][..], "\n"),
-//         ("\r\n\r\n", &["", "", ""][..], "\n\n"),
-//         ("a\r\nb", &["a", "b"][..], "a\nb"),
-//         ("a\r\nb\r\n", &["a", "b", ""][..], "a\nb\n"),
-//     ];
-//     for test in tests {
-//         let (pasted, lines, yanked) = test;
-//         let mut t = MergeArea::default();
-//         t.set_yank_text(pasted);
-//         t.paste();
-//         assert_eq!(t.lines(), lines, "{test:?}");
-//         assert_eq!(t.yank_text(), yanked, "{test:?}");
-//     }
-// }
+#[test]
+fn test_delete_selection_before_insert() {
+    macro_rules! test_case {
+        ($name:ident($($args:expr),*), $want:expr) => {
+            (
+                stringify!($name),
+                (|t| {
+                    t.$name($($args),*);
+                }) as fn(&mut MergeArea),
+                &$want as &str,
+            )
+        };
+    }
+
+    let tests = [
+        test_case!(insert_newline(), "a\nf"),
+        test_case!(insert_char('x'), "axf"),
+        test_case!(insert_tab(), "a   f"), // Default tab is 4 spaces
+        test_case!(insert_str("xyz"), "axyzf"),
+    ];
+
+    for (n, f, after) in tests {
+        let mut t = MergeArea::with_value("ab\ncd\nef");
+        t.move_cursor(CursorMove::Jump(0, 1));
+        t.start_selection();
+        t.move_cursor(CursorMove::Jump(2, 1));
+
+        f(&mut t);
+        assert_eq!(t.text().as_str(), after, "{n}");
+
+        // Deleting the selection and inserting the text are undone separately
+        t.undo();
+        t.undo();
+        assert_eq!(t.text().as_str(), "ab\ncd\nef", "{n}");
+    }
+}
+
+#[test]
+fn test_undo_redo_stop_selection() {
+    fn check(t: &mut MergeArea, f: fn(&mut MergeArea) -> bool) {
+        t.move_cursor(CursorMove::Jump(0, 0));
+        t.start_selection();
+        t.move_cursor(CursorMove::Jump(0, 1));
+        assert!(t.is_selecting());
+        assert!(f(t));
+        assert!(!t.is_selecting());
+    }
+
+    let mut t = MergeArea::default();
+    t.insert_char('a');
+
+    check(&mut t, |t| t.undo());
+    assert_eq!(t.text().as_str(), "");
+    check(&mut t, |t| t.redo());
+    assert_eq!(t.text().as_str(), "a");
+}
+
+#[test]
+fn test_set_yank_paste_text() {
+    let tests = [
+        ("", (0, 0)),
+        ("abc", (0, 3)),
+        ("abc\ndef", (1, 3)),
+        ("\n\n", (2, 0)),
+        ("abc\n", (1, 0)),
+    ];
+
+    for test in tests {
+        let (text, pos) = test;
+        let mut t = MergeArea::default();
+        t.set_yank_text(text);
+        t.paste();
+        assert_eq!(t.text().as_str(), text, "{test:?}");
+        assert_eq!(t.yank_text(), text, "{test:?}");
+        assert_eq!(t.cursor2(), pos, "{test:?}");
+        assert_undo_redo((0, 0), "", text, &mut t, test);
+    }
+}
+
+#[test]
+fn test_set_yank_crlf() {
+    let tests = [
+        ("\r\n", "\n"),
+        ("\r\n\r\n", "\n\n"),
+        ("a\r\nb", "a\nb"),
+        ("a\r\nb\r\n", "a\nb\n"),
+        ("a\rb", "a\rb"), // Lone \r is not a newline
+    ];
+    for test in tests {
+        let (pasted, want) = test;
+        let mut t = MergeArea::default();
+        t.set_yank_text(pasted);
+        t.paste();
+        assert_eq!(t.text().as_str(), want, "{test:?}");
+        assert_eq!(t.yank_text(), want, "{test:?}");
+    }
+}
 
 #[test]
 fn test_select_all() {
@@ -1514,26 +1405,26 @@ fn test_select_all() {
     // assert_undo_redo((2, 3), "aaa\nbbb\nccc", "", &mut t, "");
 }
 
-// #[test]
-// fn test_paste_while_selection() {
-//     let mut t = MergeArea::from(["ab", "cd"]);
-//     t.move_cursor(CursorMove::Jump(0, 1));
-//     t.start_selection();
-//     t.move_cursor(CursorMove::Jump(1, 1));
-//     t.set_yank_text("x\ny");
-//     assert!(t.paste());
-//     assert_eq!(t.lines(), ["ax", "yd"]);
-//     assert_eq!(t.cursor(), (1, 1));
-//     assert!(!t.is_selecting());
-
-//     let mut t = MergeArea::from(["ab", "cd"]);
-//     t.select_all();
-//     t.set_yank_text("xy\nzw");
-//     assert!(t.paste());
-//     assert_eq!(t.lines(), ["xy", "zw"]);
-//     assert_eq!(t.cursor(), (1, 2));
-//     assert!(!t.is_selecting());
-// }
+#[test]
+fn test_paste_while_selection() {
+    let mut t = MergeArea::with_value("ab\ncd");
+    t.move_cursor(CursorMove::Jump(0, 1));
+    t.start_selection();
+    t.move_cursor(CursorMove::Jump(1, 1));
+    t.set_yank_text("x\ny");
+    assert!(t.paste());
+    assert_eq!(t.text().as_str(), "ax\nyd");
+    assert_eq!(t.cursor2(), (1, 1));
+    assert!(!t.is_selecting());
+
+    let mut t = MergeArea::with_value("ab\ncd");
+    t.select_all();
+    t.set_yank_text("xy\nzw");
+    assert!(t.paste());
+    assert_eq!(t.text().as_str(), "xy\nzw");
+    assert_eq!(t.cursor2(), (1, 2));
+    assert!(!t.is_selecting());
+}
 
 #[test]
 fn test_selection_range() {
@@ -1566,7 +1457,7 @@ fn test_selection_range() {
         let want = if from <= to { (from, to) } else { (to, from) };
         assert_eq!(have, want, "selection from {from:?} to {to:?}");
 
-        t.cancel_selection_v2();
+        t.cancel_selection();
         let range = t.selection_range();
         assert_eq!(range, None, "selection from {from:?} to {to:?}");
     }
@@ -1591,7 +1482,7 @@ impl DeleteTester {
         if modified {
             t.undo();
             assert_eq!(t.text().as_str(), buf_before);
-            t.redo_v2();
+            t.redo();
             assert_eq!(t.text().as_str(), buf_after);
         } else {
             assert_no_undo_redo(&mut t, "");
@@ -1644,34 +1535,46 @@ fn test_delete_line_by_head() {
     t.test((1, 0), (0, 7, "aaa bbbd", "")); // Newline is not yanked
 }
 
-// #[test]
-// fn test_delete_word() {
-//     let t = DeleteTester("word  ことば 🐶\n x", |t| t.delete_word());
-//     t.test((0, 0), (0, 0, t.0, ""));
-//     t.test((0, 2), (0, 0, "rd  ことば 🐶\n x", "wo"));
-//     t.test((0, 4), (0, 0, "  ことば 🐶\n x", "word"));
-//     t.test((0, 5), (0, 0, " ことば 🐶\n x", "word "));
-//     t.test((0, 6), (0, 0, "ことば 🐶\n x", "word  "));
-//     t.test((0, 7), (0, 6, "word  とば 🐶\n x", "こ"));
-//     t.test((0, 9), (0, 6, "word   🐶\n x", "ことば"));
-//     t.test((0, 10), (0, 6, "word  🐶\n x", "ことば "));
-//     t.test((0, 11), (0, 10, "word  ことば \n x", "🐶"));
-//     t.test((1, 0), (0, 11, "word  ことば 🐶 x", ""));
-//     t.test((1, 1), (1, 0, "word  ことば 🐶\nx", " "));
-//     t.test((1, 2), (1, 1, "word  ことば 🐶\n ", "x"));
-// }
-
-// #[test]
-// fn test_delete_next_word() {
-//     let t = DeleteTester(&["word  ことば 🐶", " x"], |t| t.delete_next_word());
-//     t.test((0, 0), (0, 0, &["  ことば 🐶", " x"], "word"));
-//     t.test((0, 2), (0, 2, &["wo  ことば 🐶", " x"], "rd"));
-//     t.test((0, 4), (0, 4, &["word 🐶", " x"], "  ことば"));
-//     t.test((0, 5), (0, 5, &["word  🐶", " x"], " ことば"));
-//     t.test((0, 6), (0, 6, &["word   🐶", " x"], "ことば"));
-//     t.test((0, 9), (0, 9, &["word  ことば", " x"], " 🐶"));
-//     t.test((0, 10), (0, 10, &["word  ことば ", " x"], "🐶"));
-//     t.test((0, 11), (0, 11, &["word  ことば 🐶 x"], ""));
-//     t.test((1, 0), (1, 0, &["word  ことば 🐶", ""], " x"));
-//     t.test((1, 2), (1, 2, t.0, ""));
-// }
+#[test]
+fn test_delete_word() {
+    let t = DeleteTester("word  ことば 🐶\n x", |t| t.delete_word());
+    t.test((0, 0), (0, 0, t.0, ""));
+    t.test((0, 2), (0, 0, "rd  ことば 🐶\n x", "wo"));
+    t.test((0, 4), (0, 0, "  ことば 🐶\n x", "word"));
+    t.test((0, 5), (0, 0, " ことば 🐶\n x", "word "));
+    t.test((0, 6), (0, 0, "ことば 🐶\n x", "word  "));
+    t.test((0, 7), (0, 6, "word  とば 🐶\n x", "こ"));
+    t.test((0, 9), (0, 6, "word   🐶\n x", "ことば"));
+    t.test((0, 10), (0, 6, "word  🐶\n x", "ことば "));
+    t.test((0, 11), (0, 10, "word  ことば \n x", "🐶"));
+    t.test((1, 0), (0, 11, "word  ことば 🐶 x", ""));
+    t.test((1, 1), (1, 0, "word  ことば 🐶\nx", " "));
+    t.test((1, 2), (1, 1, "word  ことば 🐶\n ", "x"));
+}
+
+#[test]
+fn test_delete_next_word() {
+    let t = DeleteTester("word  ことば 🐶\n x", |t| t.delete_next_word());
+    t.test((0, 0), (0, 0, "  ことば 🐶\n x", "word"));
+    t.test((0, 2), (0, 2, "wo  ことば 🐶\n x", "rd"));
+    t.test((0, 4), (0, 4, "word 🐶\n x", "  ことば"));
+    t.test((0, 5), (0, 5, "word  🐶\n x", " ことば"));
+    t.test((0, 6), (0, 6, "word   🐶\n x", "ことば"));
+    t.test((0, 9), (0, 9, "word  ことば\n x", " 🐶"));
+    t.test((0, 10), (0, 10, "word  ことば \n x", "🐶"));
+    t.test((0, 11), (0, 11, "word  ことば 🐶 x", ""));
+    t.test((1, 0), (1, 0, "word  ことば 🐶\n", " x"));
+    t.test((1, 2), (1, 2, t.0, ""));
+}
+
+#[test]
+fn test_undo_redo_multibyte() {
+    let mut t = MergeArea::with_value("🐱🐶");
+    t.move_cursor(CursorMove::Forward);
+    t.insert_str("あい");
+    assert_undo_redo((0, 1), "🐱🐶", "🐱あい🐶", &mut t, "insert");
+
+    t.move_cursor(CursorMove::End);
+    t.delete_char();
+    assert_undo_redo((0, 4), "🐱あい🐶", "🐱あい", &mut t, "delete");
+}
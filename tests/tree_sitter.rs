@@ -0,0 +1,112 @@
+#![cfg(feature = "tree-sitter")]
+
+mod common;
+
+use automerge::transaction::Transactable as _;
+use automerge::{ActorId, AutoCommit, ObjType, ROOT};
+use common::render;
+use ratatui::style::{Color, Style};
+use ratatui_mergearea::{CursorMove, MergeArea};
+use tree_sitter::Parser;
+
+fn style(name: &str) -> Style {
+    match name {
+        "keyword" => Style::default().fg(Color::Magenta),
+        "string" => Style::default().fg(Color::Green),
+        _ => Style::default(),
+    }
+}
+
+fn set_rust(t: &mut MergeArea<'_>) {
+    t.set_tree_sitter(
+        tree_sitter_rust::LANGUAGE.into(),
+        tree_sitter_rust::HIGHLIGHTS_QUERY,
+        style,
+    )
+    .unwrap();
+}
+
+// The incrementally updated tree must be the same as the tree parsed from scratch
+#[track_caller]
+fn assert_tree(t: &MergeArea<'_>) {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_rust::LANGUAGE.into())
+        .unwrap();
+    let want = parser.parse(t.text().as_str(), None).unwrap();
+    let have = t.syntax_tree().unwrap();
+    assert_eq!(have.root_node().to_sexp(), want.root_node().to_sexp());
+    assert_eq!(have.root_node().byte_range(), want.root_node().byte_range());
+}
+
+fn fg(t: &MergeArea<'_>, x: u16, y: u16) -> Color {
+    render(t, 20, 3)[(x, y)].fg
+}
+
+#[test]
+fn invalid_query() {
+    let mut t = MergeArea::default();
+    let err = t.set_tree_sitter(tree_sitter_rust::LANGUAGE.into(), "(nope) @x", style);
+    assert!(err.is_err());
+    assert!(t.syntax_tree().is_none());
+}
+
+#[test]
+fn follow_local_edits() {
+    let mut t = MergeArea::with_value("fn main() {\n}");
+    set_rust(&mut t);
+    assert_tree(&t);
+    assert_eq!(fg(&t, 1, 0), Color::Magenta); // The cursor is at (0, 0)
+
+    t.move_cursor(CursorMove::Down);
+    t.insert_str("    let s = \"あ\";\n");
+    assert_tree(&t);
+    assert_eq!(fg(&t, 4, 1), Color::Magenta);
+    assert_eq!(fg(&t, 12, 1), Color::Green);
+
+    t.move_cursor(CursorMove::Top);
+    t.delete_next_word();
+    assert_tree(&t);
+    assert_eq!(fg(&t, 1, 0), Color::Reset);
+
+    t.undo();
+    assert_tree(&t);
+    t.redo();
+    assert_tree(&t);
+
+    t.select_all();
+    t.cut();
+    assert_tree(&t);
+    assert!(t.text().as_str().is_empty());
+}
+
+#[test]
+fn follow_merged_edits() {
+    let mut doc = AutoCommit::new();
+    let obj = doc.put_object(ROOT, "text", ObjType::Text).unwrap();
+    doc.splice_text(&obj, 0, 0, "fn f() {}\n").unwrap();
+    let mut t = MergeArea::from_doc(doc, obj.clone()).unwrap();
+    set_rust(&mut t);
+
+    let mut remote = t.doc_mut().unwrap().fork().with_actor(ActorId::random());
+    t.reload();
+    remote
+        .splice_text(&obj, 10, 0, "const X: &str = \"x\";\n")
+        .unwrap();
+    remote.splice_text(&obj, 0, 2, "async fn").unwrap();
+    t.move_cursor(CursorMove::Jump(0, 8));
+    t.insert_char(' ');
+    t.merge(&mut remote).unwrap();
+
+    assert_eq!(
+        t.text().as_str(),
+        "async fn f() { }\nconst X: &str = \"x\";\n"
+    );
+    assert_tree(&t);
+    assert_eq!(fg(&t, 0, 1), Color::Magenta);
+    assert_eq!(fg(&t, 16, 1), Color::Green);
+
+    t.remove_tree_sitter();
+    assert!(t.syntax_tree().is_none());
+    assert_eq!(fg(&t, 0, 1), Color::Reset);
+}
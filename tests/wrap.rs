@@ -0,0 +1,96 @@
+mod common;
+
+use common::render_rows;
+use ratatui::style::Style;
+use ratatui_mergearea::{CursorMove, MergeArea, Scrolling, WrapMode};
+
+#[test]
+fn wrap_modes() {
+    let mut textarea = MergeArea::with_value("abc defgh\nij");
+
+    assert_eq!(render_rows(&textarea, 5, 3), ["abc d", "ij   ", "     "]);
+
+    textarea.set_wrap_mode(WrapMode::Char);
+    assert_eq!(render_rows(&textarea, 5, 3), ["abc d", "efgh ", "ij   "]);
+
+    textarea.set_wrap_mode(WrapMode::Word);
+    assert_eq!(render_rows(&textarea, 5, 3), ["abc  ", "defgh", "ij   "]);
+}
+
+#[test]
+fn rerender_after_changes() {
+    let mut textarea = MergeArea::with_value("abcdef");
+    textarea.set_wrap_mode(WrapMode::Char);
+    assert_eq!(render_rows(&textarea, 4, 2), ["abcd", "ef  "]);
+
+    // The rows follow the width, the text and the gutter which grows with the lines
+    assert_eq!(render_rows(&textarea, 3, 2), ["abc", "def"]);
+    textarea.move_cursor(CursorMove::End);
+    textarea.insert_str("\ngh");
+    assert_eq!(render_rows(&textarea, 3, 3), ["abc", "def", "gh "]);
+    textarea.set_line_number_style(Style::default());
+    assert_eq!(render_rows(&textarea, 6, 3), [" 1 abc", "   def", " 2 gh "]);
+    textarea.insert_str("\n".repeat(9));
+    textarea.move_cursor(CursorMove::Top);
+    assert_eq!(render_rows(&textarea, 7, 2), ["  1 abc", "    def"]);
+}
+
+#[test]
+fn line_numbers_on_first_row() {
+    let mut textarea = MergeArea::with_value("abcdef\ng");
+    textarea.set_line_number_style(Style::default());
+    textarea.set_wrap_mode(WrapMode::Char);
+
+    assert_eq!(
+        render_rows(&textarea, 6, 4),
+        [" 1 abc", "   def", " 2 g  ", "      "]
+    );
+}
+
+#[test]
+fn move_by_visual_rows() {
+    let mut textarea = MergeArea::with_value("abcdefghi\nij");
+    textarea.set_wrap_mode(WrapMode::Char);
+
+    // Before the first render the width is not known and the cursor moves by lines
+    textarea.move_cursor(CursorMove::Down);
+    assert_eq!(textarea.cursor2(), (1, 0));
+    textarea.move_cursor(CursorMove::Top);
+
+    render_rows(&textarea, 3, 4);
+    textarea.move_cursor(CursorMove::Forward);
+    for pos in [(0, 4), (0, 7), (1, 1)] {
+        textarea.move_cursor(CursorMove::Down);
+        assert_eq!(textarea.cursor2(), pos);
+    }
+    textarea.move_cursor(CursorMove::Down);
+    assert_eq!(textarea.cursor2(), (1, 1));
+
+    // The column is clamped to the last char of a wrapped row
+    textarea.move_cursor(CursorMove::Jump(0, 9));
+    for pos in [(0, 5), (0, 2)] {
+        textarea.move_cursor(CursorMove::Up);
+        assert_eq!(textarea.cursor2(), pos);
+    }
+    textarea.move_cursor(CursorMove::Up);
+    assert_eq!(textarea.cursor2(), (0, 2));
+}
+
+#[test]
+fn scroll_by_visual_rows() {
+    let mut textarea = MergeArea::with_value("abcdefghi\nj");
+    textarea.set_wrap_mode(WrapMode::Char);
+
+    render_rows(&textarea, 3, 2);
+    textarea.move_cursor(CursorMove::Bottom);
+    assert_eq!(render_rows(&textarea, 3, 2), ["ghi", "j  "]);
+
+    textarea.move_cursor(CursorMove::Top);
+    textarea.move_cursor(CursorMove::Forward);
+    assert_eq!(render_rows(&textarea, 3, 2), ["abc", "def"]);
+
+    // The cursor is kept in the rows of the viewport
+    textarea.scroll(Scrolling::Delta { rows: 2, cols: 0 });
+    assert_eq!(textarea.cursor2(), (0, 7));
+    assert_eq!(render_rows(&textarea, 3, 2), ["ghi", "j  "]);
+}